@@ -3,8 +3,9 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use cw900::ve_token::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, StakerResponse,
-    StateResponse,
+    BalancesResponse, BoostResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, PendingCheckpointResponse, QueryMsg, StakerHistoryResponse, StakerLocksResponse,
+    StakerResponse, StakersResponse, StateResponse, WhitelistResponse,
 };
 
 fn main() {
@@ -21,4 +22,11 @@ fn main() {
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(StakerResponse), &out_dir);
+    export_schema(&schema_for!(StakerLocksResponse), &out_dir);
+    export_schema(&schema_for!(StakersResponse), &out_dir);
+    export_schema(&schema_for!(StakerHistoryResponse), &out_dir);
+    export_schema(&schema_for!(PendingCheckpointResponse), &out_dir);
+    export_schema(&schema_for!(BoostResponse), &out_dir);
+    export_schema(&schema_for!(BalancesResponse), &out_dir);
+    export_schema(&schema_for!(WhitelistResponse), &out_dir);
 }
@@ -1,29 +1,80 @@
-#[cfg(not(feature = "library"))]
 use crate::error::ContractError;
 
 use crate::staking::{
-    apply_pending_slope_changes_to_state, apply_pending_slope_changes_to_state_and_save_updates,
-    send_tokens, update_user_lock,
+    apply_pending_slope_changes_to_state, checkpoint_state, pending_weeks, send_tokens,
+    token_transfer_msg, update_user_lock,
 };
 use crate::state::{
-    Config, State, UserLockedBalance, CONFIG, MAX_SECONDS, MAX_WEEKS, SECONDS_PER_WEEK, STATE,
-    USER_LOCKED_BALANCES,
+    load_user_locked_balance, migrate_legacy_user_locked_balances,
+    read_all_user_locked_balances_at_timestamp, read_user_lock_history,
+    read_user_locked_balances_at_timestamp, read_whitelisted_contracts, user_has_active_lock,
+    user_has_lock, user_voting_power_at_timestamp, Config, State, UserLockedBalance, CONFIG,
+    DEFAULT_MAX_WEEKS, DEFAULT_SECONDS_PER_WEEK, DEFAULT_TOKENLESS_PRODUCTION_PERCENT,
+    DEFAULT_VOTING_POWER_CONSTANT_DIVISOR, DELEGATED_TO, DELEGATORS, LEGACY_CONFIG,
+    MAX_DELEGATORS_PER_DELEGATE, MAX_LOCKS_PER_USER, MAX_LOCK_DURATION, MAX_PENDING_WEEKS,
+    REMOVED_DELEGATORS, STATE, USER_LOCK_COUNT, WHITELISTED_CONTRACTS,
 };
+#[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, QuerierWrapper,
+    Response, StdError, StdResult, Storage, Uint128,
 };
 use cw20::Cw20ReceiveMsg;
 
+use cw900::gauge_controller::{
+    QueryMsg as GaugeControllerQueryMsg, UserVotesResponse as GaugeControllerUserVotesResponse,
+};
 use cw900::ve_token::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, StakerResponse,
-    StateResponse,
+    BalancesResponse, BoostResponse, ConfigResponse, Cw20HookMsg, DecayModel, ExecuteMsg,
+    InstantiateMsg, LockAction, LockCheckpointResponse, LockResponse, LockStatus, MigrateMsg,
+    PendingCheckpointResponse, QueryMsg, StakerHistoryResponse, StakerLocksResponse,
+    StakerResponse, StakersResponse, StakersResponseItem, StateResponse, WhitelistResponse,
 };
+use std::convert::TryFrom;
+use terra_cosmwasm::TerraQuerier;
+
+/// Return whether or not an address belongs to a contract.
+/// Contract info only exists for contract addresses, so the query fails for everything else.
+pub fn is_contract(querier: &QuerierWrapper, addr: &Addr) -> bool {
+    TerraQuerier::new(querier)
+        .query_contract_info(addr.to_string())
+        .is_ok()
+}
 
-pub fn is_contract(_addr: &Addr) -> bool {
-    false
+/// Validate that an address is allowed to create, modify, or withdraw locks.
+/// Contracts are only allowed to do so if the owner has whitelisted them.
+pub fn assert_can_interact_with_locks(deps: Deps, addr: &Addr) -> Result<(), ContractError> {
+    if !WHITELISTED_CONTRACTS.has(deps.storage, addr) && is_contract(&deps.querier, addr) {
+        return Err(ContractError::ContractsCannotInteractWithLocks {});
+    }
+
+    Ok(())
+}
+
+/// Validate that the address hasn't allocated voting power in the gauge controller.
+/// Gauge votes follow the voter's locks as they were at the time of the vote,
+/// so transferring, merging or withdrawing a lock early would let the votes outlive the lock.
+pub fn assert_no_gauge_votes(
+    deps: Deps,
+    config: &Config,
+    addr: &Addr,
+) -> Result<(), ContractError> {
+    if let Some(gauge_controller) = &config.gauge_controller {
+        let res: GaugeControllerUserVotesResponse = deps.querier.query_wasm_smart(
+            gauge_controller,
+            &GaugeControllerQueryMsg::UserVotes {
+                address: addr.to_string(),
+            },
+        )?;
+
+        if res.power_used_bps > 0 {
+            return Err(ContractError::LockHasGaugeVotes {});
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -31,17 +82,35 @@ pub fn instantiate(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let config = Config {
         cw20_address: None,
         owner: deps.api.addr_validate(info.sender.as_str())?,
+        pending_owner: None,
+        seconds_per_week: msg.seconds_per_week.unwrap_or(DEFAULT_SECONDS_PER_WEEK),
+        max_weeks: msg.max_weeks.unwrap_or(DEFAULT_MAX_WEEKS),
+        voting_power_constant_divisor: msg
+            .voting_power_constant_divisor
+            .unwrap_or(DEFAULT_VOTING_POWER_CONSTANT_DIVISOR),
+        decay_model: msg.decay_model.unwrap_or(DecayModel::LinearUnlock),
+        penalty_recipient: None,
+        early_withdraw_penalty_rate: Decimal::zero(),
+        tokenless_production: Decimal::percent(DEFAULT_TOKENLESS_PRODUCTION_PERCENT),
+        gauge_controller: None,
+        fee_distributor: None,
     };
 
+    validate_curve_parameters(&config)?;
+
     CONFIG.save(deps.storage, &config)?;
 
     // Save an initial default state
-    let state = State::default();
+    // which is up to date as of instantiation
+    let state = State {
+        timestamp: env.block.time.seconds(),
+        ..State::default()
+    };
     STATE.save(deps.storage, &state, env.block.time.seconds())?;
 
     Ok(Response::default())
@@ -56,13 +125,57 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
-        ExecuteMsg::Withdraw {} => execute_withdraw(deps, env, info),
-        ExecuteMsg::IncreaseEndLockTime { end_lock_time } => {
-            execute_increase_end_lock_time(deps, env, info, end_lock_time)
+        ExecuteMsg::Withdraw { lock_id } => execute_withdraw(deps, env, info, lock_id),
+        ExecuteMsg::EarlyWithdraw { lock_id } => execute_early_withdraw(deps, env, info, lock_id),
+        ExecuteMsg::IncreaseEndLockTime {
+            lock_id,
+            end_lock_time,
+        } => execute_increase_end_lock_time(deps, env, info, lock_id, end_lock_time),
+        ExecuteMsg::Checkpoint { max_weeks } => {
+            execute_global_checkpoint(deps, env, info, max_weeks)
         }
-        ExecuteMsg::Checkpoint {} => execute_global_checkpoint(deps, env, info),
         ExecuteMsg::RegisterContracts { cw20_address } => {
-            execute_register_contracts(deps, cw20_address)
+            execute_register_contracts(deps, info, cw20_address)
+        }
+        ExecuteMsg::UpdateConfig {
+            cw20_address,
+            penalty_recipient,
+            early_withdraw_penalty_rate,
+            tokenless_production,
+            gauge_controller,
+            fee_distributor,
+        } => execute_update_config(
+            deps,
+            info,
+            cw20_address,
+            penalty_recipient,
+            early_withdraw_penalty_rate,
+            tokenless_production,
+            gauge_controller,
+            fee_distributor,
+        ),
+        ExecuteMsg::ProposeNewOwner { new_owner } => {
+            execute_propose_new_owner(deps, info, new_owner)
+        }
+        ExecuteMsg::CancelOwnershipProposal {} => execute_cancel_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::TransferLock { lock_id, recipient } => {
+            execute_transfer_lock(deps, env, info, lock_id, recipient)
+        }
+        ExecuteMsg::MergeLock {
+            lock_id,
+            other_lock_id,
+        } => execute_merge_lock(deps, env, info, lock_id, other_lock_id),
+        ExecuteMsg::Delegate { to } => execute_delegate(deps, env, info, to),
+        ExecuteMsg::Undelegate {} => execute_undelegate(deps, env, info),
+        ExecuteMsg::RemoveDelegator { delegator } => {
+            execute_remove_delegator(deps, env, info, delegator)
+        }
+        ExecuteMsg::AddToWhitelist { contract_address } => {
+            execute_add_to_whitelist(deps, info, contract_address)
+        }
+        ExecuteMsg::RemoveFromWhitelist { contract_address } => {
+            execute_remove_from_whitelist(deps, info, contract_address)
         }
     }
 }
@@ -95,14 +208,23 @@ pub fn receive_cw20(
             )
         }
 
-        Ok(Cw20HookMsg::IncreaseLockAmount {}) => {
-            let api = deps.api;
-            execute_increase_lock_amount(
-                deps,
-                env,
-                api.addr_validate(&cw20_msg.sender)?,
-                cw20_msg.amount,
-            )
+        Ok(Cw20HookMsg::IncreaseLockAmount { lock_id, address }) => {
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+
+            // Only the fee distributor can add to another staker's lock,
+            // otherwise anyone could reset the start_lock_time of a lock by adding dust to it
+            let user = match address {
+                Some(address) => {
+                    let address = deps.api.addr_validate(&address)?;
+                    if address != sender && config.fee_distributor.as_ref() != Some(&sender) {
+                        return Err(ContractError::Unauthorized {});
+                    }
+                    address
+                }
+                None => sender,
+            };
+
+            execute_increase_lock_amount(deps, env, user, lock_id, cw20_msg.amount)
         }
         _ => Err(ContractError::DataShouldBeGiven {}),
     }
@@ -112,16 +234,22 @@ pub fn execute_global_checkpoint(
     deps: DepsMut,
     env: Env,
     _info: MessageInfo,
+    max_weeks: Option<u64>,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
 
-    apply_pending_slope_changes_to_state_and_save_updates(
+    let remaining_weeks = checkpoint_state(
         deps.storage,
         &mut state,
         env.block.time.seconds(),
+        max_weeks.unwrap_or(MAX_PENDING_WEEKS),
     )?;
 
-    Ok(Response::new())
+    Ok(Response::new().add_attributes(vec![
+        ("action", "checkpoint"),
+        ("state_timestamp", state.timestamp.to_string().as_str()),
+        ("remaining_weeks", remaining_weeks.to_string().as_str()),
+    ]))
 }
 
 pub fn execute_create_lock(
@@ -131,21 +259,17 @@ pub fn execute_create_lock(
     amount: Uint128,
     end_lock_time: u64,
 ) -> Result<Response, ContractError> {
-    let end_lock_time = end_lock_time / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    // Validate that the address is not a contract
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
 
-    let prev_user_locked_balance = USER_LOCKED_BALANCES
-        .may_load(deps.storage, &user)?
-        .unwrap_or_default();
+    let config = CONFIG.load(deps.storage)?;
 
-    // Validate that the address is not a contract
-    if is_contract(&user) {
-        return Err(ContractError::ContractsCannotInteractWithLocks {});
-    }
+    let end_lock_time = end_lock_time / config.seconds_per_week * config.seconds_per_week;
 
-    // Validate that the old lock is finished
-    if prev_user_locked_balance.exists() {
-        return Err(ContractError::LockAlreadyExists {});
-    }
+    // Find a lock id for the new lock, reusing those of void locks
+    let lock_id = next_available_lock_id(deps.storage, &user)?;
+
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
 
     // Validate that the new lock is positive
     if amount == Uint128::zero() {
@@ -158,10 +282,11 @@ pub fn execute_create_lock(
     }
 
     // Validate that the unlock week isn't too far in the future
-    if end_lock_time > env.block.time.seconds() + MAX_SECONDS {
+    if end_lock_time > env.block.time.seconds() + config.max_seconds() {
         return Err(ContractError::EndLockTimeTooLate {
-            max_weeks: MAX_WEEKS,
-            lock_duration_in_weeks: (end_lock_time - env.block.time.seconds()) / MAX_WEEKS,
+            max_weeks: config.max_weeks,
+            lock_duration_in_weeks: (end_lock_time - env.block.time.seconds())
+                / config.seconds_per_week,
         });
     }
 
@@ -179,34 +304,59 @@ pub fn execute_create_lock(
     update_user_lock(
         deps.storage,
         &user,
+        lock_id,
         prev_user_locked_balance,
         new_user_locked_balance,
+        LockAction::CreateLock,
     )?;
 
     Ok(Response::new().add_attributes(vec![
         ("action", "create_lock"),
         ("user", user.as_str()),
+        ("lock_id", lock_id.to_string().as_str()),
         ("amount", amount.to_string().as_str()),
     ]))
 }
 
+/// Return the id of the first void or undefined lock of a user.
+/// If all of the user's lock ids are in use, allocate a new one.
+fn next_available_lock_id(storage: &mut dyn Storage, user: &Addr) -> Result<u64, ContractError> {
+    let lock_count = USER_LOCK_COUNT.may_load(storage, user)?.unwrap_or_default();
+
+    for lock_id in 0..lock_count {
+        if load_user_locked_balance(storage, user, lock_id)?.is_void_or_undefined() {
+            return Ok(lock_id);
+        }
+    }
+
+    if lock_count >= MAX_LOCKS_PER_USER {
+        return Err(ContractError::TooManyLocks {
+            max_locks: MAX_LOCKS_PER_USER,
+        });
+    }
+
+    USER_LOCK_COUNT.save(storage, user, &(lock_count + 1))?;
+
+    Ok(lock_count)
+}
+
 pub fn execute_increase_end_lock_time(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    lock_id: u64,
     new_end_lock_time: u64,
 ) -> Result<Response, ContractError> {
     let user = info.sender;
-    let new_end_lock_time = new_end_lock_time / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
-
-    let prev_user_locked_balance = USER_LOCKED_BALANCES
-        .may_load(deps.storage, &user)?
-        .unwrap_or_default();
 
     // Validate that the address is not a contract
-    if is_contract(&user) {
-        return Err(ContractError::ContractsCannotInteractWithLocks {});
-    }
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let new_end_lock_time = new_end_lock_time / config.seconds_per_week * config.seconds_per_week;
+
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
 
     // Validate that the lock exists
     if prev_user_locked_balance.is_void_or_undefined() {
@@ -224,10 +374,11 @@ pub fn execute_increase_end_lock_time(
     }
 
     // Validate that you aren't increasing the lock period too far
-    if new_end_lock_time > env.block.time.seconds() + MAX_SECONDS {
+    if new_end_lock_time > env.block.time.seconds() + config.max_seconds() {
         return Err(ContractError::EndLockTimeTooLate {
-            max_weeks: MAX_WEEKS,
-            lock_duration_in_weeks: (new_end_lock_time - env.block.time.seconds()) / MAX_WEEKS,
+            max_weeks: config.max_weeks,
+            lock_duration_in_weeks: (new_end_lock_time - env.block.time.seconds())
+                / config.seconds_per_week,
         });
     }
 
@@ -245,8 +396,10 @@ pub fn execute_increase_end_lock_time(
     update_user_lock(
         deps.storage,
         &user,
+        lock_id,
         prev_user_locked_balance,
         new_user_locked_balance,
+        LockAction::IncreaseEndLockTime,
     )?;
 
     Ok(Response::new())
@@ -256,16 +409,13 @@ pub fn execute_increase_lock_amount(
     deps: DepsMut,
     env: Env,
     user: Addr,
+    lock_id: u64,
     increase_amount: Uint128,
 ) -> Result<Response, ContractError> {
     // Validate that the address is not a contract
-    if is_contract(&user) {
-        return Err(ContractError::ContractsCannotInteractWithLocks {});
-    }
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
 
-    let prev_user_locked_balance = USER_LOCKED_BALANCES
-        .may_load(deps.storage, &user)?
-        .unwrap_or_default();
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
 
     // Validate that a lock exists
     if prev_user_locked_balance.is_void_or_undefined() {
@@ -296,8 +446,10 @@ pub fn execute_increase_lock_amount(
     update_user_lock(
         deps.storage,
         &user,
+        lock_id,
         prev_user_locked_balance,
         new_user_locked_balance,
+        LockAction::IncreaseLockAmount,
     )?;
 
     Ok(Response::new())
@@ -307,18 +459,17 @@ pub fn execute_withdraw(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    lock_id: u64,
 ) -> Result<Response, ContractError> {
     let user = info.sender;
 
     // Validate that the address is not a contract
-    if is_contract(&user) {
-        return Err(ContractError::ContractsCannotInteractWithLocks {});
-    }
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+
+    let config = CONFIG.load(deps.storage)?;
 
     // Get the user locked balance
-    let prev_user_locked_balance = USER_LOCKED_BALANCES
-        .may_load(deps.storage, &user)?
-        .unwrap_or_default();
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
 
     // Validate that the lock isn't void
     if prev_user_locked_balance.is_void_or_undefined() {
@@ -339,13 +490,19 @@ pub fn execute_withdraw(
 
         // Get the locked_amount at the current timestamp
         let locked_amount =
-            prev_user_locked_balance.locked_amount_at_timestamp(env.block.time.seconds());
+            prev_user_locked_balance.locked_amount_at_timestamp(&config, env.block.time.seconds());
 
         // The amount available to be withdrawn
         let unlocked_amount = prev_user_locked_balance.deposited_amount - locked_amount;
 
         withdrawn_amount = unlocked_amount;
 
+        // Validate that something is available to be withdrawn,
+        // which is never the case before expiry with the cliff unlock decay model
+        if withdrawn_amount.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+
         if withdrawn_amount == prev_user_locked_balance.deposited_amount {
             // Set the new user locked balance to be zeroed out
             // if the withdrawn amount happens to be the entire deposit amount
@@ -368,12 +525,12 @@ pub fn execute_withdraw(
     update_user_lock(
         deps.storage,
         &user,
+        lock_id,
         prev_user_locked_balance,
         new_user_locked_balance,
+        LockAction::Withdraw,
     )?;
 
-    let config = CONFIG.load(deps.storage)?;
-
     let cw20_address = if let Some(cw20_address) = config.cw20_address {
         cw20_address
     } else {
@@ -383,12 +540,417 @@ pub fn execute_withdraw(
     send_tokens(&cw20_address, &user, withdrawn_amount, "withdraw")
 }
 
+pub fn execute_early_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+) -> Result<Response, ContractError> {
+    let user = info.sender;
+
+    // Validate that the address is not a contract
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let penalty_recipient = if let Some(penalty_recipient) = config.penalty_recipient.clone() {
+        penalty_recipient
+    } else {
+        return Err(ContractError::EarlyWithdrawDisabled {});
+    };
+
+    let cw20_address = if let Some(cw20_address) = config.cw20_address.clone() {
+        cw20_address
+    } else {
+        return Err(ContractError::ConfigContractsNotRegistered {});
+    };
+
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
+
+    // Validate that the lock isn't void
+    if prev_user_locked_balance.is_void_or_undefined() {
+        return Err(ContractError::LockDoesNotExist {});
+    }
+
+    // Validate that the lock isn't expired, expired locks should be withdrawn normally
+    if prev_user_locked_balance.expired_at_timestamp(env.block.time.seconds()) {
+        return Err(ContractError::LockIsExpired {});
+    }
+
+    assert_no_gauge_votes(deps.as_ref(), &config, &user)?;
+
+    // The penalty is only taken from the locked part of the deposit
+    let penalty_amount = prev_user_locked_balance
+        .early_withdraw_penalty_at_timestamp(&config, env.block.time.seconds());
+    let withdrawn_amount = prev_user_locked_balance.deposited_amount - penalty_amount;
+
+    // Propogate the changes
+    update_user_lock(
+        deps.storage,
+        &user,
+        lock_id,
+        prev_user_locked_balance,
+        UserLockedBalance::void_lock_with_timestamp(env.block.time.seconds()),
+        LockAction::EarlyWithdraw,
+    )?;
+
+    let mut messages = vec![];
+    if !withdrawn_amount.is_zero() {
+        messages.push(token_transfer_msg(&cw20_address, &user, withdrawn_amount)?);
+    }
+    if !penalty_amount.is_zero() {
+        messages.push(token_transfer_msg(
+            &cw20_address,
+            &penalty_recipient,
+            penalty_amount,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "early_withdraw"),
+        ("user", user.as_str()),
+        ("lock_id", lock_id.to_string().as_str()),
+        ("amount", withdrawn_amount.to_string().as_str()),
+        ("penalty_amount", penalty_amount.to_string().as_str()),
+    ]))
+}
+
+pub fn execute_transfer_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let user = info.sender;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    // Validate that neither the sender nor the recipient are contracts
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+    assert_can_interact_with_locks(deps.as_ref(), &recipient)?;
+
+    if user == recipient {
+        return Err(ContractError::CannotTransferToSelf {});
+    }
+
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
+
+    // Validate that the lock exists
+    if prev_user_locked_balance.is_void_or_undefined() {
+        return Err(ContractError::LockDoesNotExist {});
+    }
+
+    // Validate that the lock isn't expired, expired locks should be withdrawn instead
+    if prev_user_locked_balance.expired_at_timestamp(env.block.time.seconds()) {
+        return Err(ContractError::LockIsExpired {});
+    }
+
+    // Validate that the recipient doesn't hold a lock,
+    // so that unsolicited transfers can't use up the recipient's lock ids
+    if user_has_lock(deps.storage, &recipient)? {
+        return Err(ContractError::RecipientHasLock {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    assert_no_gauge_votes(deps.as_ref(), &config, &user)?;
+
+    // Find a lock id for the lock at the recipient
+    let recipient_lock_id = next_available_lock_id(deps.storage, &recipient)?;
+    let prev_recipient_locked_balance =
+        load_user_locked_balance(deps.storage, &recipient, recipient_lock_id)?;
+
+    // The transferred lock keeps its deposit and lock times
+    let new_recipient_locked_balance = UserLockedBalance {
+        // History tracking info
+        timestamp: env.block.time.seconds(),
+        ..prev_user_locked_balance.clone()
+    };
+
+    // Propogate the changes, first removing the lock from the sender
+    // and then adding it to the recipient
+    update_user_lock(
+        deps.storage,
+        &user,
+        lock_id,
+        prev_user_locked_balance,
+        UserLockedBalance::void_lock_with_timestamp(env.block.time.seconds()),
+        LockAction::TransferLock,
+    )?;
+
+    update_user_lock(
+        deps.storage,
+        &recipient,
+        recipient_lock_id,
+        prev_recipient_locked_balance,
+        new_recipient_locked_balance,
+        LockAction::ReceiveLock,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "transfer_lock"),
+        ("user", user.as_str()),
+        ("lock_id", lock_id.to_string().as_str()),
+        ("recipient", recipient.as_str()),
+        ("recipient_lock_id", recipient_lock_id.to_string().as_str()),
+    ]))
+}
+
+pub fn execute_merge_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+    other_lock_id: u64,
+) -> Result<Response, ContractError> {
+    let user = info.sender;
+
+    // Validate that the address is not a contract
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+
+    if lock_id == other_lock_id {
+        return Err(ContractError::CannotMergeLockWithItself {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+
+    let prev_user_locked_balance = load_user_locked_balance(deps.storage, &user, lock_id)?;
+    let prev_other_locked_balance = load_user_locked_balance(deps.storage, &user, other_lock_id)?;
+
+    for locked_balance in [&prev_user_locked_balance, &prev_other_locked_balance] {
+        // Validate that the lock exists
+        if locked_balance.is_void_or_undefined() {
+            return Err(ContractError::LockDoesNotExist {});
+        }
+
+        // Validate that the lock isn't expired
+        if locked_balance.expired_at_timestamp(current_time) {
+            return Err(ContractError::LockIsExpired {});
+        }
+    }
+
+    assert_no_gauge_votes(deps.as_ref(), &config, &user)?;
+
+    let deposited_amount =
+        prev_user_locked_balance.deposited_amount + prev_other_locked_balance.deposited_amount;
+    let locked_amount = prev_user_locked_balance.locked_amount_at_timestamp(&config, current_time)
+        + prev_other_locked_balance.locked_amount_at_timestamp(&config, current_time);
+    let end_lock_time = prev_user_locked_balance
+        .end_lock_time
+        .max(prev_other_locked_balance.end_lock_time);
+
+    // Pick the start_lock_time so that the merged lock currently has
+    // the same locked amount as the two locks combined:
+    // locked_amount = deposited_amount * (end_lock_time - t) / (end_lock_time - start_lock_time)
+    // Flooring the lock duration can only increase the locked amount.
+    // The lock duration is capped so that the start_lock_time stays positive.
+    let lock_duration = u64::try_from(
+        deposited_amount
+            .multiply_ratio(end_lock_time - current_time, locked_amount)
+            .u128(),
+    )
+    .unwrap_or(u64::MAX)
+    .min(end_lock_time - 1);
+
+    let new_user_locked_balance = UserLockedBalance {
+        // Locked balance info
+        deposited_amount,
+        end_lock_time,
+        start_lock_time: end_lock_time - lock_duration,
+        // History tracking info
+        timestamp: current_time,
+    };
+
+    // Propogate the changes, first voiding the other lock
+    // and then replacing the lock with the merged lock
+    update_user_lock(
+        deps.storage,
+        &user,
+        other_lock_id,
+        prev_other_locked_balance,
+        UserLockedBalance::void_lock_with_timestamp(current_time),
+        LockAction::MergeLock,
+    )?;
+
+    update_user_lock(
+        deps.storage,
+        &user,
+        lock_id,
+        prev_user_locked_balance,
+        new_user_locked_balance,
+        LockAction::MergeLock,
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "merge_lock"),
+        ("user", user.as_str()),
+        ("lock_id", lock_id.to_string().as_str()),
+        ("other_lock_id", other_lock_id.to_string().as_str()),
+    ]))
+}
+
+pub fn execute_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: String,
+) -> Result<Response, ContractError> {
+    let user = info.sender;
+    let delegate = deps.api.addr_validate(&to)?;
+
+    // Validate that neither the delegator nor the delegate are contracts
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+    assert_can_interact_with_locks(deps.as_ref(), &delegate)?;
+
+    if user == delegate {
+        return Err(ContractError::CannotDelegateToSelf {});
+    }
+
+    // Validate that the user has voting power to delegate,
+    // so that delegates can't be flooded with empty delegations
+    if !user_has_active_lock(deps.storage, &user, env.block.time.seconds())? {
+        return Err(ContractError::NoActiveLock {});
+    }
+
+    let prev_delegate = DELEGATED_TO.may_load(deps.storage, &user)?;
+    if prev_delegate.as_ref() == Some(&delegate) {
+        return Err(ContractError::AlreadyDelegated {});
+    }
+
+    if REMOVED_DELEGATORS.has(deps.storage, (&delegate, &user)) {
+        return Err(ContractError::DelegatorRemoved {});
+    }
+
+    let delegators = DELEGATORS
+        .may_load(deps.storage, &delegate)?
+        .unwrap_or_default();
+
+    // Drop the delegators whose locks have expired or been withdrawn,
+    // so that they don't hold on to the delegate's slots
+    let mut active_delegators = vec![];
+    for delegator in delegators {
+        if user_has_active_lock(deps.storage, &delegator, env.block.time.seconds())? {
+            active_delegators.push(delegator);
+        } else {
+            DELEGATED_TO.remove(deps.storage, &delegator, env.block.time.seconds())?;
+        }
+    }
+    let mut delegators = active_delegators;
+
+    // Validate that the delegate's voting power can still be summed cheaply
+    if delegators.len() as u64 >= MAX_DELEGATORS_PER_DELEGATE {
+        return Err(ContractError::TooManyDelegators {
+            max_delegators: MAX_DELEGATORS_PER_DELEGATE,
+        });
+    }
+
+    // Remove the user from the delegators of the previous delegate
+    if let Some(prev_delegate) = prev_delegate {
+        remove_delegator(
+            deps.storage,
+            &prev_delegate,
+            &user,
+            env.block.time.seconds(),
+        )?;
+    }
+
+    delegators.push(user.clone());
+    DELEGATORS.save(
+        deps.storage,
+        &delegate,
+        &delegators,
+        env.block.time.seconds(),
+    )?;
+
+    DELEGATED_TO.save(deps.storage, &user, &delegate, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "delegate"),
+        ("user", user.as_str()),
+        ("delegate", delegate.as_str()),
+    ]))
+}
+
+pub fn execute_undelegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let user = info.sender;
+
+    // Validate that the address is not a contract
+    assert_can_interact_with_locks(deps.as_ref(), &user)?;
+
+    let delegate = if let Some(delegate) = DELEGATED_TO.may_load(deps.storage, &user)? {
+        delegate
+    } else {
+        return Err(ContractError::NotDelegated {});
+    };
+
+    remove_delegator(deps.storage, &delegate, &user, env.block.time.seconds())?;
+    DELEGATED_TO.remove(deps.storage, &user, env.block.time.seconds())?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "undelegate"),
+        ("user", user.as_str()),
+        ("delegate", delegate.as_str()),
+    ]))
+}
+
+/// Remove one of the sender's delegators, which can't delegate to the sender again
+pub fn execute_remove_delegator(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegator: String,
+) -> Result<Response, ContractError> {
+    let delegate = info.sender;
+    let delegator = deps.api.addr_validate(&delegator)?;
+
+    if DELEGATED_TO.may_load(deps.storage, &delegator)?.as_ref() != Some(&delegate) {
+        return Err(ContractError::NotDelegated {});
+    }
+
+    remove_delegator(
+        deps.storage,
+        &delegate,
+        &delegator,
+        env.block.time.seconds(),
+    )?;
+    DELEGATED_TO.remove(deps.storage, &delegator, env.block.time.seconds())?;
+    REMOVED_DELEGATORS.save(deps.storage, (&delegate, &delegator), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_delegator"),
+        ("delegate", delegate.as_str()),
+        ("delegator", delegator.as_str()),
+    ]))
+}
+
+/// Remove a delegator from the list of delegators of a delegate
+fn remove_delegator(
+    storage: &mut dyn Storage,
+    delegate: &Addr,
+    delegator: &Addr,
+    timestamp: u64,
+) -> StdResult<()> {
+    let mut delegators = DELEGATORS.may_load(storage, delegate)?.unwrap_or_default();
+    delegators.retain(|addr| addr != delegator);
+
+    if delegators.is_empty() {
+        DELEGATORS.remove(storage, delegate, timestamp)
+    } else {
+        DELEGATORS.save(storage, delegate, &delegators, timestamp)
+    }
+}
+
 pub fn execute_register_contracts(
     deps: DepsMut,
+    info: MessageInfo,
     cw20_address: String,
 ) -> Result<Response, ContractError> {
     let mut config: Config = CONFIG.load(deps.storage)?;
-    if config.cw20_address != None {
+    if info.sender != config.owner || config.cw20_address.is_some() {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -398,6 +960,205 @@ pub fn execute_register_contracts(
     Ok(Response::default())
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20_address: Option<String>,
+    penalty_recipient: Option<String>,
+    early_withdraw_penalty_rate: Option<Decimal>,
+    tokenless_production: Option<Decimal>,
+    gauge_controller: Option<String>,
+    fee_distributor: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(cw20_address) = cw20_address {
+        // Changing the token while deposits exist would leave them unwithdrawable
+        let state = STATE.load(deps.storage)?;
+        if !state.total_deposit.is_zero() {
+            return Err(ContractError::CannotUpdateTokenWithDeposits {});
+        }
+
+        config.cw20_address = Some(deps.api.addr_validate(&cw20_address)?);
+    }
+
+    if let Some(penalty_recipient) = penalty_recipient {
+        config.penalty_recipient = Some(deps.api.addr_validate(&penalty_recipient)?);
+    }
+
+    if let Some(early_withdraw_penalty_rate) = early_withdraw_penalty_rate {
+        validate_penalty_rate(early_withdraw_penalty_rate)?;
+        config.early_withdraw_penalty_rate = early_withdraw_penalty_rate;
+    }
+
+    if let Some(tokenless_production) = tokenless_production {
+        validate_tokenless_production(tokenless_production)?;
+        config.tokenless_production = tokenless_production;
+    }
+
+    if let Some(gauge_controller) = gauge_controller {
+        config.gauge_controller = Some(deps.api.addr_validate(&gauge_controller)?);
+    }
+
+    if let Some(fee_distributor) = fee_distributor {
+        config.fee_distributor = Some(deps.api.addr_validate(&fee_distributor)?);
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "update_config")]))
+}
+
+fn validate_curve_parameters(config: &Config) -> StdResult<()> {
+    if config.seconds_per_week == 0 {
+        Err(StdError::generic_err("seconds_per_week must be positive"))
+    } else if config.max_weeks == 0 {
+        Err(StdError::generic_err("max_weeks must be positive"))
+    } else if config.voting_power_constant_divisor == 0 {
+        Err(StdError::generic_err(
+            "voting_power_constant_divisor must be positive",
+        ))
+    } else if config
+        .seconds_per_week
+        .checked_mul(config.max_weeks)
+        .is_none_or(|max_seconds| max_seconds > MAX_LOCK_DURATION)
+    {
+        Err(StdError::generic_err(format!(
+            "max_weeks * seconds_per_week must be at most {}",
+            MAX_LOCK_DURATION
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_penalty_rate(penalty_rate: Decimal) -> StdResult<()> {
+    if penalty_rate > Decimal::one() {
+        Err(StdError::generic_err(
+            "early_withdraw_penalty_rate must be 0 to 1",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_tokenless_production(tokenless_production: Decimal) -> StdResult<()> {
+    if tokenless_production.is_zero() || tokenless_production > Decimal::one() {
+        Err(StdError::generic_err(
+            "tokenless_production must be greater than 0 and at most 1",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn execute_propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    config.pending_owner = Some(new_owner.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("new_owner", new_owner.as_str()),
+    ]))
+}
+
+pub fn execute_cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.pending_owner.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+
+    config.pending_owner = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![("action", "cancel_ownership_proposal")]))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    match config.pending_owner {
+        Some(ref pending_owner) if *pending_owner == info.sender => {}
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => return Err(ContractError::NoPendingOwner {}),
+    }
+
+    config.owner = info.sender;
+    config.pending_owner = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_ownership"),
+        ("owner", config.owner.as_str()),
+    ]))
+}
+
+pub fn execute_add_to_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract_address: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let contract_address = deps.api.addr_validate(&contract_address)?;
+    WHITELISTED_CONTRACTS.save(deps.storage, &contract_address, &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_to_whitelist"),
+        ("contract_address", contract_address.as_str()),
+    ]))
+}
+
+pub fn execute_remove_from_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract_address: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let contract_address = deps.api.addr_validate(&contract_address)?;
+    if !WHITELISTED_CONTRACTS.has(deps.storage, &contract_address) {
+        return Err(ContractError::ContractNotWhitelisted {});
+    }
+
+    WHITELISTED_CONTRACTS.remove(deps.storage, &contract_address);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_from_whitelist"),
+        ("contract_address", contract_address.as_str()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -406,6 +1167,58 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
         QueryMsg::Staker { address, timestamp } => {
             Ok(to_binary(&query_staker(deps, env, address, timestamp)?)?)
         }
+        QueryMsg::StakerLocks { address, timestamp } => Ok(to_binary(&query_staker_locks(
+            deps, env, address, timestamp,
+        )?)?),
+        QueryMsg::Whitelist { start_after, limit } => {
+            Ok(to_binary(&query_whitelist(deps, start_after, limit)?)?)
+        }
+        QueryMsg::Boost {
+            address,
+            working_supply,
+            deposit,
+            timestamp,
+        } => Ok(to_binary(&query_boost(
+            deps,
+            env,
+            address,
+            working_supply,
+            deposit,
+            timestamp,
+        )?)?),
+        QueryMsg::PendingCheckpoint {} => Ok(to_binary(&query_pending_checkpoint(deps, env)?)?),
+        QueryMsg::StakerBalances {
+            address,
+            timestamps,
+        } => Ok(to_binary(&query_staker_balances(
+            deps, address, timestamps,
+        )?)?),
+        QueryMsg::TotalBalances { timestamps } => {
+            Ok(to_binary(&query_total_balances(deps, env, timestamps)?)?)
+        }
+        QueryMsg::StakerHistory {
+            address,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_staker_history(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::Stakers {
+            start_after,
+            limit,
+            timestamp,
+            status,
+        } => Ok(to_binary(&query_stakers(
+            deps,
+            env,
+            start_after,
+            limit,
+            timestamp,
+            status,
+        )?)?),
     }
 }
 
@@ -413,9 +1226,17 @@ fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     Ok(ConfigResponse {
         owner: config.owner.to_string(),
-        cw20_address: config
-            .cw20_address
-            .map_or("None".to_string(), |a| a.to_string()),
+        pending_owner: config.pending_owner.map(|a| a.to_string()),
+        cw20_address: config.cw20_address.map(|a| a.to_string()),
+        penalty_recipient: config.penalty_recipient.map(|a| a.to_string()),
+        early_withdraw_penalty_rate: config.early_withdraw_penalty_rate,
+        seconds_per_week: config.seconds_per_week,
+        max_weeks: config.max_weeks,
+        voting_power_constant_divisor: config.voting_power_constant_divisor,
+        decay_model: config.decay_model,
+        tokenless_production: config.tokenless_production,
+        gauge_controller: config.gauge_controller.map(|a| a.to_string()),
+        fee_distributor: config.fee_distributor.map(|a| a.to_string()),
     })
 }
 
@@ -424,21 +1245,98 @@ fn query_state(
     env: Env,
     timestamp: Option<u64>,
 ) -> Result<StateResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
     let timestamp = timestamp.unwrap_or_else(|| env.block.time.seconds());
     let mut state: State = STATE
         .may_load_at_height(deps.storage, timestamp)?
         .unwrap_or_default();
 
-    apply_pending_slope_changes_to_state(deps.storage, &mut state, env.block.time.seconds())?;
+    apply_pending_slope_changes_to_state(deps.storage, &mut state, timestamp)?;
 
     Ok(StateResponse {
         total_deposited_amount: state.total_deposit,
         total_locked_amount: state
             .voting_power_coefficients
-            .evaluate_locked_balance_at_timestamp(timestamp),
+            .evaluate_locked_balance_at_timestamp(timestamp, config.decay_model),
         total_balance: state
             .voting_power_coefficients
-            .evaluate_voting_power_at_timestamp(timestamp),
+            .evaluate_voting_power_at_timestamp(timestamp, config.voting_power_constant_divisor),
+    })
+}
+
+fn query_total_balances(
+    deps: Deps,
+    env: Env,
+    timestamps: Vec<u64>,
+) -> Result<BalancesResponse, ContractError> {
+    let balances = timestamps
+        .into_iter()
+        .map(|timestamp| Ok(query_state(deps, env.clone(), Some(timestamp))?.total_balance))
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(BalancesResponse { balances })
+}
+
+fn query_staker_balances(
+    deps: Deps,
+    address: String,
+    timestamps: Vec<u64>,
+) -> Result<BalancesResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let staker_addr = deps.api.addr_validate(address.as_str())?;
+
+    let balances = timestamps
+        .into_iter()
+        .map(|timestamp| {
+            user_voting_power_at_timestamp(deps.storage, &config, &staker_addr, timestamp)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BalancesResponse { balances })
+}
+
+fn query_boost(
+    deps: Deps,
+    env: Env,
+    address: String,
+    working_supply: Uint128,
+    deposit: Uint128,
+    timestamp: Option<u64>,
+) -> Result<BoostResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let staker = query_staker(deps, env.clone(), address, timestamp)?;
+    let state = query_state(deps, env, timestamp)?;
+
+    let working_balance =
+        config.working_balance(deposit, working_supply, staker.balance, state.total_balance);
+
+    // The working balance of the deposit without any voting power
+    let unboosted_working_balance = deposit * config.tokenless_production;
+
+    let boost = if unboosted_working_balance.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(working_balance, unboosted_working_balance)
+    };
+
+    Ok(BoostResponse {
+        working_balance,
+        boost,
+    })
+}
+
+fn query_pending_checkpoint(
+    deps: Deps,
+    env: Env,
+) -> Result<PendingCheckpointResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let state: State = STATE.load(deps.storage)?;
+
+    Ok(PendingCheckpointResponse {
+        state_timestamp: state.timestamp,
+        pending_weeks: pending_weeks(&state, env.block.time.seconds(), config.seconds_per_week),
+        max_pending_weeks: MAX_PENDING_WEEKS,
     })
 }
 
@@ -448,20 +1346,187 @@ pub fn query_staker(
     address: String,
     timestamp: Option<u64>,
 ) -> Result<StakerResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
     let timestamp = timestamp.unwrap_or_else(|| env.block.time.seconds());
     let staker_addr = deps.api.addr_validate(address.as_str())?;
-    let user_locked_balance = USER_LOCKED_BALANCES
+
+    // Sum over all of the staker's locks
+    let mut deposited_amount = Uint128::zero();
+    let mut locked_amount = Uint128::zero();
+    let mut balance = Uint128::zero();
+    for (_, user_locked_balance) in
+        read_user_locked_balances_at_timestamp(deps.storage, &staker_addr, timestamp)?
+    {
+        deposited_amount += user_locked_balance.deposited_amount;
+        locked_amount += user_locked_balance.locked_amount_at_timestamp(&config, timestamp);
+        balance += user_locked_balance.voting_power_at_timestamp(&config, timestamp);
+    }
+
+    let delegate = DELEGATED_TO.may_load_at_height(deps.storage, &staker_addr, timestamp)?;
+
+    // Sum the voting power of everyone who had delegated to the staker at the timestamp
+    let mut delegated_balance = Uint128::zero();
+    for delegator in DELEGATORS
         .may_load_at_height(deps.storage, &staker_addr, timestamp)?
-        .unwrap_or_default();
+        .unwrap_or_default()
+    {
+        delegated_balance +=
+            user_voting_power_at_timestamp(deps.storage, &config, &delegator, timestamp)?;
+    }
+
+    let voting_power = if delegate.is_some() {
+        delegated_balance
+    } else {
+        balance + delegated_balance
+    };
 
     Ok(StakerResponse {
-        deposited_amount: user_locked_balance.deposited_amount,
-        locked_amount: user_locked_balance.locked_amount_at_timestamp(timestamp),
-        balance: user_locked_balance.voting_power_at_timestamp(timestamp),
+        deposited_amount,
+        locked_amount,
+        balance,
+        delegated_balance,
+        delegate: delegate.map(|a| a.to_string()),
+        voting_power,
+    })
+}
+
+pub fn query_staker_locks(
+    deps: Deps,
+    env: Env,
+    address: String,
+    timestamp: Option<u64>,
+) -> Result<StakerLocksResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let timestamp = timestamp.unwrap_or_else(|| env.block.time.seconds());
+    let staker_addr = deps.api.addr_validate(address.as_str())?;
+
+    let locks = read_user_locked_balances_at_timestamp(deps.storage, &staker_addr, timestamp)?
+        .into_iter()
+        .filter(|(_, user_locked_balance)| user_locked_balance.exists())
+        .map(|(lock_id, user_locked_balance)| LockResponse {
+            lock_id,
+            deposited_amount: user_locked_balance.deposited_amount,
+            locked_amount: user_locked_balance.locked_amount_at_timestamp(&config, timestamp),
+            balance: user_locked_balance.voting_power_at_timestamp(&config, timestamp),
+            start_lock_time: user_locked_balance.start_lock_time,
+            end_lock_time: user_locked_balance.end_lock_time,
+        })
+        .collect();
+
+    Ok(StakerLocksResponse { locks })
+}
+
+fn query_whitelist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<WhitelistResponse, ContractError> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_validate(&start_after)?)
+    } else {
+        None
+    };
+
+    let contracts = read_whitelisted_contracts(deps.storage, start_after, limit)?;
+
+    Ok(WhitelistResponse {
+        contracts: contracts.iter().map(|addr| addr.to_string()).collect(),
+    })
+}
+
+fn query_staker_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> Result<StakerHistoryResponse, ContractError> {
+    let staker_addr = deps.api.addr_validate(address.as_str())?;
+
+    let history = read_user_lock_history(deps.storage, &staker_addr, start_after, limit)?
+        .into_iter()
+        .map(|lock_checkpoint| LockCheckpointResponse {
+            lock_id: lock_checkpoint.lock_id,
+            action: lock_checkpoint.action,
+            timestamp: lock_checkpoint.timestamp,
+            deposited_amount: lock_checkpoint.user_locked_balance.deposited_amount,
+            start_lock_time: lock_checkpoint.user_locked_balance.start_lock_time,
+            end_lock_time: lock_checkpoint.user_locked_balance.end_lock_time,
+        })
+        .collect();
+
+    Ok(StakerHistoryResponse { history })
+}
+
+fn query_stakers(
+    deps: Deps,
+    env: Env,
+    start_after: Option<(String, u64)>,
+    limit: Option<u32>,
+    timestamp: Option<u64>,
+    status: Option<LockStatus>,
+) -> Result<StakersResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let timestamp = timestamp.unwrap_or_else(|| env.block.time.seconds());
+    let start_after = if let Some((address, lock_id)) = start_after {
+        Some((deps.api.addr_validate(&address)?, lock_id))
+    } else {
+        None
+    };
+
+    let (user_locked_balances, next_start_after) = read_all_user_locked_balances_at_timestamp(
+        deps.storage,
+        start_after,
+        limit,
+        timestamp,
+        status,
+    )?;
+
+    let stakers = user_locked_balances
+        .into_iter()
+        .map(|(user, lock_id, user_locked_balance)| StakersResponseItem {
+            address: user.to_string(),
+            lock_id,
+            deposited_amount: user_locked_balance.deposited_amount,
+            locked_amount: user_locked_balance.locked_amount_at_timestamp(&config, timestamp),
+            balance: user_locked_balance.voting_power_at_timestamp(&config, timestamp),
+            end_lock_time: user_locked_balance.end_lock_time,
+        })
+        .collect();
+
+    Ok(StakersResponse {
+        stakers,
+        next_start_after: next_start_after.map(|(user, lock_id)| (user.to_string(), lock_id)),
     })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // Contracts deployed before lock ids were added store a config which can't be loaded
+    // as the current Config. Fill in the new fields with the values the contract used to
+    // have hardcoded, and move each user's lock to lock id 0.
+    if CONFIG.load(deps.storage).is_err() {
+        let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                cw20_address: legacy_config.cw20_address,
+                owner: legacy_config.owner,
+                pending_owner: None,
+                seconds_per_week: DEFAULT_SECONDS_PER_WEEK,
+                max_weeks: DEFAULT_MAX_WEEKS,
+                voting_power_constant_divisor: DEFAULT_VOTING_POWER_CONSTANT_DIVISOR,
+                decay_model: DecayModel::LinearUnlock,
+                penalty_recipient: None,
+                early_withdraw_penalty_rate: Decimal::zero(),
+                tokenless_production: Decimal::percent(DEFAULT_TOKENLESS_PRODUCTION_PERCENT),
+                gauge_controller: None,
+                fee_distributor: None,
+            },
+        )?;
+
+        migrate_legacy_user_locked_balances(deps.storage)?;
+    }
+
     Ok(Response::default())
 }
@@ -21,8 +21,10 @@ pub enum ContractError {
     #[error("Contracts can't create, modify, or withdraw locks")]
     ContractsCannotInteractWithLocks {},
 
-    #[error("A lock already exists. You cannot create a new one until the old one expires and is withdrawn.")]
-    LockAlreadyExists {},
+    #[error(
+        "Cannot hold more than {max_locks} locks at once. Withdraw an expired lock and try again."
+    )]
+    TooManyLocks { max_locks: u64 },
 
     #[error("A lock does not exist. You cannot modify a lock before creating one.")]
     LockDoesNotExist {},
@@ -47,4 +49,52 @@ pub enum ContractError {
 
     #[error("Config contracts have not been registered yet")]
     ConfigContractsNotRegistered {},
+
+    #[error("The locked token cannot be changed while tokens are deposited")]
+    CannotUpdateTokenWithDeposits {},
+
+    #[error("Nothing is available to withdraw from the lock yet")]
+    NothingToWithdraw {},
+
+    #[error("Early withdraws are disabled")]
+    EarlyWithdrawDisabled {},
+
+    #[error("There is no pending ownership proposal")]
+    NoPendingOwner {},
+
+    #[error("Cannot transfer a lock to yourself")]
+    CannotTransferToSelf {},
+
+    #[error("Cannot transfer a lock to an address which already holds a lock")]
+    RecipientHasLock {},
+
+    #[error("Cannot merge a lock with itself")]
+    CannotMergeLockWithItself {},
+
+    #[error("Cannot delegate voting power to yourself")]
+    CannotDelegateToSelf {},
+
+    #[error("Voting power is already delegated to this address")]
+    AlreadyDelegated {},
+
+    #[error("Must hold an active lock to delegate voting power")]
+    NoActiveLock {},
+
+    #[error("Cannot delegate to an address with {max_delegators} delegators")]
+    TooManyDelegators { max_delegators: u64 },
+
+    #[error("Voting power is not delegated")]
+    NotDelegated {},
+
+    #[error("The delegate has removed this delegator")]
+    DelegatorRemoved {},
+
+    #[error("Contract is not whitelisted")]
+    ContractNotWhitelisted {},
+
+    #[error("State is {pending_weeks} weeks behind, call Checkpoint first")]
+    CheckpointRequired { pending_weeks: u64 },
+
+    #[error("Lock cannot be changed while voting power is allocated to gauges")]
+    LockHasGaugeVotes {},
 }
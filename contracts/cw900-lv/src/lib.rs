@@ -6,3 +6,6 @@ mod state;
 
 #[cfg(test)]
 mod tests;
+
+#[cfg(test)]
+mod mock_querier;
@@ -0,0 +1,139 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use cw900::gauge_controller::{QueryMsg as GaugeControllerQueryMsg, UserVotesResponse};
+use std::collections::HashMap;
+
+use terra_cosmwasm::{ContractInfoResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<TerraQueryWrapper>,
+    contract_info_querier: ContractInfoQuerier,
+    gauge_controller_querier: GaugeControllerQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct GaugeControllerQuerier {
+    // basis points of voting power each voter has allocated to gauges
+    power_used: HashMap<String, u16>,
+}
+
+#[derive(Clone, Default)]
+pub struct ContractInfoQuerier {
+    // addresses which should be treated as contracts
+    contracts: Vec<String>,
+}
+
+impl ContractInfoQuerier {
+    pub fn new(contracts: &[&str]) -> Self {
+        ContractInfoQuerier {
+            contracts: contracts.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        // MockQuerier doesn't support Custom, so we ignore it completely here
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+        match &request {
+            QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
+                if route == &TerraRoute::Wasm {
+                    match query_data {
+                        TerraQuery::ContractInfo { contract_address } => {
+                            if !self
+                                .contract_info_querier
+                                .contracts
+                                .contains(contract_address)
+                            {
+                                return SystemResult::Err(SystemError::NoSuchContract {
+                                    addr: contract_address.to_string(),
+                                });
+                            }
+
+                            let res = ContractInfoResponse {
+                                address: contract_address.to_string(),
+                                creator: "creator".to_string(),
+                                code_id: 1,
+                                admin: None,
+                            };
+                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                        }
+                        _ => panic!("DO NOT ENTER HERE"),
+                    }
+                } else {
+                    panic!("DO NOT ENTER HERE")
+                }
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => match from_binary(msg) {
+                Ok(GaugeControllerQueryMsg::UserVotes { address }) => {
+                    let res = UserVotesResponse {
+                        power_used_bps: self
+                            .gauge_controller_querier
+                            .power_used
+                            .get(&address)
+                            .cloned()
+                            .unwrap_or_default(),
+                        votes: vec![],
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<TerraQueryWrapper>) -> Self {
+        WasmMockQuerier {
+            base,
+            contract_info_querier: ContractInfoQuerier::default(),
+            gauge_controller_querier: GaugeControllerQuerier::default(),
+        }
+    }
+
+    // configure the addresses which are contracts
+    pub fn with_contracts(&mut self, contracts: &[&str]) {
+        self.contract_info_querier = ContractInfoQuerier::new(contracts);
+    }
+
+    // configure the basis points of voting power a voter has allocated to gauges
+    pub fn with_gauge_votes(&mut self, voter: &str, power_used_bps: u16) {
+        self.gauge_controller_querier
+            .power_used
+            .insert(voter.to_string(), power_used_bps);
+    }
+}
@@ -1,18 +1,22 @@
 use crate::error::ContractError;
 use crate::state::{
-    State, UserLockedBalance, COEFFICIENT_CHANGES, SECONDS_PER_WEEK, STATE, USER_LOCKED_BALANCES,
+    Config, State, UserLockedBalance, COEFFICIENT_CHANGES, CONFIG, MAX_PENDING_WEEKS, STATE,
+    USER_LOCKED_BALANCES, USER_LOCK_ACTIONS,
 };
 
 use cosmwasm_std::{to_binary, Addr, CosmosMsg, Response, StdResult, Storage, Uint128, WasmMsg};
 use cw20::Cw20ExecuteMsg;
+use cw900::ve_token::LockAction;
 use cw_storage_plus::U64Key;
 
 pub fn update_user_lock(
     storage: &mut dyn Storage,
     user: &Addr,
+    lock_id: u64,
     prev_user_locked_balance: UserLockedBalance,
     new_user_locked_balance: UserLockedBalance,
-) -> StdResult<()> {
+    action: LockAction,
+) -> Result<(), ContractError> {
     // When creating a new lock
     // prev_user_locked_balance doesn't exist.
     // new_user_locked_balance is has positive amount and unlocks in the future
@@ -33,6 +37,8 @@ pub fn update_user_lock(
     // prev_user_locked_balance has positive amount and unlocks in the future
     // new_user_locked_balance doesn't exist.
 
+    let config = CONFIG.load(storage)?;
+
     // Update the last global point
     let mut state = STATE.load(storage)?;
 
@@ -47,6 +53,7 @@ pub fn update_user_lock(
     // Update the state
     // to reflect the update to the lock
     update_state_for_lock_update(
+        &config,
         &mut state,
         &prev_user_locked_balance,
         &new_user_locked_balance,
@@ -55,6 +62,7 @@ pub fn update_user_lock(
     // Update slope changes to schedule a reversal of the changes to made to state in update_state_for_lock_update
     update_slope_changes_for_lock_update(
         storage,
+        &config,
         &prev_user_locked_balance,
         &new_user_locked_balance,
     )?;
@@ -65,14 +73,31 @@ pub fn update_user_lock(
     // Save the new user ve token point
     USER_LOCKED_BALANCES.save(
         storage,
-        user,
+        (user, U64Key::from(lock_id)),
         &new_user_locked_balance,
         new_user_locked_balance.timestamp,
     )?;
 
+    // Record the action for the lock history
+    USER_LOCK_ACTIONS.save(
+        storage,
+        (
+            user,
+            U64Key::from(lock_id),
+            U64Key::from(new_user_locked_balance.timestamp),
+        ),
+        &action,
+    )?;
+
     Ok(())
 }
 
+/// Return the number of weeks between state.timestamp and timestamp
+/// for which slope changes haven't been applied to state
+pub fn pending_weeks(state: &State, timestamp: u64, seconds_per_week: u64) -> u64 {
+    (timestamp / seconds_per_week).saturating_sub(state.timestamp / seconds_per_week)
+}
+
 /// Apply pending slope changes to state between state.timestamp and block_timestamp
 pub fn apply_pending_slope_changes_to_state(
     storage: &dyn Storage,
@@ -83,21 +108,54 @@ pub fn apply_pending_slope_changes_to_state(
         IMStorage::ImmutableStorage(storage),
         state,
         timestamp,
+        u64::MAX,
     )
 }
 
 /// Apply pending slope changes to state between state.timestamp and block_timestamp,
 /// and save each updated state to storage.
+/// Fails if more than MAX_PENDING_WEEKS weeks are pending.
 pub fn apply_pending_slope_changes_to_state_and_save_updates(
     storage: &mut dyn Storage,
     state: &mut State,
     timestamp: u64,
-) -> StdResult<()> {
+) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+
+    let pending_weeks = pending_weeks(state, timestamp, config.seconds_per_week);
+    if pending_weeks > MAX_PENDING_WEEKS {
+        return Err(ContractError::CheckpointRequired { pending_weeks });
+    }
+
     internal_apply_pending_slope_changes_to_state(
         IMStorage::MutableStorage(storage),
         state,
         timestamp,
-    )
+        pending_weeks,
+    )?;
+
+    Ok(())
+}
+
+/// Apply pending slope changes to state for at most max_weeks weeks between state.timestamp
+/// and block_timestamp, and save each updated state to storage.
+/// Returns the number of weeks which remain pending.
+pub fn checkpoint_state(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    timestamp: u64,
+    max_weeks: u64,
+) -> StdResult<u64> {
+    let config = CONFIG.load(storage)?;
+
+    internal_apply_pending_slope_changes_to_state(
+        IMStorage::MutableStorage(storage),
+        state,
+        timestamp,
+        max_weeks,
+    )?;
+
+    Ok(pending_weeks(state, timestamp, config.seconds_per_week))
 }
 
 /// Enum for allowing user to pass immutable or mutable storage to a function
@@ -107,22 +165,31 @@ enum IMStorage<'a> {
     MutableStorage(&'a mut dyn Storage),
 }
 
-/// Apply pending slope changes to state between state.timestamp and block_timestamp.
+/// Apply pending slope changes to state for at most max_weeks weeks
+/// between state.timestamp and block_timestamp.
 /// If imstorage is of type IMStorage::MutableStorage, then save each updated state to storage.
 fn internal_apply_pending_slope_changes_to_state(
     mut imstorage: IMStorage,
     state: &mut State,
     timestamp: u64,
+    max_weeks: u64,
 ) -> StdResult<()> {
+    let seconds_per_week = CONFIG
+        .load(match &imstorage {
+            IMStorage::ImmutableStorage(x) => *x,
+            IMStorage::MutableStorage(x) => *x,
+        })?
+        .seconds_per_week;
+
     // Get the week that comes before the state's timestamp
-    let mut week_iterator_timestamp = state.timestamp / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let mut week_iterator_timestamp = state.timestamp / seconds_per_week * seconds_per_week;
 
     // Go to the next week because we already processed
     // all weeks at or before the state's timestamp
-    week_iterator_timestamp += SECONDS_PER_WEEK;
+    week_iterator_timestamp += seconds_per_week;
 
     // Loop to update state.
-    for _ in 0..255 {
+    for _ in 0..max_weeks {
         if week_iterator_timestamp > timestamp {
             // We are past the current block timestamp, so break out of the loop
             break;
@@ -151,7 +218,7 @@ fn internal_apply_pending_slope_changes_to_state(
         }
 
         // Increment week_interator
-        week_iterator_timestamp += SECONDS_PER_WEEK;
+        week_iterator_timestamp += seconds_per_week;
     }
 
     Ok(())
@@ -159,6 +226,7 @@ fn internal_apply_pending_slope_changes_to_state(
 
 /// Update state to incorporate lock update changes
 pub fn update_state_for_lock_update(
+    config: &Config,
     state: &mut State,
     prev_user_locked_balance: &UserLockedBalance,
     new_user_locked_balance: &UserLockedBalance,
@@ -169,7 +237,8 @@ pub fn update_state_for_lock_update(
     // Otherwise the prev coefficients were already removed as part of coefficient changes
     if !prev_user_locked_balance.expired_at_timestamp(new_user_locked_balance.timestamp) {
         // Remove prev token slope and bias
-        state.voting_power_coefficients -= prev_user_locked_balance.voting_power_coefficients();
+        state.voting_power_coefficients -=
+            prev_user_locked_balance.voting_power_coefficients(config);
     }
 
     // Remove prev point deposited amount
@@ -179,7 +248,7 @@ pub fn update_state_for_lock_update(
     state.total_deposit -= prev_user_locked_balance.deposited_amount;
 
     // Add new token slope and bias
-    state.voting_power_coefficients += new_user_locked_balance.voting_power_coefficients();
+    state.voting_power_coefficients += new_user_locked_balance.voting_power_coefficients(config);
 
     // Add new point deposited amount
     state.total_deposit += new_user_locked_balance.deposited_amount;
@@ -192,6 +261,7 @@ pub fn update_state_for_lock_update(
 /// `update_state_for_lock_update`
 pub fn update_slope_changes_for_lock_update(
     storage: &mut dyn Storage,
+    config: &Config,
     prev_user_locked_balance: &UserLockedBalance,
     new_user_locked_balance: &UserLockedBalance,
 ) -> StdResult<()> {
@@ -203,15 +273,19 @@ pub fn update_slope_changes_for_lock_update(
         )?
         .unwrap_or_default();
 
-    // Remove prev token point slope
-    old_coefficient_changes -= prev_user_locked_balance.voting_power_coefficients();
+    // Only remove the prev coefficient changes if the lock isn't expired
+    // Otherwise they were already applied to state and are needed to evaluate past timestamps
+    if !prev_user_locked_balance.expired_at_timestamp(new_user_locked_balance.timestamp) {
+        // Remove prev token point slope
+        old_coefficient_changes -= prev_user_locked_balance.voting_power_coefficients(config);
+    }
 
     if new_user_locked_balance.end_lock_time == prev_user_locked_balance.end_lock_time {
         // If new token point ends at the same location, update old coefficient changes accordingly
 
         // Add new coefficient changes
 
-        old_coefficient_changes += new_user_locked_balance.voting_power_coefficients();
+        old_coefficient_changes += new_user_locked_balance.voting_power_coefficients(config);
     } else {
         // If new token points ends at a new location,
         // read the corresponding slope, update it, and save it
@@ -220,7 +294,7 @@ pub fn update_slope_changes_for_lock_update(
             .unwrap_or_default();
 
         // Add new coefficient changes
-        new_coefficient_changes += new_user_locked_balance.voting_power_coefficients();
+        new_coefficient_changes += new_user_locked_balance.voting_power_coefficients(config);
 
         COEFFICIENT_CHANGES.save(
             storage,
@@ -239,6 +313,22 @@ pub fn update_slope_changes_for_lock_update(
     Ok(())
 }
 
+/// Create a message transferring `amount` tokens of type `asset_token` to `recipient`
+pub fn token_transfer_msg(
+    asset_token: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: asset_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
+
 /// Send `amount` tokens of type `asset_token` to `recipient`
 pub fn send_tokens(
     asset_token: &Addr,
@@ -247,14 +337,7 @@ pub fn send_tokens(
     action: &str,
 ) -> Result<Response, ContractError> {
     Ok(Response::new()
-        .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: asset_token.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: recipient.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        })])
+        .add_message(token_transfer_msg(asset_token, recipient, amount)?)
         .add_attributes(vec![
             ("action", action),
             ("recipient", recipient.to_string().as_str()),
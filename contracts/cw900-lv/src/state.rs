@@ -1,135 +1,84 @@
-use core::fmt;
-use std::convert::TryFrom;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::convert::{TryFrom, TryInto};
 
-use cosmwasm_std::{Addr, Decimal256, Uint128, Uint256};
-use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, U64Key};
+pub use cw900::curve::QuadraticEquationCoefficients;
+use cw900::ve_token::{DecayModel, LockAction, LockStatus};
+
+use cosmwasm_std::{
+    Addr, Decimal, Decimal256, Order, StdError, StdResult, Storage, Uint128, Uint256,
+};
+use cw_storage_plus::{Bound, Item, Map, SnapshotItem, SnapshotMap, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-pub const SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60; // Order of 10 ** 6
-pub const MAX_WEEKS: u64 = 52;
-pub const MAX_SECONDS: u64 = MAX_WEEKS * SECONDS_PER_WEEK; // Order of 10 ** 8
-pub const VOTING_POWER_CONSTANT_DIVISOR: u64 = MAX_SECONDS;
+// Defaults for the curve parameters stored in the config
+pub const DEFAULT_SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60; // Order of 10 ** 6
+pub const DEFAULT_MAX_WEEKS: u64 = 52;
+/// Upper bound on the maximum lock duration in seconds,
+/// keeping end lock times and voting power coefficients far from overflowing
+pub const MAX_LOCK_DURATION: u64 = 10 * 365 * 24 * 60 * 60;
+pub const DEFAULT_VOTING_POWER_CONSTANT_DIVISOR: u64 = DEFAULT_MAX_WEEKS * DEFAULT_SECONDS_PER_WEEK; // Order of 10 ** 8
+pub const MAX_LOCKS_PER_USER: u64 = 10;
+/// Bounds the work done when summing a delegate's delegated voting power
+pub const MAX_DELEGATORS_PER_DELEGATE: u64 = 20;
+pub const DEFAULT_TOKENLESS_PRODUCTION_PERCENT: u64 = 40;
+/// Maximum number of weeks of pending slope changes that user actions apply to the state.
+/// Beyond this, the state must first be brought up to date with Checkpoint.
+pub const MAX_PENDING_WEEKS: u64 = 255;
 
 pub const CONFIG: Item<Config> = Item::new("config");
 // pub const STATE: Item<State> = Item::new("state");
 pub const COEFFICIENT_CHANGES: Map<U64Key, QuadraticEquationCoefficients> =
     Map::new("coefficient_changes");
 
-pub const USER_LOCKED_BALANCES: SnapshotMap<&Addr, UserLockedBalance> = SnapshotMap::new(
+/// Locks keyed by user and lock id
+pub const USER_LOCKED_BALANCES: SnapshotMap<(&Addr, U64Key), UserLockedBalance> = SnapshotMap::new(
     "user_locked_balance",
     "user_locked_balance__checkpoint",
     "user_locked_balance__changelog",
     cw_storage_plus::Strategy::EveryBlock,
 );
 
+/// Read-only view of the changelog kept by USER_LOCKED_BALANCES, keyed by user, lock id and timestamp.
+/// Each entry holds the lock as it was before the update made at that timestamp.
+pub const USER_LOCKED_BALANCES_CHANGELOG: Map<(&Addr, U64Key, U64Key), LockChangeSet> =
+    Map::new("user_locked_balance__changelog");
+
+/// The action of each update in USER_LOCKED_BALANCES_CHANGELOG, keyed by user, lock id and timestamp
+pub const USER_LOCK_ACTIONS: Map<(&Addr, U64Key, U64Key), LockAction> =
+    Map::new("user_lock_actions");
+
+/// The number of lock ids which have been allocated to each user.
+/// Lock ids of void locks are reused, so this never exceeds MAX_LOCKS_PER_USER.
+pub const USER_LOCK_COUNT: Map<&Addr, u64> = Map::new("user_lock_count");
+
 pub const STATE: SnapshotItem<State> = SnapshotItem::new(
     "state",
     "state__checkpoint",
     "state__changelog",
     cw_storage_plus::Strategy::EveryBlock,
 );
-#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct QuadraticEquationCoefficients {
-    pub quad_coefficient: Decimal256,
-    pub linear_coefficient: Decimal256,
-    pub constant_coefficient: Decimal256,
-}
 
-impl QuadraticEquationCoefficients {
-    pub fn evaluate_voting_power_at_timestamp(&self, timestamp: u64) -> Uint128 {
-        Uint128::try_from(
-            ((
-                // Floor
-                Uint256::from(1u128) * self.constant_coefficient
-                // Floor
-                + Uint256::from(timestamp) * Uint256::from(timestamp) * self.quad_coefficient)
-                // Subtracts a truncated value value
-                .checked_sub(Uint256::from(timestamp) * self.linear_coefficient))
-            // In the event of an underflow
-            // which can happen because of truncation
-            // default to 0
-            .unwrap_or_default()
-            // Scales everything down by VOTING_POWER_CONSTANT_DIVISOR
-                / Uint256::from(VOTING_POWER_CONSTANT_DIVISOR),
-        )
-        .unwrap()
-    }
-
-    // Notice that we can also express rla as a linear function and that:
-    // - the linear coefficient of this function is the negative quadratic coefficient for vp
-    // - the constant coefficient of this function is the negative linear coefficient over two for vp
-    // This means we can calculate the corresponding locked amount without storing more coefficients separately!
-    pub fn evaluate_locked_balance_at_timestamp(&self, timestamp: u64) -> Uint128 {
-        Uint128::try_from(
-            // Floor
-            (Uint256::from(1u128) * self.linear_coefficient / Uint256::from(2u128))
-                // Subtracts a truncated value value
-                .checked_sub(Uint256::from(timestamp) * self.quad_coefficient)
-                // In the event of an underflow
-                // which can happen because of truncation
-                // default to 0
-                .unwrap_or_default(),
-        )
-        .unwrap()
-    }
-}
-
-// Implement Display in order to make testing easier.
-impl fmt::Display for QuadraticEquationCoefficients {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "Quad_coefficient: {}, Linear_coefficient: {}, Constant_coefficient: {}",
-            self.quad_coefficient, self.linear_coefficient, self.constant_coefficient
-        )
-    }
-}
-
-impl Add for QuadraticEquationCoefficients {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Self {
-            quad_coefficient: self.quad_coefficient + other.quad_coefficient,
-            linear_coefficient: self.linear_coefficient + other.linear_coefficient,
-            constant_coefficient: self.constant_coefficient + other.constant_coefficient,
-        }
-    }
-}
+/// The address each delegator has delegated its voting power to
+pub const DELEGATED_TO: SnapshotMap<&Addr, Addr> = SnapshotMap::new(
+    "delegated_to",
+    "delegated_to__checkpoint",
+    "delegated_to__changelog",
+    cw_storage_plus::Strategy::EveryBlock,
+);
 
-impl AddAssign for QuadraticEquationCoefficients {
-    fn add_assign(&mut self, other: Self) {
-        *self = Self {
-            quad_coefficient: self.quad_coefficient + other.quad_coefficient,
-            linear_coefficient: self.linear_coefficient + other.linear_coefficient,
-            constant_coefficient: self.constant_coefficient + other.constant_coefficient,
-        }
-    }
-}
+/// The addresses which have delegated their voting power to each delegate
+pub const DELEGATORS: SnapshotMap<&Addr, Vec<Addr>> = SnapshotMap::new(
+    "delegators",
+    "delegators__checkpoint",
+    "delegators__changelog",
+    cw_storage_plus::Strategy::EveryBlock,
+);
 
-impl Sub for QuadraticEquationCoefficients {
-    type Output = Self;
+/// Delegators which each delegate has removed, and which can't delegate to it again
+pub const REMOVED_DELEGATORS: Map<(&Addr, &Addr), bool> = Map::new("removed_delegators");
 
-    fn sub(self, other: Self) -> Self {
-        Self {
-            quad_coefficient: self.quad_coefficient - other.quad_coefficient,
-            linear_coefficient: self.linear_coefficient - other.linear_coefficient,
-            constant_coefficient: self.constant_coefficient - other.constant_coefficient,
-        }
-    }
-}
-
-impl SubAssign for QuadraticEquationCoefficients {
-    fn sub_assign(&mut self, other: Self) {
-        *self = Self {
-            quad_coefficient: self.quad_coefficient - other.quad_coefficient,
-            linear_coefficient: self.linear_coefficient - other.linear_coefficient,
-            constant_coefficient: self.constant_coefficient - other.constant_coefficient,
-        }
-    }
-}
+/// Contracts which the owner has approved to interact with locks
+pub const WHITELISTED_CONTRACTS: Map<&Addr, bool> = Map::new("whitelisted_contracts");
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UserLockedBalance {
@@ -171,6 +120,18 @@ impl UserLockedBalance {
         !self.exists()
     }
 
+    /// Return the state of a lock at a given timestamp.
+    /// Undefined locks are reported as void.
+    pub fn status_at_timestamp(&self, timestamp: u64) -> LockStatus {
+        if self.is_void_or_undefined() {
+            LockStatus::Void
+        } else if self.expired_at_timestamp(timestamp) {
+            LockStatus::Expired
+        } else {
+            LockStatus::Active
+        }
+    }
+
     /// Create a void lock with a timestamp
     pub fn void_lock_with_timestamp(timestamp: u64) -> Self {
         UserLockedBalance {
@@ -202,11 +163,16 @@ impl UserLockedBalance {
     /// Get the remaining locked_amount for a point at a given timestamp
     /// At start_lock_time time, the locked amount equals the deposited amount
     /// At end_lock_time time, the locked amount is 0
-    pub fn locked_amount_at_timestamp(&self, timestamp: u64) -> Uint128 {
+    /// With the cliff unlock decay model, the locked amount is the deposited amount until end_lock_time
+    pub fn locked_amount_at_timestamp(&self, config: &Config, timestamp: u64) -> Uint128 {
         if self.is_void_or_undefined() || self.expired_at_timestamp(timestamp) {
             return Uint128::zero();
         }
 
+        if config.decay_model == DecayModel::CliffUnlock {
+            return self.deposited_amount;
+        }
+
         // Doing subtraction from deposited_amount in order to make sure we overestimate locked amount
         // instead of underestimating it.
         Uint128::try_from(
@@ -221,8 +187,20 @@ impl UserLockedBalance {
         .unwrap()
     }
 
+    /// Get the penalty for withdrawing the locked amount at a given timestamp.
+    /// The penalty is proportional to both the locked amount and the remaining lock time.
+    pub fn early_withdraw_penalty_at_timestamp(&self, config: &Config, timestamp: u64) -> Uint128 {
+        if self.is_void_or_undefined() || self.expired_at_timestamp(timestamp) {
+            return Uint128::zero();
+        }
+
+        self.locked_amount_at_timestamp(config, timestamp)
+            .multiply_ratio(self.end_lock_time - timestamp, config.max_seconds())
+            * config.early_withdraw_penalty_rate
+    }
+
     // Get the voting power for a point at a given timestamp
-    pub fn voting_power_at_timestamp(&self, timestamp: u64) -> Uint128 {
+    pub fn voting_power_at_timestamp(&self, config: &Config, timestamp: u64) -> Uint128 {
         if self.is_void_or_undefined() || self.expired_at_timestamp(timestamp) {
             return Uint128::zero();
         }
@@ -231,101 +209,80 @@ impl UserLockedBalance {
         // it will sometimes be off by a little bit.
         // self.locked_amount_at_timestamp(timestamp)
         //     * Uint128::from(self.remaining_lock_time_at_timestamp(timestamp))
-        //     / Uint128::from(VOTING_POWER_CONSTANT_DIVISOR)
-
-        self.voting_power_coefficients()
-            .evaluate_voting_power_at_timestamp(timestamp)
-    }
-
-    // The following functions are for specifying the coefficients
-    // of the quadratic function specifying the voting power for a given locked balance
-
-    // The formula is:
-    // voting_power = remaining_locked_amount * remaining_lock_time / voting_power_constant_divisor
-    // where remaining_locked_amount = deposited_amount * remaining_lock_time / (end_lock_time - start_lock_time)
-
-    // But we wait until evaluating the quadratic coefficients to divide by voting_power_constant_divisor
-    // This is to increase the sig figs of the quadratic coefficients
-    // Also, rla is calculated as da * rlt / (elt - slt) instead of da - da * (t - slt) / (elt - slt)
-    // as is done in the locked_amount function, but this is fine for a voting power calculation.
-
-    // i.e.
-    // vp = rla * rlt
-    // rla = da * rlt / (elt - slt)
-    // => vp = da / (elt - slt) * (elt - t)^2
-    // = da / (elt - slt) * t^2
-    // - 2 * elt * da / (elt - slt) * t
-    // + elt^2 * da / (elt - slt)
-
-    // Notice that we can also express rla as a linear function:
-    // and that
-    // - the linear coefficient of this function is the negative quadratic coefficient for vp
-    // - the constant coefficient of this function is the negative linear coefficient over two for vp
-    // This means we can calculate the corresponding locked amount without storing more coefficients separately!
-
-    // rla is da * rlt / (elt - slt)
-    // da * (elt - t) / (elt - slt)
-    // da * elt / (elt - slt)
-    // - da / (elt - slt) * t
-
-    fn voting_power_constant_coefficient(&self) -> Decimal256 {
-        if self.is_void_or_undefined() {
-            return Decimal256::zero();
-        }
-
-        // First do all multiplications, then divisions
-        Decimal256::from_ratio(
-            Uint128::from(self.end_lock_time)
-                * Uint128::from(self.end_lock_time)
-                * self.deposited_amount,
-            // Denominator is always positive
-            Uint128::from(self.initial_lock_duration()),
-        )
-    }
+        //     / Uint128::from(config.voting_power_constant_divisor)
 
-    fn voting_power_linear_coefficient(&self) -> Decimal256 {
-        if self.is_void_or_undefined() {
-            return Decimal256::zero();
-        }
-
-        // First do all multiplications, then divisions
-        Decimal256::from_ratio(
-            Uint128::from(2 * self.end_lock_time) * self.deposited_amount,
-            // Denominator is always positive
-            Uint128::from(self.initial_lock_duration()),
-        )
+        self.voting_power_coefficients(config)
+            .evaluate_voting_power_at_timestamp(timestamp, config.voting_power_constant_divisor)
     }
 
-    fn voting_power_quad_coefficient(&self) -> Decimal256 {
+    pub fn voting_power_coefficients(&self, config: &Config) -> QuadraticEquationCoefficients {
         if self.is_void_or_undefined() {
-            return Decimal256::zero();
+            return QuadraticEquationCoefficients::default();
         }
 
-        // First do all multiplications, then divisions
-        Decimal256::from_ratio(
+        QuadraticEquationCoefficients::for_lock(
             self.deposited_amount,
-            // Denominator is always positive
-            Uint128::from(self.initial_lock_duration()),
+            self.start_lock_time,
+            self.end_lock_time,
+            config.decay_model,
         )
     }
-
-    pub fn voting_power_coefficients(&self) -> QuadraticEquationCoefficients {
-        if self.is_void_or_undefined() {
-            return QuadraticEquationCoefficients::default();
-        }
-
-        QuadraticEquationCoefficients {
-            constant_coefficient: self.voting_power_constant_coefficient(),
-            linear_coefficient: self.voting_power_linear_coefficient(),
-            quad_coefficient: self.voting_power_quad_coefficient(),
-        }
-    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub cw20_address: Option<Addr>,
     pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    /// Length of an epoch in seconds. End lock times are rounded down to a multiple of it.
+    pub seconds_per_week: u64,
+    /// Maximum lock duration in epochs
+    pub max_weeks: u64,
+    /// Divisor scaling voting power down so that one token locked for
+    /// voting_power_constant_divisor seconds provides one unit of voting power
+    pub voting_power_constant_divisor: u64,
+    /// Shape of the voting power and locked amount curves
+    pub decay_model: DecayModel,
+    /// Recipient of early withdraw penalties, early withdraws are disabled if this is not set
+    pub penalty_recipient: Option<Addr>,
+    /// Fraction of the locked amount taken as a penalty when withdrawing
+    /// with the maximum lock time remaining.
+    /// Legacy configs are migrated with no penalty.
+    pub early_withdraw_penalty_rate: Decimal,
+    /// Fraction of a deposit which counts towards its working balance
+    /// regardless of the depositor's voting power
+    pub tokenless_production: Decimal,
+    /// Gauge controller whose voters can't transfer, merge or early withdraw their locks
+    pub gauge_controller: Option<Addr>,
+    /// Fee distributor which can add claimed tokens to the locks of other stakers
+    pub fee_distributor: Option<Addr>,
+}
+
+impl Config {
+    /// Return the maximum lock duration in seconds
+    pub fn max_seconds(&self) -> u64 {
+        self.max_weeks * self.seconds_per_week
+    }
+
+    /// Return the working balance of a deposit, following the Curve gauge boost formula:
+    /// min(deposit, tokenless_production * deposit
+    ///     + (1 - tokenless_production) * working_supply * balance / total_balance)
+    pub fn working_balance(
+        &self,
+        deposit: Uint128,
+        working_supply: Uint128,
+        balance: Uint128,
+        total_balance: Uint128,
+    ) -> Uint128 {
+        let mut working_balance = deposit * self.tokenless_production;
+
+        if !total_balance.is_zero() {
+            working_balance += working_supply.multiply_ratio(balance, total_balance)
+                * (Decimal::one() - self.tokenless_production);
+        }
+
+        working_balance.min(deposit)
+    }
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -337,3 +294,348 @@ pub struct State {
     /// History tracking
     pub timestamp: u64,
 }
+
+/// An entry of a lock changelog, matching the layout used by SnapshotMap
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockChangeSet {
+    pub old: Option<UserLockedBalance>,
+}
+
+/// Config as stored before the owner and curve parameters became configurable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub cw20_address: Option<Addr>,
+    pub owner: Addr,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
+/// Locks as stored before lock ids were added, keyed only by user
+pub const LEGACY_USER_LOCKED_BALANCES: Map<&Addr, UserLockedBalance> =
+    Map::new("user_locked_balance");
+
+/// Changelog of LEGACY_USER_LOCKED_BALANCES, keyed by user and timestamp
+pub const LEGACY_USER_LOCKED_BALANCES_CHANGELOG: Map<(&Addr, U64Key), LockChangeSet> =
+    Map::new("user_locked_balance__changelog");
+
+/// A lock as it was after an update, along with the action which produced it
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockCheckpoint {
+    pub lock_id: u64,
+    pub timestamp: u64,
+    pub action: LockAction,
+    pub user_locked_balance: UserLockedBalance,
+}
+
+/// Infer the action of an update made before actions were recorded,
+/// when locks could only be created, increased, or withdrawn
+fn legacy_lock_action(
+    prev_user_locked_balance: &UserLockedBalance,
+    new_user_locked_balance: &UserLockedBalance,
+) -> LockAction {
+    if new_user_locked_balance.is_void_or_undefined() {
+        LockAction::Withdraw
+    } else if prev_user_locked_balance.is_void_or_undefined() {
+        LockAction::CreateLock
+    } else if new_user_locked_balance.deposited_amount > prev_user_locked_balance.deposited_amount {
+        LockAction::IncreaseLockAmount
+    } else if new_user_locked_balance.end_lock_time > prev_user_locked_balance.end_lock_time {
+        LockAction::IncreaseEndLockTime
+    } else {
+        LockAction::Withdraw
+    }
+}
+
+/// Keys of a user's entries in USER_LOCKED_BALANCES_CHANGELOG are the length prefixed lock id
+/// followed by the timestamp
+fn lock_changelog_key(lock_id: u64, timestamp: u64) -> Vec<u8> {
+    [
+        &8u16.to_be_bytes()[..],
+        &lock_id.to_be_bytes(),
+        &timestamp.to_be_bytes(),
+    ]
+    .concat()
+}
+
+fn parse_lock_changelog_key(key: &[u8]) -> StdResult<(u64, u64)> {
+    if key.len() != 18 || key[..2] != 8u16.to_be_bytes() {
+        return Err(StdError::generic_err("Invalid lock changelog key"));
+    }
+
+    let lock_id = u64::from_be_bytes(key[2..10].try_into().unwrap());
+    let timestamp = u64::from_be_bytes(key[10..].try_into().unwrap());
+
+    Ok((lock_id, timestamp))
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_whitelisted_contracts(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_bytes()));
+
+    WHITELISTED_CONTRACTS
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|k| Ok(Addr::unchecked(String::from_utf8(k)?)))
+        .collect()
+}
+
+/// Load a user's lock, defaulting to an undefined lock
+pub fn load_user_locked_balance(
+    storage: &dyn Storage,
+    user: &Addr,
+    lock_id: u64,
+) -> StdResult<UserLockedBalance> {
+    Ok(USER_LOCKED_BALANCES
+        .may_load(storage, (user, U64Key::from(lock_id)))?
+        .unwrap_or_default())
+}
+
+/// Return whether or not a user holds any lock which hasn't been withdrawn
+pub fn user_has_lock(storage: &dyn Storage, user: &Addr) -> StdResult<bool> {
+    let lock_count = USER_LOCK_COUNT.may_load(storage, user)?.unwrap_or_default();
+
+    for lock_id in 0..lock_count {
+        if load_user_locked_balance(storage, user, lock_id)?.exists() {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Return whether or not any of a user's locks is active at a timestamp
+pub fn user_has_active_lock(storage: &dyn Storage, user: &Addr, timestamp: u64) -> StdResult<bool> {
+    let lock_count = USER_LOCK_COUNT.may_load(storage, user)?.unwrap_or_default();
+
+    for lock_id in 0..lock_count {
+        if load_user_locked_balance(storage, user, lock_id)?.status_at_timestamp(timestamp)
+            == LockStatus::Active
+        {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Load all of a user's locks as of a timestamp, along with their lock ids.
+/// Void and undefined locks are included.
+pub fn read_user_locked_balances_at_timestamp(
+    storage: &dyn Storage,
+    user: &Addr,
+    timestamp: u64,
+) -> StdResult<Vec<(u64, UserLockedBalance)>> {
+    let lock_count = USER_LOCK_COUNT.may_load(storage, user)?.unwrap_or_default();
+
+    (0..lock_count)
+        .map(|lock_id| {
+            let user_locked_balance = USER_LOCKED_BALANCES
+                .may_load_at_height(storage, (user, U64Key::from(lock_id)), timestamp)?
+                .unwrap_or_default();
+            Ok((lock_id, user_locked_balance))
+        })
+        .collect()
+}
+
+/// Maximum number of lock ids read by a single page of read_all_user_locked_balances_at_timestamp,
+/// including those of locks which are skipped
+pub const MAX_SCANNED_LOCKS: u32 = 300;
+
+/// Load the locks of all users as of a timestamp, ordered by address and lock id,
+/// starting after the given (address, lock_id).
+/// Undefined locks are skipped, as are locks which aren't in the given state.
+/// At most MAX_SCANNED_LOCKS lock ids are read, so a page can hold fewer than `limit` locks
+/// even when more remain. The (address, lock_id) to continue after is returned alongside the
+/// locks, and is None once every lock has been read.
+#[allow(clippy::type_complexity)]
+pub fn read_all_user_locked_balances_at_timestamp(
+    storage: &dyn Storage,
+    start_after: Option<(Addr, u64)>,
+    limit: Option<u32>,
+    timestamp: u64,
+    status: Option<LockStatus>,
+) -> StdResult<(Vec<(Addr, u64, UserLockedBalance)>, Option<(Addr, u64)>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|(addr, _)| Bound::inclusive(addr.as_bytes()));
+
+    let mut user_locked_balances = vec![];
+    let mut scanned_locks = 0;
+    let mut last_scanned_lock = None;
+
+    for item in USER_LOCK_COUNT.range(storage, start, None, Order::Ascending) {
+        let (k, lock_count) = item?;
+        let user = Addr::unchecked(String::from_utf8(k)?);
+
+        // Skip the locks of the start address which were returned in the previous page
+        let first_lock_id = match &start_after {
+            Some((addr, lock_id)) if *addr == user => lock_id + 1,
+            _ => 0,
+        };
+
+        for lock_id in first_lock_id..lock_count {
+            if user_locked_balances.len() == limit || scanned_locks == MAX_SCANNED_LOCKS {
+                return Ok((user_locked_balances, last_scanned_lock));
+            }
+
+            scanned_locks += 1;
+            last_scanned_lock = Some((user.clone(), lock_id));
+
+            let user_locked_balance = USER_LOCKED_BALANCES.may_load_at_height(
+                storage,
+                (&user, U64Key::from(lock_id)),
+                timestamp,
+            )?;
+
+            let user_locked_balance = match user_locked_balance {
+                Some(user_locked_balance) => user_locked_balance,
+                None => continue,
+            };
+
+            if let Some(status) = status {
+                if user_locked_balance.status_at_timestamp(timestamp) != status {
+                    continue;
+                }
+            }
+
+            user_locked_balances.push((user.clone(), lock_id, user_locked_balance));
+        }
+    }
+
+    Ok((user_locked_balances, None))
+}
+
+/// Load a page of a user's lock history from the USER_LOCKED_BALANCES changelog,
+/// ordered by lock id and then by timestamp
+pub fn read_user_lock_history(
+    storage: &dyn Storage,
+    user: &Addr,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LockCheckpoint>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|(lock_id, timestamp)| Bound::exclusive(lock_changelog_key(lock_id, timestamp)));
+
+    // Read one more entry than needed, since each entry holds the lock before its update
+    let changes = USER_LOCKED_BALANCES_CHANGELOG
+        .sub_prefix(user)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .map(|item| {
+            let (k, change) = item?;
+            let (lock_id, timestamp) = parse_lock_changelog_key(&k)?;
+            Ok((lock_id, timestamp, change.old.unwrap_or_default()))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    changes
+        .iter()
+        .enumerate()
+        .take(limit)
+        .map(|(i, (lock_id, timestamp, prev_user_locked_balance))| {
+            // The lock after an update is held by the next entry of the same lock,
+            // or is the current lock if there is none
+            let user_locked_balance = match changes.get(i + 1) {
+                Some((next_lock_id, _, next_prev_user_locked_balance))
+                    if next_lock_id == lock_id =>
+                {
+                    next_prev_user_locked_balance.clone()
+                }
+                _ => load_user_locked_balance(storage, user, *lock_id)?,
+            };
+
+            let action = match USER_LOCK_ACTIONS.may_load(
+                storage,
+                (user, U64Key::from(*lock_id), U64Key::from(*timestamp)),
+            )? {
+                Some(action) => action,
+                None => legacy_lock_action(prev_user_locked_balance, &user_locked_balance),
+            };
+
+            Ok(LockCheckpoint {
+                lock_id: *lock_id,
+                timestamp: *timestamp,
+                action,
+                user_locked_balance,
+            })
+        })
+        .collect()
+}
+
+/// Return the sum of the voting power of all of a user's locks at a timestamp
+pub fn user_voting_power_at_timestamp(
+    storage: &dyn Storage,
+    config: &Config,
+    user: &Addr,
+    timestamp: u64,
+) -> StdResult<Uint128> {
+    Ok(
+        read_user_locked_balances_at_timestamp(storage, user, timestamp)?
+            .iter()
+            .map(|(_, user_locked_balance)| {
+                user_locked_balance.voting_power_at_timestamp(config, timestamp)
+            })
+            .sum(),
+    )
+}
+
+/// Move each user's lock from before lock ids were added to lock id 0, along with its changelog.
+/// Must only be called on storage written before lock ids were added,
+/// as the legacy and current keys share their namespaces.
+pub fn migrate_legacy_user_locked_balances(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy_user_locked_balances = LEGACY_USER_LOCKED_BALANCES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, user_locked_balance) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k)?), user_locked_balance))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (user, user_locked_balance) in legacy_user_locked_balances {
+        let changes = LEGACY_USER_LOCKED_BALANCES_CHANGELOG
+            .prefix(&user)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (k, change_set) = item?;
+                let timestamp = u64::from_be_bytes(
+                    k.as_slice()
+                        .try_into()
+                        .map_err(|_| StdError::generic_err("Invalid lock changelog key"))?,
+                );
+                Ok((timestamp, change_set))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (timestamp, change_set) in changes {
+            LEGACY_USER_LOCKED_BALANCES_CHANGELOG.remove(storage, (&user, U64Key::from(timestamp)));
+            USER_LOCKED_BALANCES_CHANGELOG.save(
+                storage,
+                (&user, U64Key::from(0), U64Key::from(timestamp)),
+                &change_set,
+            )?;
+        }
+
+        LEGACY_USER_LOCKED_BALANCES.remove(storage, &user);
+
+        // The lock's last update already has a changelog entry,
+        // so this only moves the current value
+        USER_LOCKED_BALANCES.save(
+            storage,
+            (&user, U64Key::from(0)),
+            &user_locked_balance,
+            user_locked_balance.timestamp,
+        )?;
+
+        USER_LOCK_COUNT.save(storage, &user, &1)?;
+    }
+
+    Ok(())
+}
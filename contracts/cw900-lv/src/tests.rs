@@ -1,30 +1,46 @@
 use crate::{
-    contract::{execute, instantiate, query},
+    contract::{execute, instantiate, migrate, query},
     error::ContractError,
+    mock_querier::mock_dependencies,
     state::{
-        UserLockedBalance, MAX_SECONDS, SECONDS_PER_WEEK, STATE, USER_LOCKED_BALANCES,
-        VOTING_POWER_CONSTANT_DIVISOR,
+        Config, LegacyConfig, LockChangeSet, QuadraticEquationCoefficients, UserLockedBalance,
+        COEFFICIENT_CHANGES, CONFIG, DEFAULT_MAX_WEEKS, DEFAULT_SECONDS_PER_WEEK,
+        DEFAULT_VOTING_POWER_CONSTANT_DIVISOR, LEGACY_CONFIG, LEGACY_USER_LOCKED_BALANCES,
+        LEGACY_USER_LOCKED_BALANCES_CHANGELOG, MAX_DELEGATORS_PER_DELEGATE, MAX_LOCKS_PER_USER,
+        MAX_LOCK_DURATION, MAX_PENDING_WEEKS, MAX_SCANNED_LOCKS, STATE, USER_LOCKED_BALANCES,
+        USER_LOCK_COUNT,
     },
 };
 use cosmwasm_std::{
-    from_binary,
-    testing::{mock_dependencies, mock_env, mock_info},
-    to_binary, Addr, CosmosMsg, DepsMut, Env, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, from_binary,
+    testing::{mock_env, mock_info},
+    to_binary, Addr, CosmosMsg, Decimal, Deps, DepsMut, Env, StdError, SubMsg, Timestamp, Uint128,
+    WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw900::ve_token::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StakerResponse, StateResponse,
+    BalancesResponse, BoostResponse, ConfigResponse, Cw20HookMsg, DecayModel, ExecuteMsg,
+    InstantiateMsg, LockAction, LockCheckpointResponse, LockStatus, MigrateMsg,
+    PendingCheckpointResponse, QueryMsg, StakerHistoryResponse, StakerLocksResponse,
+    StakerResponse, StakersResponse, StakersResponseItem, StateResponse, WhitelistResponse,
 };
+use cw_storage_plus::U64Key;
 
 const TEST_CREATOR: &str = "creator";
 const VOTING_TOKEN: &str = "voting_token";
 const TEST_VOTER: &str = "voter1";
 const TEST_VOTER_2: &str = "voter2";
-// const TEST_VOTER_3: &str = "voter3";
+const TEST_CONTRACT: &str = "contract1";
+const FEE_DISTRIBUTOR: &str = "fee_distributor";
+const TEST_VOTER_3: &str = "voter3";
+const GAUGE_CONTROLLER: &str = "gauge_controller";
+const SECONDS_PER_WEEK: u64 = DEFAULT_SECONDS_PER_WEEK;
+const MAX_SECONDS: u64 = DEFAULT_MAX_WEEKS * DEFAULT_SECONDS_PER_WEEK;
+const VOTING_POWER_CONSTANT_DIVISOR: u64 = DEFAULT_VOTING_POWER_CONSTANT_DIVISOR;
 const BLOCKS_PER_SECOND: f64 = 0.16;
 
 fn mock_instantiate(deps: DepsMut, env: Env) {
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg::default();
 
     let info = mock_info(TEST_CREATOR, &[]);
     let _res =
@@ -74,7 +90,7 @@ pub fn one_depositor_query_staker() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
@@ -117,6 +133,9 @@ pub fn one_depositor_query_staker() {
         deposited_amount: Uint128::from(1000000000u128),
         locked_amount: Uint128::from(999999918u128),
         balance: Uint128::from(384615321u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(384615321u128),
     };
 
     assert_eq!(staker_info, expected_staker_info);
@@ -141,6 +160,9 @@ pub fn one_depositor_query_staker() {
         deposited_amount: Uint128::from(1000000000u128),
         locked_amount: Uint128::from(975000000u128),
         balance: Uint128::from(365624999u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(365624999u128),
     };
     assert_eq!(staker_info, expected_staker_info);
 
@@ -170,6 +192,11 @@ pub fn one_depositor_query_staker() {
         balance: Uint128::from(
             deposit_amount as u64 / 2 * SECONDS_PER_WEEK * 10 / VOTING_POWER_CONSTANT_DIVISOR,
         ),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(
+            deposit_amount as u64 / 2 * SECONDS_PER_WEEK * 10 / VOTING_POWER_CONSTANT_DIVISOR,
+        ),
     };
 
     assert_eq!(expected_staker_info, staker_info);
@@ -198,6 +225,9 @@ pub fn one_depositor_query_staker() {
         locked_amount: Uint128::from(83u128),
         // balance is locked_amount * remaining time / constant multiplier
         balance: Uint128::from(0u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(0u128),
     };
 
     assert_eq!(expected_staker_info, staker_info);
@@ -225,6 +255,9 @@ pub fn one_depositor_query_staker() {
         locked_amount: Uint128::from(0u128),
         // balance is locked_amount * remaining time / constant multiplier
         balance: Uint128::from(0u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(0u128),
     };
 
     assert_eq!(expected_staker_info, staker_info);
@@ -252,6 +285,9 @@ pub fn one_depositor_query_staker() {
         locked_amount: Uint128::from(0u128),
         // balance is locked_amount * remaining time / constant multiplier
         balance: Uint128::from(0u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(0u128),
     };
 
     assert_eq!(expected_staker_info, staker_info);
@@ -279,6 +315,9 @@ pub fn one_depositor_query_staker() {
         locked_amount: Uint128::from(0u128),
         // balance is locked_amount * remaining time / constant multiplier
         balance: Uint128::from(0u128),
+        delegated_balance: Uint128::zero(),
+        delegate: None,
+        voting_power: Uint128::from(0u128),
     };
 
     assert_eq!(expected_staker_info, staker_info);
@@ -307,14 +346,16 @@ pub fn voting_power_consistency() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     // Make sure that the voting power stays close to the expected voting power for many iterations
@@ -323,10 +364,14 @@ pub fn voting_power_consistency() {
         // They might be off by a little bit due to rounding errors.
 
         // voting_power is calculated using the corresponding quadratic equation
-        let voting_power = user_locked_balance.voting_power_at_timestamp(env.block.time.seconds());
+        let voting_power =
+            user_locked_balance.voting_power_at_timestamp(&config, env.block.time.seconds());
         // expected_voting_power is calculated using the simplified equation
-        let expected_voting_power =
-            calculate_voting_power_at_timestamp(&user_locked_balance, env.block.time.seconds());
+        let expected_voting_power = calculate_voting_power_at_timestamp(
+            &config,
+            &user_locked_balance,
+            env.block.time.seconds(),
+        );
 
         assert!(
             voting_power > expected_voting_power - Uint128::from(10u128)
@@ -362,14 +407,14 @@ pub fn expired_at_edge() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     // Test that the lock is not expired one second before expiration
@@ -405,162 +450,2867 @@ pub fn test_underflow_overflow() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
-    println!("Locked balance: {:?}", user_locked_balance);
+    println!("Locked balance: {:?}", user_locked_balance);
+
+    increase_env_time(&mut env, 1);
+
+    // Read the staker info
+    let staker_info: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    println!("Staker info: {:?}", staker_info);
+}
+
+#[test]
+pub fn test_create_lock_validation() {
+    // Set the time to right before the next week starts
+    let env = mock_env_time(SECONDS_PER_WEEK - 1);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+
+    // Try and fail to lock up a zero amount
+
+    let deposit_amount: u128 = 0;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds() + 1;
+
+    // Create the lock
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    match execute_res {
+        Err(ContractError::InsufficientLockAmount {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try and fail to lock with end_lock_time in the past
+
+    let deposit_amount: u128 = 1;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds() - 1;
+
+    // Create the lock
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    match execute_res {
+        Err(ContractError::EndLockTimeTooEarly {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try and fail to lock with end_lock_time in the past
+
+    let deposit_amount: u128 = 1;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds();
+
+    // Create the lock
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    match execute_res {
+        Err(ContractError::EndLockTimeTooEarly {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try to lock up for longer than 52 weeks.
+
+    let deposit_amount: u128 = 1;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds() + MAX_SECONDS + 1;
+
+    // Create the lock
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    match execute_res {
+        Err(ContractError::EndLockTimeTooLate { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Succeed to lock up for 52 weeks with amount of 1
+
+    let deposit_amount: u128 = 1;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds() + MAX_SECONDS;
+
+    // Create the lock
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let _execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Create more locks until the maximum number of locks is reached
+
+    let deposit_amount: u128 = 1;
+
+    // Sent the end_lock_time for 20 weeks in the future
+    let end_lock_time = env.block.time.seconds() + MAX_SECONDS;
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+
+    for _ in 1..MAX_LOCKS_PER_USER {
+        let _execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    }
+
+    // Try and fail to create a lock when the maximum number of locks already exist
+
+    let execute_res = execute(deps.as_mut(), env, info, msg);
+
+    match execute_res {
+        Err(ContractError::TooManyLocks { max_locks }) => {
+            assert_eq!(max_locks, MAX_LOCKS_PER_USER)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_contracts_cannot_interact_with_locks() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_contracts(&[TEST_CONTRACT]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+
+    let deposit_amount: u128 = 1000 * u128::pow(10, 6);
+    let end_lock_time = SECONDS_PER_WEEK * 3;
+
+    // Try and fail to create a lock from a contract
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CONTRACT.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg.clone());
+
+    match res {
+        Err(ContractError::ContractsCannotInteractWithLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try and fail to withdraw from a contract
+
+    let contract_info = mock_info(TEST_CONTRACT, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        contract_info.clone(),
+        ExecuteMsg::Withdraw { lock_id: 0 },
+    );
+
+    match res {
+        Err(ContractError::ContractsCannotInteractWithLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only the owner can whitelist contracts
+
+    let whitelist_msg = ExecuteMsg::AddToWhitelist {
+        contract_address: TEST_CONTRACT.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        whitelist_msg.clone(),
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        whitelist_msg,
+    )
+    .unwrap();
+
+    let whitelist: WhitelistResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Whitelist {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(whitelist.contracts, vec![TEST_CONTRACT.to_string()]);
+
+    // Whitelisted contracts can create locks
+
+    let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+
+    // Remove the contract from the whitelist
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::RemoveFromWhitelist {
+            contract_address: TEST_CONTRACT.to_string(),
+        },
+    )
+    .unwrap();
+
+    // Try and fail to increase the lock amount from a contract which is no longer whitelisted
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CONTRACT.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), token_info, msg);
+
+    match res {
+        Err(ContractError::ContractsCannotInteractWithLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try and fail to increase the end lock time from a contract which is no longer whitelisted
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        contract_info,
+        ExecuteMsg::IncreaseEndLockTime {
+            lock_id: 0,
+            end_lock_time: SECONDS_PER_WEEK * 4,
+        },
+    );
+
+    match res {
+        Err(ContractError::ContractsCannotInteractWithLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_update_config() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+
+    // Only the owner can register contracts
+
+    let msg = ExecuteMsg::RegisterContracts {
+        cw20_address: VOTING_TOKEN.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.cw20_address, None);
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // Contracts can only be registered once
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only the owner can update the config
+
+    let msg = ExecuteMsg::UpdateConfig {
+        cw20_address: Some("new_voting_token".to_string()),
+        penalty_recipient: None,
+        early_withdraw_penalty_rate: None,
+        tokenless_production: None,
+        gauge_controller: None,
+        fee_distributor: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.cw20_address, Some("new_voting_token".to_string()));
+
+    // Create a lock
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: SECONDS_PER_WEEK * 3,
+        })
+        .unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("new_voting_token", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // The token can't be changed once there are deposits
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: Some(VOTING_TOKEN.to_string()),
+            penalty_recipient: None,
+            early_withdraw_penalty_rate: None,
+            tokenless_production: None,
+            gauge_controller: None,
+            fee_distributor: None,
+        },
+    );
+
+    match res {
+        Err(ContractError::CannotUpdateTokenWithDeposits {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_ownership_transfer() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+
+    // Nothing to accept or cancel yet
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+
+    match res {
+        Err(ContractError::NoPendingOwner {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    );
+
+    match res {
+        Err(ContractError::NoPendingOwner {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only the owner can propose a new owner
+
+    let msg = ExecuteMsg::ProposeNewOwner {
+        new_owner: TEST_VOTER.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, TEST_CREATOR.to_string());
+    assert_eq!(config.pending_owner, Some(TEST_VOTER.to_string()));
+
+    // Cancel the proposal
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+
+    match res {
+        Err(ContractError::NoPendingOwner {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Propose again, only the pending owner can accept
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, TEST_VOTER.to_string());
+    assert_eq!(config.pending_owner, None);
+
+    // The previous owner no longer has any permissions
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::RegisterContracts {
+            cw20_address: VOTING_TOKEN.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_delegation() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let end_lock_time = SECONDS_PER_WEEK * 20;
+
+    // Create a lock for each voter
+
+    for voter in [TEST_VOTER, TEST_VOTER_2] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(1000 * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
+
+    increase_env_time(&mut env, 1);
+    let before_delegation_time = env.block.time.seconds();
+
+    // Validate delegation
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::CannotDelegateToSelf {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Undelegate {},
+    );
+
+    match res {
+        Err(ContractError::NotDelegated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Addresses without an active lock have nothing to delegate
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_3, &[]),
+        ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::NoActiveLock {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Delegate voter1's voting power to voter2
+
+    let msg = ExecuteMsg::Delegate {
+        to: TEST_VOTER_2.to_string(),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+
+    match res {
+        Err(ContractError::AlreadyDelegated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Delegation is not transitive
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, 1);
+
+    let query_staker = |deps: Deps, env: Env, address: &str, timestamp: Option<u64>| {
+        let res: StakerResponse = from_binary(
+            &query(
+                deps,
+                env,
+                QueryMsg::Staker {
+                    address: address.to_string(),
+                    timestamp,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res
+    };
+
+    let voter_1 = query_staker(deps.as_ref(), env.clone(), TEST_VOTER, None);
+    let voter_2 = query_staker(deps.as_ref(), env.clone(), TEST_VOTER_2, None);
+
+    assert_eq!(voter_1.delegate, Some(TEST_VOTER_2.to_string()));
+    assert_eq!(voter_2.delegate, Some(TEST_VOTER.to_string()));
+    assert_eq!(voter_1.delegated_balance, voter_2.balance);
+    assert_eq!(voter_2.delegated_balance, voter_1.balance);
+    assert_eq!(voter_1.voting_power, voter_2.balance);
+    assert_eq!(voter_2.voting_power, voter_1.balance);
+
+    // Delegations are snapshotted
+
+    let voter_1 = query_staker(
+        deps.as_ref(),
+        env.clone(),
+        TEST_VOTER,
+        Some(before_delegation_time),
+    );
+
+    assert_eq!(voter_1.delegate, None);
+    assert_eq!(voter_1.delegated_balance, Uint128::zero());
+    assert_eq!(voter_1.voting_power, voter_1.balance);
+
+    // Undelegate voter2's voting power
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::Undelegate {},
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, 1);
+
+    let voter_1 = query_staker(deps.as_ref(), env.clone(), TEST_VOTER, None);
+    let voter_2 = query_staker(deps.as_ref(), env, TEST_VOTER_2, None);
+
+    assert_eq!(voter_1.delegated_balance, Uint128::zero());
+    assert_eq!(voter_1.voting_power, Uint128::zero());
+    assert_eq!(voter_2.delegate, None);
+    assert_eq!(voter_2.voting_power, voter_1.balance + voter_2.balance);
+}
+
+#[test]
+pub fn test_delegators_are_capped() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let end_lock_time = SECONDS_PER_WEEK * 20;
+
+    // Fill up all of voter1's delegator slots, and then try to delegate once more
+
+    for i in 0..=MAX_DELEGATORS_PER_DELEGATE {
+        let delegator = format!("delegator{}", i);
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: delegator.clone(),
+            amount: Uint128::from(1u128),
+            msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&delegator, &[]),
+            ExecuteMsg::Delegate {
+                to: TEST_VOTER.to_string(),
+            },
+        );
+
+        if i < MAX_DELEGATORS_PER_DELEGATE {
+            res.unwrap();
+        } else {
+            match res {
+                Err(ContractError::TooManyDelegators { max_delegators }) => {
+                    assert_eq!(max_delegators, MAX_DELEGATORS_PER_DELEGATE)
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            }
+        }
+    }
+
+    // A delegator leaving frees up a slot
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("delegator0", &[]),
+        ExecuteMsg::Undelegate {},
+    )
+    .unwrap();
+
+    let _res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&format!("delegator{}", MAX_DELEGATORS_PER_DELEGATE), &[]),
+        ExecuteMsg::Delegate {
+            to: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+pub fn test_dust_delegators_can_be_removed() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let delegate_msg = ExecuteMsg::Delegate {
+        to: TEST_VOTER.to_string(),
+    };
+
+    // Fill up all of voter1's delegator slots with dust locks which end in two weeks
+
+    for i in 0..MAX_DELEGATORS_PER_DELEGATE {
+        let delegator = format!("delegator{}", i);
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: delegator.clone(),
+            amount: Uint128::from(1u128),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * 3,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&delegator, &[]),
+            delegate_msg.clone(),
+        )
+        .unwrap();
+    }
+
+    for voter in ["honest0", "honest1"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(1000 * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * 20,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
+
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("honest0", &[]),
+        delegate_msg.clone(),
+    ) {
+        Err(ContractError::TooManyDelegators { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only the delegate can remove its delegators
+
+    let remove_msg = ExecuteMsg::RemoveDelegator {
+        delegator: "delegator0".to_string(),
+    };
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        remove_msg.clone(),
+    ) {
+        Err(ContractError::NotDelegated {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        remove_msg,
+    )
+    .unwrap();
+
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: "delegator0".to_string(),
+                timestamp: Some(env.block.time.seconds() + 1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.delegate, None);
+
+    // The removed delegator can't take the slot back, but someone else can
+
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("delegator0", &[]),
+        delegate_msg.clone(),
+    ) {
+        Err(ContractError::DelegatorRemoved {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("honest0", &[]),
+        delegate_msg.clone(),
+    )
+    .unwrap();
+
+    // Once the dust locks expire, their delegators stop holding slots
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 3);
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("honest1", &[]),
+        delegate_msg,
+    )
+    .unwrap();
+
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: "delegator1".to_string(),
+                timestamp: Some(env.block.time.seconds() + 1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(staker.delegate, None);
+}
+
+#[test]
+pub fn test_multiple_locks() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+
+    // Create two locks with different end lock times
+
+    for weeks in [3, 20] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_VOTER.to_string(),
+            amount: Uint128::from(1000 * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * weeks,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let both_locks_time = env.block.time.seconds();
+
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let staker_locks: StakerLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerLocks {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // Staker aggregates over the locks
+
+    assert_eq!(
+        staker_locks
+            .locks
+            .iter()
+            .map(|lock| lock.lock_id)
+            .collect::<Vec<u64>>(),
+        vec![0, 1]
+    );
+    assert_eq!(staker_locks.locks[0].end_lock_time, SECONDS_PER_WEEK * 3);
+    assert_eq!(staker_locks.locks[1].end_lock_time, SECONDS_PER_WEEK * 20);
+    assert_eq!(
+        staker.deposited_amount,
+        staker_locks.locks[0].deposited_amount + staker_locks.locks[1].deposited_amount
+    );
+    assert_eq!(
+        staker.balance,
+        staker_locks.locks[0].balance + staker_locks.locks[1].balance
+    );
+    assert_eq!(state.total_deposited_amount, staker.deposited_amount);
+    assert!(state.total_balance.u128().abs_diff(staker.balance.u128()) <= 1);
+
+    // Withdraw the first lock after it expires
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 2);
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Withdraw { lock_id: 0 },
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, 1);
+
+    let staker_locks: StakerLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerLocks {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(staker_locks.locks.len(), 1);
+    assert_eq!(staker_locks.locks[0].lock_id, 1);
+
+    // Past lock breakdowns are still available
+
+    let staker_locks: StakerLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerLocks {
+                address: TEST_VOTER.to_string(),
+                timestamp: Some(both_locks_time),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(staker_locks.locks.len(), 2);
+
+    // The lock id of the withdrawn lock is reused
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(1000 * u128::pow(10, 6)),
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: SECONDS_PER_WEEK * 10,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, token_info, msg).unwrap();
+
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "lock_id" && attr.value == "0"));
+}
+
+#[test]
+pub fn test_migrate_legacy_locks() {
+    let env = mock_env_time(SECONDS_PER_WEEK * 2);
+    let mut deps = mock_dependencies(&[]);
+
+    // Store the config, state and a lock as they were before lock ids were added.
+    // The lock was created at create_time and increased at increase_time.
+
+    let user = Addr::unchecked(TEST_VOTER);
+    let create_time = SECONDS_PER_WEEK + 10;
+    let increase_time = create_time + 100;
+
+    let created_lock = UserLockedBalance {
+        deposited_amount: Uint128::from(1000u128),
+        end_lock_time: SECONDS_PER_WEEK * 10,
+        start_lock_time: create_time,
+        timestamp: create_time,
+    };
+    let increased_lock = UserLockedBalance {
+        deposited_amount: Uint128::from(3000u128),
+        start_lock_time: increase_time,
+        timestamp: increase_time,
+        ..created_lock.clone()
+    };
+
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                cw20_address: Some(Addr::unchecked(VOTING_TOKEN)),
+                owner: Addr::unchecked(TEST_CREATOR),
+            },
+        )
+        .unwrap();
+    LEGACY_USER_LOCKED_BALANCES
+        .save(deps.as_mut().storage, &user, &increased_lock)
+        .unwrap();
+    for (timestamp, old) in [
+        (create_time, None),
+        (increase_time, Some(created_lock.clone())),
+    ] {
+        LEGACY_USER_LOCKED_BALANCES_CHANGELOG
+            .save(
+                deps.as_mut().storage,
+                (&user, U64Key::from(timestamp)),
+                &LockChangeSet { old },
+            )
+            .unwrap();
+    }
+
+    for _ in 0..2 {
+        // Migrating an already migrated contract changes nothing
+        let _res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        let config: ConfigResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(config.owner, TEST_CREATOR);
+        assert_eq!(config.cw20_address, Some(VOTING_TOKEN.to_string()));
+        assert_eq!(config.seconds_per_week, DEFAULT_SECONDS_PER_WEEK);
+        assert_eq!(config.max_weeks, DEFAULT_MAX_WEEKS);
+        assert_eq!(
+            config.voting_power_constant_divisor,
+            DEFAULT_VOTING_POWER_CONSTANT_DIVISOR
+        );
+        assert_eq!(config.decay_model, DecayModel::LinearUnlock);
+        assert_eq!(config.penalty_recipient, None);
+        assert_eq!(config.early_withdraw_penalty_rate, Decimal::zero());
+
+        // The lock moved to lock id 0, keeping its history
+
+        assert_eq!(
+            LEGACY_USER_LOCKED_BALANCES
+                .may_load(deps.as_ref().storage, &user)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            USER_LOCK_COUNT.load(deps.as_ref().storage, &user).unwrap(),
+            1
+        );
+
+        for (timestamp, deposited_amount) in [
+            (create_time, 0u128),
+            (create_time + 1, 1000u128),
+            (increase_time + 1, 3000u128),
+        ] {
+            let staker: StakerResponse = from_binary(
+                &query(
+                    deps.as_ref(),
+                    env.clone(),
+                    QueryMsg::Staker {
+                        address: TEST_VOTER.to_string(),
+                        timestamp: Some(timestamp),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(staker.deposited_amount, Uint128::from(deposited_amount));
+        }
+
+        assert_eq!(
+            USER_LOCKED_BALANCES
+                .load(deps.as_ref().storage, (&user, U64Key::from(0)))
+                .unwrap(),
+            increased_lock
+        );
+
+        // The staker history is read from the migrated changelog
+
+        let history: StakerHistoryResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::StakerHistory {
+                    address: TEST_VOTER.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            history
+                .history
+                .iter()
+                .map(|checkpoint| (
+                    checkpoint.action,
+                    checkpoint.timestamp,
+                    checkpoint.deposited_amount
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (LockAction::CreateLock, create_time, Uint128::from(1000u128)),
+                (
+                    LockAction::IncreaseLockAmount,
+                    increase_time,
+                    Uint128::from(3000u128)
+                ),
+            ]
+        );
+    }
+}
+
+#[test]
+pub fn test_early_withdraw() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let deposit_amount: u128 = 1000 * u128::pow(10, 6);
+    let end_lock_time = SECONDS_PER_WEEK + MAX_SECONDS;
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // Early withdraws are disabled until a penalty recipient is set
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::EarlyWithdraw { lock_id: 0 };
+    let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg.clone());
+
+    match res {
+        Err(ContractError::EarlyWithdrawDisabled {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The penalty rate can't be more than one
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: Some(FEE_DISTRIBUTOR.to_string()),
+            early_withdraw_penalty_rate: Some(Decimal::percent(101)),
+            tokenless_production: None,
+            gauge_controller: None,
+            fee_distributor: None,
+        },
+    );
+
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "early_withdraw_penalty_rate must be 0 to 1")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: Some(FEE_DISTRIBUTOR.to_string()),
+            early_withdraw_penalty_rate: Some(Decimal::percent(50)),
+            tokenless_production: None,
+            gauge_controller: None,
+            fee_distributor: None,
+        },
+    )
+    .unwrap();
+
+    // Withdraw early with a quarter of the lock time remaining.
+    // Three quarters of the deposit are unlocked and are withdrawn without a penalty.
+    // A quarter of the deposit is locked, and the penalty on it is
+    // 50% * 1/4 = 12.5% of the locked amount.
+
+    increase_env_time(&mut env, MAX_SECONDS / 4 * 3);
+
+    let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg.clone()).unwrap();
+
+    let penalty_amount = deposit_amount / 4 / 8;
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_VOTER.to_string(),
+                    amount: Uint128::from(deposit_amount - penalty_amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: FEE_DISTRIBUTOR.to_string(),
+                    amount: Uint128::from(penalty_amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // The lock is void and its coefficients are fully removed
+
+    let user_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(0)),
+        )
+        .unwrap();
+    assert!(user_locked_balance.is_void_or_undefined());
+
+    let state = STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+    assert_eq!(
+        state.voting_power_coefficients,
+        QuadraticEquationCoefficients::default()
+    );
+    assert_eq!(
+        COEFFICIENT_CHANGES
+            .load(deps.as_ref().storage, U64Key::from(end_lock_time))
+            .unwrap(),
+        QuadraticEquationCoefficients::default()
+    );
+
+    // The lock can't be withdrawn again
+
+    let res = execute(deps.as_mut(), env, voter_info, msg);
+
+    match res {
+        Err(ContractError::LockDoesNotExist {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_transfer_lock() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let end_lock_time = SECONDS_PER_WEEK * 20;
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(1000 * u128::pow(10, 6)),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+
+    // Try and fail to transfer to yourself
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::TransferLock {
+            lock_id: 0,
+            recipient: TEST_VOTER.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::CannotTransferToSelf {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Try and fail to transfer a lock which doesn't exist
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::TransferLock {
+            lock_id: 1,
+            recipient: TEST_VOTER_2.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::LockDoesNotExist {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let user_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(0)),
+        )
+        .unwrap();
+    let state_before_transfer: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // Transfer the lock
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info,
+        ExecuteMsg::TransferLock {
+            lock_id: 0,
+            recipient: TEST_VOTER_2.to_string(),
+        },
+    )
+    .unwrap();
+
+    // The sender's lock is void and the recipient holds the same lock
+
+    let sender_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(0)),
+        )
+        .unwrap();
+    assert!(sender_locked_balance.is_void_or_undefined());
+
+    let recipient_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER_2), U64Key::from(0)),
+        )
+        .unwrap();
+    assert_eq!(
+        recipient_locked_balance,
+        UserLockedBalance {
+            timestamp: env.block.time.seconds(),
+            ..user_locked_balance
+        }
+    );
+
+    // The global curve is unchanged
+
+    let state_after_transfer: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state_before_transfer, state_after_transfer);
+
+    increase_env_time(&mut env, 1);
+
+    let recipient: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER_2.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(
+        state
+            .total_balance
+            .u128()
+            .abs_diff(recipient.balance.u128())
+            <= 1
+    );
+
+    // Try and fail to transfer another lock to an address which now holds a lock
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(1u128),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::TransferLock {
+            lock_id: 0,
+            recipient: TEST_VOTER_2.to_string(),
+        },
+    );
+
+    match res {
+        Err(ContractError::RecipientHasLock {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+pub fn test_merge_lock() {
+    // Start far enough from the epoch for the merged start_lock_time to be positive
+    let mut env = mock_env_time(SECONDS_PER_WEEK * 100);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    // Create two locks with different end lock times
+
+    for weeks in [110, 120] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_VOTER.to_string(),
+            amount: Uint128::from(1000 * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * weeks,
+            })
+            .unwrap(),
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 5);
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+
+    // Try and fail to merge a lock with itself
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::MergeLock {
+            lock_id: 0,
+            other_lock_id: 0,
+        },
+    );
+
+    match res {
+        Err(ContractError::CannotMergeLockWithItself {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let staker_before_merge: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // Merge the second lock into the first one
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info,
+        ExecuteMsg::MergeLock {
+            lock_id: 0,
+            other_lock_id: 1,
+        },
+    )
+    .unwrap();
+
+    let merged_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(0)),
+        )
+        .unwrap();
+    let other_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(1)),
+        )
+        .unwrap();
+
+    assert!(other_locked_balance.is_void_or_undefined());
+    assert_eq!(merged_locked_balance.end_lock_time, SECONDS_PER_WEEK * 120);
+    assert_eq!(
+        merged_locked_balance.deposited_amount,
+        staker_before_merge.deposited_amount
+    );
+
+    // The locked amount is preserved, up to rounding in favor of the lock
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    let merged_locked_amount =
+        merged_locked_balance.locked_amount_at_timestamp(&config, env.block.time.seconds());
+    assert!(merged_locked_amount >= staker_before_merge.locked_amount);
+    assert!(merged_locked_amount.u128() - staker_before_merge.locked_amount.u128() <= 1000);
+
+    // The scheduled changes of the voided lock are removed
+
+    assert_eq!(
+        COEFFICIENT_CHANGES
+            .load(deps.as_ref().storage, U64Key::from(SECONDS_PER_WEEK * 110))
+            .unwrap(),
+        QuadraticEquationCoefficients::default()
+    );
+
+    // The global curve matches the merged lock
+
+    for _ in 0..4 {
+        increase_env_time(&mut env, SECONDS_PER_WEEK * 4);
+
+        let staker: StakerResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Staker {
+                    address: TEST_VOTER.to_string(),
+                    timestamp: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let state: StateResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::State { timestamp: None },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert!(state.total_balance.u128().abs_diff(staker.balance.u128()) <= 1);
+        assert!(
+            state
+                .total_locked_amount
+                .u128()
+                .abs_diff(staker.locked_amount.u128())
+                <= 1
+        );
+    }
+}
+
+#[test]
+pub fn test_gauge_votes_block_lock_changes() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: Some(FEE_DISTRIBUTOR.to_string()),
+            early_withdraw_penalty_rate: Some(Decimal::percent(50)),
+            tokenless_production: None,
+            gauge_controller: Some(GAUGE_CONTROLLER.to_string()),
+            fee_distributor: None,
+        },
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.gauge_controller, Some(GAUGE_CONTROLLER.to_string()));
+
+    for _ in 0..2 {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_VOTER.to_string(),
+            amount: Uint128::from(1000 * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * 10,
+            })
+            .unwrap(),
+        });
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    // The voter allocated voting power to gauges, so its votes would outlive
+    // a transferred, merged or early withdrawn lock
+
+    deps.querier.with_gauge_votes(TEST_VOTER, 5000);
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+    for msg in [
+        ExecuteMsg::TransferLock {
+            lock_id: 0,
+            recipient: TEST_VOTER_2.to_string(),
+        },
+        ExecuteMsg::MergeLock {
+            lock_id: 0,
+            other_lock_id: 1,
+        },
+        ExecuteMsg::EarlyWithdraw { lock_id: 0 },
+    ] {
+        let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg);
+
+        match res {
+            Err(ContractError::LockHasGaugeVotes {}) => {}
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    // The locks are untouched
+
+    let res: StakerLocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerLocks {
+                address: TEST_VOTER.to_string(),
+                timestamp: Some(env.block.time.seconds() + 1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.locks.len(), 2);
+
+    // Once the votes are removed the locks can be changed again
+
+    deps.querier.with_gauge_votes(TEST_VOTER, 0);
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::MergeLock {
+            lock_id: 0,
+            other_lock_id: 1,
+        },
+    )
+    .unwrap();
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::TransferLock {
+            lock_id: 0,
+            recipient: TEST_VOTER_2.to_string(),
+        },
+    )
+    .unwrap();
+
+    // Votes of the recipient only block its own lock changes
+
+    deps.querier.with_gauge_votes(TEST_VOTER_2, 5000);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::EarlyWithdraw { lock_id: 0 },
+    );
+
+    match res {
+        Err(ContractError::LockHasGaugeVotes {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_gauge_votes(TEST_VOTER_2, 0);
+
+    let _res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::EarlyWithdraw { lock_id: 0 },
+    )
+    .unwrap();
+}
+
+#[test]
+pub fn test_cliff_unlock_with_daily_epochs() {
+    let seconds_per_day: u64 = 24 * 60 * 60;
+    let max_days: u64 = 4 * 365;
+    let lock_days: u64 = 200;
+
+    let mut env = mock_env_time(seconds_per_day * 10);
+    let mut deps = mock_dependencies(&[]);
+
+    // Curve parameters must be positive
+
+    let res = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        InstantiateMsg {
+            seconds_per_week: Some(0),
+            ..InstantiateMsg::default()
+        },
+    );
+
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "seconds_per_week must be positive")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The maximum lock duration must not overflow or exceed MAX_LOCK_DURATION
+
+    for max_weeks in [u64::MAX, MAX_LOCK_DURATION / seconds_per_day + 1] {
+        let res = instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(TEST_CREATOR, &[]),
+            InstantiateMsg {
+                seconds_per_week: Some(seconds_per_day),
+                max_weeks: Some(max_weeks),
+                ..InstantiateMsg::default()
+            },
+        );
+
+        match res {
+            Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+                msg,
+                format!(
+                    "max_weeks * seconds_per_week must be at most {}",
+                    MAX_LOCK_DURATION
+                )
+            ),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let _res = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        InstantiateMsg {
+            seconds_per_week: Some(seconds_per_day),
+            max_weeks: Some(max_days),
+            voting_power_constant_divisor: Some(seconds_per_day * lock_days),
+            decay_model: Some(DecayModel::CliffUnlock),
+        },
+    )
+    .unwrap();
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.seconds_per_week, seconds_per_day);
+    assert_eq!(config.max_weeks, max_days);
+    assert_eq!(
+        config.voting_power_constant_divisor,
+        seconds_per_day * lock_days
+    );
+    assert_eq!(config.decay_model, DecayModel::CliffUnlock);
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let deposit_amount: u128 = 1000 * u128::pow(10, 6);
+
+    // Try and fail to lock for longer than the maximum number of days
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: seconds_per_day * (10 + max_days + 1),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg);
+
+    match res {
+        Err(ContractError::EndLockTimeTooLate {
+            max_weeks,
+            lock_duration_in_weeks,
+        }) => {
+            assert_eq!(max_weeks, max_days);
+            assert_eq!(lock_duration_in_weeks, max_days + 1);
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Lock for 200 days, which provides one unit of voting power per token at the start.
+    // The end lock time is rounded down to the start of the day.
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: seconds_per_day * (10 + lock_days) + 100,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
+
+    let user_locked_balance = USER_LOCKED_BALANCES
+        .load(
+            deps.as_ref().storage,
+            (&Addr::unchecked(TEST_VOTER), U64Key::from(0)),
+        )
+        .unwrap();
+    assert_eq!(
+        user_locked_balance.end_lock_time,
+        seconds_per_day * (10 + lock_days)
+    );
+
+    // Half way through the lock, the whole deposit is still locked
+    // and the voting power has decayed linearly to half
+
+    increase_env_time(&mut env, seconds_per_day * lock_days / 2);
+
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(staker.locked_amount, Uint128::from(deposit_amount));
+    assert_eq!(staker.balance, Uint128::from(deposit_amount / 2));
+    assert_eq!(state.total_locked_amount, Uint128::from(deposit_amount));
+    assert_eq!(state.total_balance, Uint128::from(deposit_amount / 2));
+
+    // Nothing can be withdrawn before the lock expires
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info.clone(),
+        ExecuteMsg::Withdraw { lock_id: 0 },
+    );
+
+    match res {
+        Err(ContractError::NothingToWithdraw {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The whole deposit can be withdrawn once the lock expires
+
+    increase_env_time(&mut env, seconds_per_day * lock_days / 2);
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        voter_info,
+        ExecuteMsg::Withdraw { lock_id: 0 },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(deposit_amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::State { timestamp: None }).unwrap())
+            .unwrap();
+    assert_eq!(state.total_locked_amount, Uint128::zero());
+    assert_eq!(state.total_balance, Uint128::zero());
+}
+
+#[test]
+pub fn test_stakers_query() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let deposit_amount = Uint128::from(1000 * u128::pow(10, 6));
+
+    // voter1 creates two locks, voter2 creates one
+
+    for (voter, weeks) in [(TEST_VOTER, 3), (TEST_VOTER, 20), (TEST_VOTER_2, 4)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: deposit_amount,
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * weeks,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
+
+    // voter1 withdraws its first lock once it has expired
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 4);
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::Withdraw { lock_id: 0 },
+    )
+    .unwrap();
+
+    // Changes become visible in the following second
+    increase_env_time(&mut env, 1);
+
+    let query_stakers = |start_after: Option<(String, u64)>,
+                         limit: Option<u32>,
+                         timestamp: Option<u64>,
+                         status: Option<LockStatus>|
+     -> StakersResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Stakers {
+                    start_after,
+                    limit,
+                    timestamp,
+                    status,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Page through all locks
+
+    let res = query_stakers(None, Some(2), None, None);
+    let page = res.stakers;
+    assert_eq!(
+        page,
+        vec![
+            StakersResponseItem {
+                address: TEST_VOTER.to_string(),
+                lock_id: 0,
+                deposited_amount: Uint128::zero(),
+                locked_amount: Uint128::zero(),
+                balance: Uint128::zero(),
+                end_lock_time: 0,
+            },
+            StakersResponseItem {
+                address: TEST_VOTER.to_string(),
+                lock_id: 1,
+                deposited_amount: deposit_amount,
+                locked_amount: page[1].locked_amount,
+                balance: page[1].balance,
+                end_lock_time: SECONDS_PER_WEEK * 20,
+            },
+        ]
+    );
+    assert!(page[1].locked_amount > Uint128::zero());
+    assert!(page[1].balance > Uint128::zero());
+    assert_eq!(res.next_start_after, Some((TEST_VOTER.to_string(), 1)));
+
+    let res = query_stakers(res.next_start_after, Some(2), None, None);
+    assert_eq!(
+        res.stakers,
+        vec![StakersResponseItem {
+            address: TEST_VOTER_2.to_string(),
+            lock_id: 0,
+            deposited_amount: deposit_amount,
+            locked_amount: Uint128::zero(),
+            balance: Uint128::zero(),
+            end_lock_time: SECONDS_PER_WEEK * 4,
+        }]
+    );
+    assert_eq!(res.next_start_after, None);
+
+    let res = query_stakers(Some((TEST_VOTER_2.to_string(), 0)), Some(2), None, None);
+    assert_eq!(res.stakers, vec![]);
+    assert_eq!(res.next_start_after, None);
+
+    // Filter by lock state
+
+    let lock_ids = |res: StakersResponse| -> Vec<(String, u64)> {
+        res.stakers
+            .into_iter()
+            .map(|staker| (staker.address, staker.lock_id))
+            .collect()
+    };
+
+    assert_eq!(
+        lock_ids(query_stakers(None, None, None, Some(LockStatus::Active))),
+        vec![(TEST_VOTER.to_string(), 1)]
+    );
+    assert_eq!(
+        lock_ids(query_stakers(None, None, None, Some(LockStatus::Expired))),
+        vec![(TEST_VOTER_2.to_string(), 0)]
+    );
+    assert_eq!(
+        lock_ids(query_stakers(None, None, None, Some(LockStatus::Void))),
+        vec![(TEST_VOTER.to_string(), 0)]
+    );
+
+    // All locks were active in the past
+
+    assert_eq!(
+        lock_ids(query_stakers(
+            None,
+            None,
+            Some(SECONDS_PER_WEEK * 2),
+            Some(LockStatus::Active)
+        )),
+        vec![
+            (TEST_VOTER.to_string(), 0),
+            (TEST_VOTER.to_string(), 1),
+            (TEST_VOTER_2.to_string(), 0)
+        ]
+    );
+
+    // No locks existed before they were created
+
+    assert_eq!(
+        query_stakers(None, None, Some(SECONDS_PER_WEEK - 1), None).stakers,
+        vec![]
+    );
+}
+
+#[test]
+pub fn test_stakers_query_scans_bounded_number_of_locks() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+
+    // Create one more active lock than a page can read
+
+    for i in 0..=MAX_SCANNED_LOCKS {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: format!("staker{:03}", i),
+            amount: Uint128::from(1u128),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * 10,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
+
+    let query_void_stakers = |start_after: Option<(String, u64)>| -> StakersResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Stakers {
+                    start_after,
+                    limit: None,
+                    timestamp: None,
+                    status: Some(LockStatus::Void),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // None of the locks match, so the first page stops after reading MAX_SCANNED_LOCKS locks
+
+    let res = query_void_stakers(None);
+    assert_eq!(res.stakers, vec![]);
+    assert_eq!(
+        res.next_start_after,
+        Some((format!("staker{:03}", MAX_SCANNED_LOCKS - 1), 0))
+    );
+
+    let res = query_void_stakers(res.next_start_after);
+    assert_eq!(res.stakers, vec![]);
+    assert_eq!(res.next_start_after, None);
+}
+
+#[test]
+pub fn test_staker_history() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let voter_info = mock_info(TEST_VOTER, &[]);
+    let deposit_amount = Uint128::from(1000 * u128::pow(10, 6));
+
+    // Create a lock
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: SECONDS_PER_WEEK * 10,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+
+    // Increase its amount
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
+
+    // Extend it
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
+        end_lock_time: SECONDS_PER_WEEK * 20,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg).unwrap();
+
+    // Withdraw what has unlocked
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 2);
+
+    let msg = ExecuteMsg::Withdraw { lock_id: 0 };
+    let _res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg).unwrap();
+
+    // Transfer it to voter2
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let msg = ExecuteMsg::TransferLock {
+        lock_id: 0,
+        recipient: TEST_VOTER_2.to_string(),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), voter_info, msg).unwrap();
+
+    // voter2 merges a new lock into the received one, and then withdraws it early
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: SECONDS_PER_WEEK * 10,
+        })
+        .unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let voter2_info = mock_info(TEST_VOTER_2, &[]);
+    let msg = ExecuteMsg::MergeLock {
+        lock_id: 0,
+        other_lock_id: 1,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), voter2_info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        cw20_address: None,
+        penalty_recipient: Some(FEE_DISTRIBUTOR.to_string()),
+        early_withdraw_penalty_rate: Some(Decimal::percent(50)),
+        tokenless_production: None,
+        gauge_controller: None,
+        fee_distributor: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let msg = ExecuteMsg::EarlyWithdraw { lock_id: 0 };
+    let _res = execute(deps.as_mut(), env.clone(), voter2_info, msg).unwrap();
+
+    let query_history = |address: &str,
+                         start_after: Option<(u64, u64)>,
+                         limit: Option<u32>|
+     -> Vec<LockCheckpointResponse> {
+        let res: StakerHistoryResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::StakerHistory {
+                    address: address.to_string(),
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.history
+    };
+
+    let history = query_history(TEST_VOTER, None, None);
+
+    assert_eq!(
+        history
+            .iter()
+            .map(|checkpoint| (checkpoint.lock_id, checkpoint.action, checkpoint.timestamp))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, LockAction::CreateLock, SECONDS_PER_WEEK),
+            (0, LockAction::IncreaseLockAmount, SECONDS_PER_WEEK * 2),
+            (0, LockAction::IncreaseEndLockTime, SECONDS_PER_WEEK * 3),
+            (0, LockAction::Withdraw, SECONDS_PER_WEEK * 5),
+            (0, LockAction::TransferLock, SECONDS_PER_WEEK * 6),
+        ]
+    );
+
+    assert_eq!(
+        history[0],
+        LockCheckpointResponse {
+            lock_id: 0,
+            action: LockAction::CreateLock,
+            timestamp: SECONDS_PER_WEEK,
+            deposited_amount: deposit_amount,
+            start_lock_time: SECONDS_PER_WEEK,
+            end_lock_time: SECONDS_PER_WEEK * 10,
+        }
+    );
+    assert_eq!(
+        history[1].deposited_amount,
+        deposit_amount * Uint128::from(2u128)
+    );
+    assert_eq!(history[2].end_lock_time, SECONDS_PER_WEEK * 20);
+    assert!(history[3].deposited_amount < history[2].deposited_amount);
+    assert_eq!(history[3].start_lock_time, SECONDS_PER_WEEK * 5);
+    assert_eq!(history[4].deposited_amount, Uint128::zero());
+
+    // Page through the history
+
+    assert_eq!(
+        query_history(TEST_VOTER, Some((0, SECONDS_PER_WEEK * 2)), Some(2)),
+        history[2..4].to_vec()
+    );
+    assert_eq!(
+        query_history(TEST_VOTER, Some((0, SECONDS_PER_WEEK * 6)), None),
+        vec![]
+    );
+
+    // The recipient's history starts with the transfer
+
+    let recipient_history = query_history(TEST_VOTER_2, None, None);
+    assert_eq!(
+        recipient_history[0],
+        LockCheckpointResponse {
+            lock_id: 0,
+            action: LockAction::ReceiveLock,
+            timestamp: SECONDS_PER_WEEK * 6,
+            deposited_amount: history[3].deposited_amount,
+            start_lock_time: history[3].start_lock_time,
+            end_lock_time: SECONDS_PER_WEEK * 20,
+        }
+    );
+    assert_eq!(
+        recipient_history
+            .iter()
+            .map(|checkpoint| (checkpoint.lock_id, checkpoint.action, checkpoint.timestamp))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, LockAction::ReceiveLock, SECONDS_PER_WEEK * 6),
+            (0, LockAction::MergeLock, SECONDS_PER_WEEK * 8),
+            (0, LockAction::EarlyWithdraw, SECONDS_PER_WEEK * 9),
+            (1, LockAction::CreateLock, SECONDS_PER_WEEK * 7),
+            (1, LockAction::MergeLock, SECONDS_PER_WEEK * 8),
+        ]
+    );
+}
+
+#[test]
+pub fn test_bounded_checkpoint() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+    let deposit_amount = Uint128::from(1000 * u128::pow(10, 6));
+
+    let create_lock_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: SECONDS_PER_WEEK * 10,
+        })
+        .unwrap(),
+    });
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        token_info.clone(),
+        create_lock_msg,
+    )
+    .unwrap();
+
+    // Nobody touches the contract for 300 weeks
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 300);
+
+    let pending_checkpoint: PendingCheckpointResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingCheckpoint {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        pending_checkpoint,
+        PendingCheckpointResponse {
+            state_timestamp: SECONDS_PER_WEEK,
+            pending_weeks: 300,
+            max_pending_weeks: MAX_PENDING_WEEKS,
+        }
+    );
+
+    // Queries still account for every pending week
+
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state.total_deposited_amount, deposit_amount);
+    assert_eq!(state.total_locked_amount, Uint128::zero());
+    assert_eq!(state.total_balance, Uint128::zero());
+
+    // User actions fail while the state lags too far behind
+
+    let create_lock_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: deposit_amount,
+        msg: to_binary(&Cw20HookMsg::CreateLock {
+            end_lock_time: env.block.time.seconds() + SECONDS_PER_WEEK * 10,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        token_info.clone(),
+        create_lock_msg.clone(),
+    );
+
+    match res {
+        Err(ContractError::CheckpointRequired { pending_weeks }) => {
+            assert_eq!(pending_weeks, 300)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Process part of the backlog
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::Checkpoint {
+            max_weeks: Some(100),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "checkpoint"),
+            attr("state_timestamp", (SECONDS_PER_WEEK * 101).to_string()),
+            attr("remaining_weeks", "200"),
+        ]
+    );
+
+    let pending_checkpoint: PendingCheckpointResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingCheckpoint {}).unwrap())
+            .unwrap();
+    assert_eq!(pending_checkpoint.pending_weeks, 200);
+
+    // Process the rest of the backlog
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::Checkpoint { max_weeks: None },
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "checkpoint"),
+            attr("state_timestamp", (SECONDS_PER_WEEK * 301).to_string()),
+            attr("remaining_weeks", "0"),
+        ]
+    );
+
+    // User actions are allowed again
+
+    let _res = execute(deps.as_mut(), env.clone(), token_info, create_lock_msg).unwrap();
 
     increase_env_time(&mut env, 1);
 
-    // Read the staker info
-    let staker_info: StakerResponse = from_binary(
+    let state: StateResponse = from_binary(
         &query(
             deps.as_ref(),
             env.clone(),
-            QueryMsg::Staker {
-                address: TEST_VOTER.to_string(),
-                timestamp: None,
-            },
+            QueryMsg::State { timestamp: None },
         )
         .unwrap(),
     )
     .unwrap();
-
-    println!("Staker info: {:?}", staker_info);
+    assert_eq!(
+        state.total_deposited_amount,
+        deposit_amount * Uint128::from(2u128)
+    );
+    assert!(state.total_balance > Uint128::zero());
 }
 
 #[test]
-pub fn test_create_lock_validation() {
-    // Set the time to right before the next week starts
-    let env = mock_env_time(SECONDS_PER_WEEK - 1);
+pub fn test_boost() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
     let mut deps = mock_dependencies(&[]);
 
     mock_instantiate(deps.as_mut(), env.clone());
     mock_register_contracts(deps.as_mut(), env.clone());
 
-    let info = mock_info(VOTING_TOKEN, &[]);
-
-    // Try and fail to lock up a zero amount
+    let token_info = mock_info(VOTING_TOKEN, &[]);
 
-    let deposit_amount: u128 = 0;
+    // voter1 holds a quarter of the voting power, voter2 holds the rest
 
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds() + 1;
+    for (voter, amount) in [(TEST_VOTER, 1000u128), (TEST_VOTER_2, 3000u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(amount * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: env.block.time.seconds() + MAX_SECONDS,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    increase_env_time(&mut env, 1);
 
-    match execute_res {
-        Err(ContractError::InsufficientLockAmount {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let query_boost = |deps: Deps, address: &str, working_supply: u128, deposit: u128| {
+        from_binary::<BoostResponse>(
+            &query(
+                deps,
+                env.clone(),
+                QueryMsg::Boost {
+                    address: address.to_string(),
+                    working_supply: Uint128::from(working_supply),
+                    deposit: Uint128::from(deposit),
+                    timestamp: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
 
-    // Try and fail to lock with end_lock_time in the past
+    // A small deposit receives the maximum boost
 
-    let deposit_amount: u128 = 1;
+    assert_eq!(
+        query_boost(deps.as_ref(), TEST_VOTER, 1_000_000, 100_000),
+        BoostResponse {
+            working_balance: Uint128::from(100_000u128),
+            boost: Decimal::from_ratio(5u128, 2u128),
+        }
+    );
 
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds() - 1;
+    // A larger deposit is boosted in proportion to the staker's share of the voting power,
+    // which is roughly a quarter
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State { timestamp: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    match execute_res {
-        Err(ContractError::EndLockTimeTooEarly {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    // 0.4 * 400_000 + 0.6 * 1_000_000 * balance / total_balance
+    let expected_working_balance = Uint128::from(160_000u128)
+        + Uint128::from(600_000u128).multiply_ratio(staker.balance, state.total_balance);
 
-    // Try and fail to lock with end_lock_time in the past
+    let boost = query_boost(deps.as_ref(), TEST_VOTER, 1_000_000, 400_000);
+    assert_eq!(boost.working_balance, expected_working_balance);
+    assert!(boost.working_balance > Uint128::from(309_990u128));
+    assert_eq!(
+        boost.boost,
+        Decimal::from_ratio(expected_working_balance, 160_000u128)
+    );
 
-    let deposit_amount: u128 = 1;
+    // Deposits without voting power aren't boosted
 
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds();
+    assert_eq!(
+        query_boost(deps.as_ref(), TEST_CREATOR, 1_000_000, 400_000),
+        BoostResponse {
+            working_balance: Uint128::from(160_000u128),
+            boost: Decimal::one(),
+        }
+    );
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    // tokenless_production must be positive and at most 1
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: None,
+            early_withdraw_penalty_rate: None,
+            tokenless_production: Some(Decimal::zero()),
+            gauge_controller: None,
+            fee_distributor: None,
+        },
+    );
 
-    match execute_res {
-        Err(ContractError::EndLockTimeTooEarly {}) => {}
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => assert_eq!(
+            msg,
+            "tokenless_production must be greater than 0 and at most 1"
+        ),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // Try to lock up for longer than 52 weeks.
-
-    let deposit_amount: u128 = 1;
-
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds() + MAX_SECONDS + 1;
+    // Without a boost, the working balance is the deposit
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: None,
+            early_withdraw_penalty_rate: None,
+            tokenless_production: Some(Decimal::one()),
+            gauge_controller: None,
+            fee_distributor: None,
+        },
+    )
+    .unwrap();
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(
+        query_boost(deps.as_ref(), TEST_VOTER, 1_000_000, 400_000),
+        BoostResponse {
+            working_balance: Uint128::from(400_000u128),
+            boost: Decimal::one(),
+        }
+    );
+}
 
-    match execute_res {
-        Err(ContractError::EndLockTimeTooLate { .. }) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+#[test]
+pub fn test_batch_balances() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
 
-    // Succeed to lock up for 52 weeks with amount of 1
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
 
-    let deposit_amount: u128 = 1;
+    let token_info = mock_info(VOTING_TOKEN, &[]);
 
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds() + MAX_SECONDS;
+    for (voter, amount) in [(TEST_VOTER, 1000u128), (TEST_VOTER_2, 3000u128)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(amount * u128::pow(10, 6)),
+            msg: to_binary(&Cw20HookMsg::CreateLock {
+                end_lock_time: SECONDS_PER_WEEK * 11,
+            })
+            .unwrap(),
+        });
+        let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+    }
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let _execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let timestamps: Vec<u64> = (1..=12).map(|week| week * SECONDS_PER_WEEK).collect();
 
-    // Try and fail to create a lock when one already exists
+    let res: BalancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerBalances {
+                address: TEST_VOTER.to_string(),
+                timestamps: timestamps.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let total_res: BalancesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::TotalBalances {
+                timestamps: timestamps.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    let deposit_amount: u128 = 1;
+    assert_eq!(res.balances.len(), timestamps.len());
+    assert_eq!(total_res.balances.len(), timestamps.len());
+
+    // Each balance matches the corresponding single timestamp query
+
+    for (i, timestamp) in timestamps.into_iter().enumerate() {
+        let staker: StakerResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Staker {
+                    address: TEST_VOTER.to_string(),
+                    timestamp: Some(timestamp),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let state: StateResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::State {
+                    timestamp: Some(timestamp),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
 
-    // Sent the end_lock_time for 20 weeks in the future
-    let end_lock_time = env.block.time.seconds() + MAX_SECONDS;
+        assert_eq!(res.balances[i], staker.balance);
+        assert_eq!(total_res.balances[i], state.total_balance);
+    }
 
-    // Create the lock
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
-    });
-    let execute_res = execute(deps.as_mut(), env, info, msg);
+    // The locks only count from the second after they were created, and expire after week 11
 
-    match execute_res {
-        Err(ContractError::LockAlreadyExists {}) => {}
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    assert_eq!(res.balances[0], Uint128::zero());
+    assert!(!res.balances[1].is_zero());
+    assert!(total_res.balances[1] > res.balances[1]);
+    assert_eq!(res.balances[10], Uint128::zero());
+    assert_eq!(total_res.balances[11], Uint128::zero());
 }
 
 #[test]
@@ -580,6 +3330,7 @@ pub fn test_increase_end_lock_time_validation() {
 
     let voter_info = mock_info(TEST_VOTER, &[]);
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 3,
     };
     let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg);
@@ -600,7 +3351,7 @@ pub fn test_increase_end_lock_time_validation() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env, token_info, msg).unwrap();
@@ -612,6 +3363,7 @@ pub fn test_increase_end_lock_time_validation() {
     // Try to increase the end lock time of an expired lock
 
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 5,
     };
     let res = execute(deps.as_mut(), env, voter_info.clone(), msg);
@@ -628,6 +3380,7 @@ pub fn test_increase_end_lock_time_validation() {
     // Try to decrease the end lock time / keep it the same
 
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 3,
     };
     let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg);
@@ -640,6 +3393,7 @@ pub fn test_increase_end_lock_time_validation() {
     // Try to increase the end lock time too much
 
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 60,
     };
     let res = execute(deps.as_mut(), env.clone(), voter_info.clone(), msg);
@@ -652,6 +3406,7 @@ pub fn test_increase_end_lock_time_validation() {
     // Increase the end lock time successfully
 
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 30,
     };
     let _res = execute(deps.as_mut(), env, voter_info, msg).unwrap();
@@ -676,7 +3431,11 @@ pub fn test_increase_lock_amount_validation() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(increase_amount as u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
     });
     let res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg);
 
@@ -696,7 +3455,7 @@ pub fn test_increase_lock_amount_validation() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env, token_info.clone(), msg).unwrap();
@@ -710,7 +3469,11 @@ pub fn test_increase_lock_amount_validation() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(increase_amount as u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
     });
     let res = execute(deps.as_mut(), env, token_info.clone(), msg);
 
@@ -728,7 +3491,11 @@ pub fn test_increase_lock_amount_validation() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(0u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
     });
     let res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg);
 
@@ -742,9 +3509,101 @@ pub fn test_increase_lock_amount_validation() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(increase_amount as u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
+
+    // Try and fail to re-lock someone else's tokens by adding dust to their lock,
+    // which would reset its start_lock_time
+
+    let grief_msg = |sender: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::from(1u128),
+            msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+                lock_id: 0,
+                address: Some(TEST_VOTER.to_string()),
+            })
+            .unwrap(),
+        })
+    };
+
+    // Not even the fee distributor can until it is configured
+    for sender in [TEST_VOTER_2, FEE_DISTRIBUTOR] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            token_info.clone(),
+            grief_msg(sender),
+        );
+
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("DO NOT ENTER"),
+        };
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateConfig {
+            cw20_address: None,
+            penalty_recipient: None,
+            early_withdraw_penalty_rate: None,
+            tokenless_production: None,
+            gauge_controller: None,
+            fee_distributor: Some(FEE_DISTRIBUTOR.to_string()),
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        token_info.clone(),
+        grief_msg(TEST_VOTER_2),
+    );
+
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER"),
+    };
+
+    // The fee distributor increases the lock amount on behalf of the lock owner
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: FEE_DISTRIBUTOR.to_string(),
+        amount: Uint128::from(increase_amount as u128),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: Some(TEST_VOTER.to_string()),
+        })
+        .unwrap(),
     });
     let _res = execute(deps.as_mut(), env, token_info, msg).unwrap();
+
+    let env = mock_env_time(SECONDS_PER_WEEK + 1);
+    let res: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.deposited_amount,
+        Uint128::from(deposit_amount + 2 * increase_amount as u128)
+    );
 }
 
 #[test]
@@ -762,7 +3621,7 @@ pub fn test_create_lock_user_locked_balances_update() {
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap_or_default();
 
     // Create a lock
@@ -776,14 +3635,14 @@ pub fn test_create_lock_user_locked_balances_update() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env, token_info, msg).unwrap();
 
     // Read the user's locked balance
     let new_user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     assert_eq!(user_locked_balance, UserLockedBalance::default());
@@ -822,7 +3681,7 @@ pub fn test_increase_amount_user_locked_balances_update() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
@@ -831,20 +3690,24 @@ pub fn test_increase_amount_user_locked_balances_update() {
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap_or_default();
 
     // Increase the amount
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
 
     // Read the user's locked balance
     let new_user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     assert_eq!(
@@ -882,7 +3745,7 @@ pub fn test_increase_end_lock_time_user_locked_balances_update() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
@@ -891,18 +3754,19 @@ pub fn test_increase_end_lock_time_user_locked_balances_update() {
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap_or_default();
 
     let voter_info = mock_info(TEST_VOTER, &[]);
     let msg = ExecuteMsg::IncreaseEndLockTime {
+        lock_id: 0,
         end_lock_time: SECONDS_PER_WEEK * 4,
     };
     let _res = execute(deps.as_mut(), env.clone(), voter_info, msg).unwrap();
 
     // Read the user's locked balance
     let new_user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     assert_eq!(
@@ -940,7 +3804,7 @@ pub fn test_full_withdraw_user_locked_balances_update() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
@@ -949,11 +3813,11 @@ pub fn test_full_withdraw_user_locked_balances_update() {
 
     // Read the user's locked balance
     let _user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     let voter_info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw { lock_id: 0 };
     let res = execute(deps.as_mut(), env.clone(), voter_info, msg).unwrap();
 
     // Res should include a message to return all of the user's deposited glow
@@ -973,7 +3837,7 @@ pub fn test_full_withdraw_user_locked_balances_update() {
 
     // Read the user's locked balance
     let new_user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     assert_eq!(
@@ -982,6 +3846,72 @@ pub fn test_full_withdraw_user_locked_balances_update() {
     );
 }
 
+#[test]
+pub fn test_withdraw_expired_lock_keeps_applied_coefficient_changes() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_register_contracts(deps.as_mut(), env.clone());
+
+    let token_info = mock_info(VOTING_TOKEN, &[]);
+
+    // Create a lock which expires in week 3
+
+    let deposit_amount: u128 = 1000 * u128::pow(10, 6);
+    let end_lock_time = SECONDS_PER_WEEK * 3;
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(deposit_amount),
+        msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
+    });
+    let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
+
+    let coefficient_changes = COEFFICIENT_CHANGES
+        .load(deps.as_ref().storage, U64Key::from(end_lock_time))
+        .unwrap();
+
+    // Withdraw as soon as the lock expires
+
+    increase_env_time(&mut env, 2 * SECONDS_PER_WEEK);
+
+    let voter_info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::Withdraw { lock_id: 0 };
+    let _res = execute(deps.as_mut(), env.clone(), voter_info, msg).unwrap();
+    increase_env_time(&mut env, 2 * SECONDS_PER_WEEK);
+
+    // The coefficient changes at the end of the lock were already applied to state,
+    // so they must be left in place for past timestamps to be evaluated correctly
+
+    assert_eq!(
+        COEFFICIENT_CHANGES
+            .load(deps.as_ref().storage, U64Key::from(end_lock_time))
+            .unwrap(),
+        coefficient_changes
+    );
+
+    for timestamp in [
+        SECONDS_PER_WEEK * 3,
+        SECONDS_PER_WEEK * 4,
+        env.block.time.seconds(),
+    ] {
+        let state: StateResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::State {
+                    timestamp: Some(timestamp),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(state.total_balance, Uint128::zero());
+        assert_eq!(state.total_locked_amount, Uint128::zero());
+    }
+}
+
 #[test]
 pub fn test_partial_withdraw_user_locked_balances_update() {
     // Set the time to right before the next week starts
@@ -1006,7 +3936,7 @@ pub fn test_partial_withdraw_user_locked_balances_update() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
@@ -1015,11 +3945,11 @@ pub fn test_partial_withdraw_user_locked_balances_update() {
 
     // Read the user's locked balance
     let user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     let voter_info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::Withdraw {};
+    let msg = ExecuteMsg::Withdraw { lock_id: 0 };
     let res = execute(deps.as_mut(), env.clone(), voter_info, msg).unwrap();
 
     // Res should include a message to return all of the user's deposited glow
@@ -1039,7 +3969,7 @@ pub fn test_partial_withdraw_user_locked_balances_update() {
 
     // Read the user's locked balance
     let new_user_locked_balance = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user)
+        .load(deps.as_mut().storage, (&user, U64Key::from(0)))
         .unwrap();
 
     assert_eq!(
@@ -1080,7 +4010,7 @@ pub fn two_depositors_query_total_balance() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info.clone(), msg).unwrap();
@@ -1096,7 +4026,7 @@ pub fn two_depositors_query_total_balance() {
     // Create the lock
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
-        amount: Uint128::from(deposit_amount as u128),
+        amount: Uint128::from(deposit_amount),
         msg: to_binary(&Cw20HookMsg::CreateLock { end_lock_time }).unwrap(),
     });
     let _execute_res = execute(deps.as_mut(), env.clone(), token_info, msg).unwrap();
@@ -1104,21 +4034,29 @@ pub fn two_depositors_query_total_balance() {
     // Verify that the total balance equals the sum of the individual balances
     // at multiple different times
 
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+
     // Read the user's locked balance
     let user_locked_balance_1 = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user1)
+        .load(deps.as_mut().storage, (&user1, U64Key::from(0)))
         .unwrap();
 
     // Read the user's locked balance
     let user_locked_balance_2 = USER_LOCKED_BALANCES
-        .load(deps.as_mut().storage, &user2)
+        .load(deps.as_mut().storage, (&user2, U64Key::from(0)))
         .unwrap();
 
-    let user1_power =
-        calculate_voting_power_at_timestamp(&user_locked_balance_1, env.block.time.seconds());
+    let user1_power = calculate_voting_power_at_timestamp(
+        &config,
+        &user_locked_balance_1,
+        env.block.time.seconds(),
+    );
 
-    let user2_power =
-        calculate_voting_power_at_timestamp(&user_locked_balance_2, env.block.time.seconds());
+    let user2_power = calculate_voting_power_at_timestamp(
+        &config,
+        &user_locked_balance_2,
+        env.block.time.seconds(),
+    );
 
     let state = STATE.load(deps.as_ref().storage).unwrap();
 
@@ -1126,7 +4064,10 @@ pub fn two_depositors_query_total_balance() {
         user1_power + user2_power,
         state
             .voting_power_coefficients
-            .evaluate_voting_power_at_timestamp(env.block.time.seconds())
+            .evaluate_voting_power_at_timestamp(
+                env.block.time.seconds(),
+                config.voting_power_constant_divisor
+            )
     );
 
     // Increase the env time
@@ -1251,7 +4192,11 @@ pub fn two_depositors_query_total_balance() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
         amount: Uint128::from(increase_amount as u128),
-        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::IncreaseLockAmount {
+            lock_id: 0,
+            address: None,
+        })
+        .unwrap(),
     });
     let _res = execute(deps.as_mut(), env.clone(), token_info, msg);
 
@@ -1410,10 +4355,11 @@ pub fn two_depositors_query_total_balance() {
 }
 
 pub fn calculate_voting_power_at_timestamp(
+    config: &Config,
     locked_balance: &UserLockedBalance,
     timestamp: u64,
 ) -> Uint128 {
-    locked_balance.locked_amount_at_timestamp(timestamp)
+    locked_balance.locked_amount_at_timestamp(config, timestamp)
         * Uint128::from(locked_balance.end_lock_time - timestamp)
-        / Uint128::from(VOTING_POWER_CONSTANT_DIVISOR)
+        / Uint128::from(config.voting_power_constant_divisor)
 }
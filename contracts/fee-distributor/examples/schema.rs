@@ -3,7 +3,8 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use cw900::fee_distributor::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, StakerResponse, StateResponse,
+    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, OrphanedWeeksResponse, QueryMsg,
+    ScheduledDistributionsResponse, StakerClaimsResponse, StakerResponse, StateResponse,
 };
 
 fn main() {
@@ -16,7 +17,10 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(StakerResponse), &out_dir);
+    export_schema(&schema_for!(StakerClaimsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(ScheduledDistributionsResponse), &out_dir);
+    export_schema(&schema_for!(OrphanedWeeksResponse), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
 }
@@ -1,33 +1,44 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cw_storage_plus::U64Key;
-use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use terraswap::asset::{Asset, AssetInfo};
 
 use crate::error::ContractError;
-use crate::helpers::compute_claimable;
+use crate::helpers::{
+    compute_claimable, query_total_voting_balances_at_weeks, query_voting_balances_at_weeks,
+    read_orphaned_weeks, read_past_weekly_distributions, Claimable,
+};
 use crate::querier::{
     query_address_voting_balance_at_timestamp, query_total_voting_balance_at_timestamp,
 };
 use crate::state::{
-    Config, State, CONFIG, STATE, USER_LAST_CLAIMED_FEE_TIMESTAMP, WEEKLY_TOKEN_DISTRIBUTION,
+    asset_key, migrate_legacy_distributions, read_all_total_distributed_unclaimed_fees,
+    read_distribution_assets, read_sweepable_denoms, read_total_distributed_unclaimed_fees,
+    read_weekly_distributions, Config, State, CONFIG, DEFAULT_LIMIT, DISTRIBUTION_ASSETS,
+    MAX_LIMIT, STATE, SWEEPABLE_DENOMS, TOTAL_DISTRIBUTED_UNCLAIMED_FEES, USER_CLAIM_FOR_ALLOWED,
+    USER_LAST_CLAIMED_FEE_TIMESTAMP, WEEKLY_TOKEN_DISTRIBUTION, WEEKLY_TOTAL_VOTING_BALANCE,
 };
 
 use cosmwasm_std::{
-    attr, to_binary, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Response, StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
 use cw900::fee_distributor::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, StakerResponse, StateResponse,
+    ClaimableFeesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    OrphanedWeekResponse, OrphanedWeeksResponse, QueryMsg, ScheduledDistributionsResponse,
+    StakerClaimsResponse, StakerResponse, StateResponse, WeeklyClaimDistributionResponse,
+    WeeklyClaimResponse, WeeklyDistributionResponse,
 };
+use cw900::sweep::{default_max_spread, sweep_msg, validate_max_spread};
+use cw900::ve_token::Cw20HookMsg as VECw20HookMsg;
 
-use terraswap::querier::{query_balance, query_pair_info, query_token_balance};
-
-use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
+use terraswap::querier::query_balance;
 
 pub const SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60;
 pub const DEFAULT_CLAIM_LIMIT: u32 = 20;
+pub const MAX_DISTRIBUTION_WEEKS: u64 = 52;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -40,12 +51,16 @@ pub fn instantiate(
         glow_token: Addr::unchecked(""),
         ve_token: Addr::unchecked(""),
         terraswap_factory: Addr::unchecked(""),
+        terraswap_router: None,
+        treasury: None,
+        paused: false,
+        max_spread: default_max_spread(),
+        distribution_weeks: 1,
         owner: info.sender,
     };
 
     let state = State {
         contract_addr: env.contract.address,
-        total_distributed_unclaimed_fees: Uint128::zero(),
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -67,17 +82,127 @@ pub fn execute(
             ve_token,
             terraswap_factory,
         } => register_contracts(deps, glow_token, ve_token, terraswap_factory),
-        ExecuteMsg::Sweep { denom } => sweep(deps, env, denom),
+        ExecuteMsg::Sweep {
+            denom,
+            max_spread,
+            belief_price,
+            intermediate_asset,
+        } => sweep(
+            deps,
+            env,
+            info,
+            denom,
+            max_spread,
+            belief_price,
+            intermediate_asset,
+        ),
         ExecuteMsg::DistributeGlow {} => distribute_glow(deps, env),
+        ExecuteMsg::Distribute { asset_info } => distribute_default_weeks(deps, env, asset_info),
+        ExecuteMsg::DistributeOverWeeks { asset_info, weeks } => {
+            distribute_over_weeks(deps, env, info, asset_info, weeks)
+        }
+        ExecuteMsg::RegisterDistributionAsset { asset_info } => {
+            register_distribution_asset(deps, info, asset_info)
+        }
         ExecuteMsg::Claim { limit } => claim(deps, env, info, limit),
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::ClaimAndLock { lock_id, limit } => {
+            claim_and_lock(deps, env, info, lock_id, limit)
+        }
+        ExecuteMsg::ClaimFor { address, limit } => claim_for(deps, env, address, limit),
+        ExecuteMsg::AllowClaimFor { allowed } => allow_claim_for(deps, info, allowed),
+        ExecuteMsg::RecoverOrphanedWeek { week } => recover_orphaned_week(deps, env, week),
+        ExecuteMsg::AddSweepableDenom { denom } => add_sweepable_denom(deps, info, denom),
+        ExecuteMsg::RemoveSweepableDenom { denom } => remove_sweepable_denom(deps, info, denom),
+        ExecuteMsg::RescueToken { asset, recipient } => rescue_token(deps, info, asset, recipient),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            treasury,
+            terraswap_router,
+            paused,
+            max_spread,
+            distribution_weeks,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            treasury,
+            terraswap_router,
+            paused,
+            max_spread,
+            distribution_weeks,
+        ),
     }
 }
 
 pub fn distribute_glow(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
-    // Get the config and mutable state
     let config = CONFIG.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
+
+    distribute_default_weeks(
+        deps,
+        env,
+        AssetInfo::Token {
+            contract_addr: config.glow_token.to_string(),
+        },
+    )
+}
+
+/// Spread a distribution over the number of weeks set by the owner.
+/// Anyone can trigger it, but only the owner decides how distributions are streamed.
+pub fn distribute_default_weeks(
+    deps: DepsMut,
+    env: Env,
+    asset_info: AssetInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    distribute(deps, env, asset_info, config.distribution_weeks)
+}
+
+/// Spread a distribution over several weeks.
+/// Only the owner can choose the number of weeks, since it decides which stakers share the distribution.
+pub fn distribute_over_weeks(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    weeks: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    distribute(deps, env, asset_info, weeks)
+}
+
+/// Distribute the unreserved balance of an asset evenly over the given number of weeks,
+/// starting with the current week.
+pub fn distribute(
+    deps: DepsMut,
+    env: Env,
+    asset_info: AssetInfo,
+    weeks: u64,
+) -> Result<Response, ContractError> {
+    // Get the config and state
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    // Verify that the asset can be distributed
+    let asset_key = asset_key(&asset_info);
+    if !DISTRIBUTION_ASSETS.has(deps.storage, &asset_key) {
+        return Err(ContractError::AssetNotRegistered {});
+    }
+
+    // Verify the number of weeks to distribute over
+    if weeks == 0 || weeks > MAX_DISTRIBUTION_WEEKS {
+        return Err(ContractError::InvalidDistributionWeeks {
+            max_weeks: MAX_DISTRIBUTION_WEEKS,
+        });
+    }
 
     // Get the ve token address and the timestamp of the current time
     // floored down to the nearest week.
@@ -96,43 +221,186 @@ pub fn distribute_glow(deps: DepsMut, env: Env) -> Result<Response, ContractErro
         return Err(ContractError::NothingStaked {});
     }
 
-    // Get the amount to distribute which includes the GLOW that has just been sent to the contractx
+    // Cache the total voting balance so that claims don't need to query it
+    WEEKLY_TOTAL_VOTING_BALANCE.save(
+        deps.storage,
+        U64Key::from(week_timestamp),
+        &total_voting_balance,
+    )?;
+
+    // Get the amount to distribute which includes the asset that has just been sent to the contract
     // but subtracts the amount reserved for previous unclaimed fee distribution.
-    let amount_to_distribute = query_token_balance(
-        &deps.querier,
-        config.glow_token,
-        state.contract_addr.clone(),
-    )?
-    .checked_sub(state.total_distributed_unclaimed_fees)?;
+    let total_distributed_unclaimed_fees =
+        read_total_distributed_unclaimed_fees(deps.storage, &asset_info)?;
+    let amount_to_distribute = asset_info
+        .query_pool(&deps.querier, deps.api, state.contract_addr)?
+        .checked_sub(total_distributed_unclaimed_fees)?;
 
     // Verify that the amount to distribute is non zero.
     if amount_to_distribute == Uint128::zero() {
         return Err(ContractError::NothingToDistribute {});
     }
 
-    // Define the function for increment token distribution amount by
-    // amount_to_distribute
-    let add_to_week_token_distribution =
-        |maybe_distribution: Option<Uint128>| -> StdResult<Uint128> {
-            Ok(maybe_distribution.unwrap_or_default() + amount_to_distribute)
+    // Split amount_to_distribute evenly across the weeks,
+    // adding the remainder to the current week
+    let amount_per_week = amount_to_distribute.multiply_ratio(1u64, weeks);
+    let remainder = amount_to_distribute - amount_per_week * Uint128::from(weeks);
+
+    for week in 0..weeks {
+        let week_amount = if week == 0 {
+            amount_per_week + remainder
+        } else {
+            amount_per_week
         };
 
-    // Update WEEKLY_TOKEN_DISTRIBUTION according to the new amount_to_distribute
-    WEEKLY_TOKEN_DISTRIBUTION.update(
+        // Define the function for increment token distribution amount by
+        // week_amount
+        let add_to_week_token_distribution =
+            |maybe_distribution: Option<Uint128>| -> StdResult<Uint128> {
+                Ok(maybe_distribution.unwrap_or_default() + week_amount)
+            };
+
+        // Update WEEKLY_TOKEN_DISTRIBUTION according to the new week_amount
+        WEEKLY_TOKEN_DISTRIBUTION.update(
+            deps.storage,
+            (
+                &asset_key,
+                U64Key::from(week_timestamp + week * SECONDS_PER_WEEK),
+            ),
+            add_to_week_token_distribution,
+        )?;
+    }
+
+    // Increase total_distributed_unclaimed_fees
+    TOTAL_DISTRIBUTED_UNCLAIMED_FEES.save(
         deps.storage,
-        U64Key::from(week_timestamp),
-        add_to_week_token_distribution,
+        &asset_key,
+        &(total_distributed_unclaimed_fees + amount_to_distribute),
     )?;
 
-    // Save the state to increase total_distributed_unclaimed_fees
-    state.total_distributed_unclaimed_fees += amount_to_distribute;
-    STATE.save(deps.storage, &state)?;
-
     // Return with Response
     Ok(Response::default().add_attributes(vec![
-        attr("action", "distribute_glow"),
-        attr("glow_distributed", amount_to_distribute.to_string()),
+        attr("action", "distribute"),
+        attr(
+            "distributed",
+            Asset {
+                info: asset_info,
+                amount: amount_to_distribute,
+            }
+            .to_string(),
+        ),
         attr("week_timestamp", week_timestamp.to_string()),
+        attr("weeks", weeks.to_string()),
+    ]))
+}
+
+/// Recover the distributions of a week before the current week in which nothing was staked.
+/// Anyone can recover them. They are sent to the treasury if one is set,
+/// and otherwise added to the distributions of the current week.
+pub fn recover_orphaned_week(
+    deps: DepsMut,
+    env: Env,
+    week: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let week_timestamp = env.block.time.seconds() / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+
+    // Distributions of the current week and later weeks can still be claimed
+    if week >= week_timestamp {
+        return Err(ContractError::WeekNotOrphaned {});
+    }
+
+    // Verify that nothing was staked at the start of the week
+    let total_voting_balance =
+        query_total_voting_balances_at_weeks(deps.as_ref(), &config, &[week])?[0];
+    if !total_voting_balance.is_zero() {
+        return Err(ContractError::WeekNotOrphaned {});
+    }
+
+    // Without a treasury, the distributions are distributed again in the current week,
+    // which requires something to be staked
+    if config.treasury.is_none() {
+        let total_voting_balance =
+            query_total_voting_balances_at_weeks(deps.as_ref(), &config, &[week_timestamp])?[0];
+        if total_voting_balance.is_zero() {
+            return Err(ContractError::NothingStaked {});
+        }
+
+        WEEKLY_TOTAL_VOTING_BALANCE.save(
+            deps.storage,
+            U64Key::from(week_timestamp),
+            &total_voting_balance,
+        )?;
+    }
+
+    // Remove the distributions of every asset in the week
+    let mut recovered_assets = vec![];
+    for asset_info in read_distribution_assets(deps.storage)? {
+        let asset_key = asset_key(&asset_info);
+        if let Some(amount) =
+            WEEKLY_TOKEN_DISTRIBUTION.may_load(deps.storage, (&asset_key, U64Key::from(week)))?
+        {
+            WEEKLY_TOKEN_DISTRIBUTION.remove(deps.storage, (&asset_key, U64Key::from(week)));
+            recovered_assets.push(Asset {
+                info: asset_info,
+                amount,
+            });
+        }
+    }
+
+    if recovered_assets.is_empty() {
+        return Err(ContractError::WeekNotOrphaned {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let destination = match config.treasury {
+        // Send the distributions to the treasury, releasing the reserved amounts
+        Some(treasury) => {
+            for asset in recovered_assets.iter() {
+                let asset_key = asset_key(&asset.info);
+                TOTAL_DISTRIBUTED_UNCLAIMED_FEES.update(
+                    deps.storage,
+                    &asset_key,
+                    |total_distributed_unclaimed_fees| -> StdResult<Uint128> {
+                        Ok(total_distributed_unclaimed_fees
+                            .unwrap_or_default()
+                            .checked_sub(asset.amount)?)
+                    },
+                )?;
+
+                if !asset.amount.is_zero() {
+                    messages.push(asset.clone().into_msg(&deps.querier, treasury.clone())?);
+                }
+            }
+
+            treasury.to_string()
+        }
+        // Otherwise add them to the distributions of the current week
+        None => {
+            for asset in recovered_assets.iter() {
+                WEEKLY_TOKEN_DISTRIBUTION.update(
+                    deps.storage,
+                    (&asset_key(&asset.info), U64Key::from(week_timestamp)),
+                    |maybe_distribution| -> StdResult<Uint128> {
+                        Ok(maybe_distribution.unwrap_or_default() + asset.amount)
+                    },
+                )?;
+            }
+
+            week_timestamp.to_string()
+        }
+    };
+
+    let recovered_assets: Vec<String> = recovered_assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect();
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "recover_orphaned_week"),
+        attr("week", week.to_string()),
+        attr("recovered_assets", recovered_assets.join(",")),
+        attr("destination", destination),
     ]))
 }
 
@@ -142,52 +410,147 @@ pub fn claim(
     info: MessageInfo,
     limit: Option<u32>,
 ) -> Result<Response, ContractError> {
-    // Read the conig and mutable state
+    claim_fees(deps, env, info.sender, limit, None)
+}
+
+/// Claim the sender's fees, locking the claimed GLOW into one of the sender's ve locks
+pub fn claim_and_lock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    claim_fees(deps, env, info.sender, limit, Some(lock_id))
+}
+
+/// Claim fees on behalf of a user who has allowed it. The fees are sent to the user.
+pub fn claim_for(
+    deps: DepsMut,
+    env: Env,
+    address: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let user = deps.api.addr_validate(&address)?;
+
+    if !USER_CLAIM_FOR_ALLOWED
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default()
+    {
+        return Err(ContractError::ClaimForNotAllowed {});
+    }
+
+    claim_fees(deps, env, user, limit, None)
+}
+
+/// Allow or disallow anyone to claim fees on behalf of the sender
+pub fn allow_claim_for(
+    deps: DepsMut,
+    info: MessageInfo,
+    allowed: bool,
+) -> Result<Response, ContractError> {
+    USER_CLAIM_FOR_ALLOWED.save(deps.storage, &info.sender, &allowed)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "allow_claim_for"),
+        attr("user", info.sender.to_string()),
+        attr("allowed", allowed.to_string()),
+    ]))
+}
+
+/// Claim the user's share of every distribution asset and send it to the user.
+/// If a lock_id is given, the claimed GLOW is added to that lock of the user instead.
+fn claim_fees(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    limit: Option<u32>,
+    lock_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    // Read the config
     let config = CONFIG.load(deps.storage)?;
-    let mut state = STATE.load(deps.storage)?;
 
-    // Compute the claimable amount
-    let (initial_last_claimed_fee_timestamp, last_claimed_fee_timestamp, claim_amount) =
-        compute_claimable(deps.as_ref(), env, &config, &info.sender, limit, None)?;
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut claimed_assets: Vec<String> = vec![];
 
-    // Save the last_claimed_fee_timestamp to the user.
-    USER_LAST_CLAIMED_FEE_TIMESTAMP.save(
-        deps.storage,
-        info.sender.clone(),
-        &last_claimed_fee_timestamp,
+    // Compute the claimable amount of each asset
+    let claimables = compute_claimable(
+        deps.as_ref(),
+        env,
+        &config,
+        &user,
+        read_distribution_assets(deps.storage)?,
+        limit,
+        None,
     )?;
 
-    // Decrease total_distributed_unclaimed fee by the claimed amount.
-    state.total_distributed_unclaimed_fees -= claim_amount;
-    STATE.save(deps.storage, &state)?;
+    for Claimable {
+        asset_info,
+        last_claimed_fee_timestamp,
+        claim_amount,
+        ..
+    } in claimables
+    {
+        let asset_key = asset_key(&asset_info);
 
-    let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
-        vec![CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.glow_token.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: claim_amount,
-            })?,
-        })]
-    } else {
-        vec![]
-    };
+        // Save the last_claimed_fee_timestamp of the asset to the user.
+        USER_LAST_CLAIMED_FEE_TIMESTAMP.save(
+            deps.storage,
+            (&user, &asset_key),
+            &last_claimed_fee_timestamp,
+        )?;
+
+        if claim_amount.is_zero() {
+            continue;
+        }
+
+        // Decrease total_distributed_unclaimed fee by the claimed amount.
+        TOTAL_DISTRIBUTED_UNCLAIMED_FEES.update(
+            deps.storage,
+            &asset_key,
+            |total| -> StdResult<_> { Ok(total.unwrap_or_default().checked_sub(claim_amount)?) },
+        )?;
+
+        let asset = Asset {
+            info: asset_info,
+            amount: claim_amount,
+        };
+        claimed_assets.push(asset.to_string());
 
-    // Return with a message to send "claim_amount" GLOW to the calling user.
+        let is_glow = asset.info
+            == AssetInfo::Token {
+                contract_addr: config.glow_token.to_string(),
+            };
+
+        match lock_id {
+            // Send the GLOW to the ve token to increase the user's lock
+            Some(lock_id) if is_glow => messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: config.glow_token.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: config.ve_token.to_string(),
+                    amount: claim_amount,
+                    msg: to_binary(&VECw20HookMsg::IncreaseLockAmount {
+                        lock_id,
+                        address: Some(user.to_string()),
+                    })?,
+                })?,
+            })),
+            _ => messages.push(asset.into_msg(&deps.querier, user.clone())?),
+        }
+    }
+
+    // Return with a message to send each claimed asset to the user.
     Ok(Response::default()
         .add_messages(messages)
         .add_attributes(vec![
             attr("action", "claim"),
-            attr("claimed_amount", claim_amount.to_string()),
-            attr(
-                "initial_last_claimed_fee_timestamp",
-                initial_last_claimed_fee_timestamp.to_string(),
-            ),
-            attr(
-                "last_claimed_fee_timestamp",
-                last_claimed_fee_timestamp.to_string(),
-            ),
+            attr("user", user.to_string()),
+            attr("claimed_assets", claimed_assets.join(",")),
         ]))
 }
 
@@ -209,38 +572,81 @@ pub fn register_contracts(
 
     CONFIG.save(deps.storage, &config)?;
 
+    // GLOW can always be distributed
+    let glow_asset_info = AssetInfo::Token {
+        contract_addr: config.glow_token.to_string(),
+    };
+    DISTRIBUTION_ASSETS.save(deps.storage, &asset_key(&glow_asset_info), &glow_asset_info)?;
+
     Ok(Response::default())
 }
 
+/// Register an asset which can be distributed. Only the owner can register assets.
+pub fn register_distribution_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let AssetInfo::Token { contract_addr } = &asset_info {
+        deps.api.addr_validate(contract_addr)?;
+    }
+
+    let asset_key = asset_key(&asset_info);
+    if DISTRIBUTION_ASSETS.has(deps.storage, &asset_key) {
+        return Err(ContractError::AssetAlreadyRegistered {});
+    }
+
+    DISTRIBUTION_ASSETS.save(deps.storage, &asset_key, &asset_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_distribution_asset"),
+        attr("asset", asset_info.to_string()),
+    ]))
+}
+
 /// Sweep
 /// Anyone can execute sweep function to swap
 /// asset native denom => GLOW token
-pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, ContractError> {
-    // Read the config, glow_token, and terraswap_factory_addr
+/// Only the owner can sweep without a belief_price
+pub fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    max_spread: Option<Decimal>,
+    belief_price: Option<Decimal>,
+    intermediate_asset: Option<AssetInfo>,
+) -> Result<Response, ContractError> {
+    // Read the config
     let config = CONFIG.load(deps.storage)?;
-    let glow_token = config.glow_token;
-    let terraswap_factory_addr = config.terraswap_factory;
 
-    // Get the pair info
-    let pair_info: PairInfo = query_pair_info(
-        &deps.querier,
-        terraswap_factory_addr,
-        &[
-            AssetInfo::NativeToken {
-                denom: denom.to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: glow_token.to_string(),
-            },
-        ],
-    )?;
+    // Only the denoms allowed by the owner can be swept
+    if !SWEEPABLE_DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::DenomNotSweepable {});
+    }
+
+    // Without a belief_price the spread is measured against the pool,
+    // which anyone can move in the same block before sweeping
+    if belief_price.is_none() && info.sender != config.owner {
+        return Err(ContractError::BeliefPriceRequired {});
+    }
 
-    // Sweep the entire balance worth of the denom to glow
-    let amount = query_balance(&deps.querier, env.contract.address, denom.to_string())?;
+    // Sweep the entire balance worth of the denom to glow,
+    // except the amount reserved for distributions of the denom
+    let swap_asset_info = AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    };
+    let amount =
+        query_balance(&deps.querier, env.contract.address, denom.to_string())?.checked_sub(
+            read_total_distributed_unclaimed_fees(deps.storage, &swap_asset_info)?,
+        )?;
     let swap_asset = Asset {
-        info: AssetInfo::NativeToken {
-            denom: denom.to_string(),
-        },
+        info: swap_asset_info,
         amount,
     };
 
@@ -249,22 +655,20 @@ pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, Contrac
 
     // Response which sweeps all the contracts UST for GLOW
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: pair_info.contract_addr,
-            msg: to_binary(&TerraswapExecuteMsg::Swap {
-                offer_asset: Asset {
-                    amount,
-                    ..swap_asset
-                },
-                max_spread: None,
-                belief_price: None,
-                to: None,
-            })?,
-            funds: vec![Coin {
+        .add_message(sweep_msg(
+            &deps.querier,
+            &config.terraswap_factory,
+            config.terraswap_router.as_ref(),
+            &config.glow_token,
+            Coin {
                 denom: denom.to_string(),
                 amount,
-            }],
-        }))
+            },
+            max_spread,
+            config.max_spread,
+            belief_price,
+            intermediate_asset,
+        )?)
         .add_attributes(vec![
             attr("action", "sweep"),
             attr(
@@ -274,10 +678,96 @@ pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, Contrac
         ]))
 }
 
+/// Allow a denom to be swept. Only the owner can allow denoms.
+pub fn add_sweepable_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    SWEEPABLE_DENOMS.save(deps.storage, &denom, &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_sweepable_denom"),
+        attr("denom", denom),
+    ]))
+}
+
+/// Stop a previously allowed denom from being swept. Only the owner can remove denoms.
+pub fn remove_sweepable_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !SWEEPABLE_DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::DenomNotSweepable {});
+    }
+
+    SWEEPABLE_DENOMS.remove(deps.storage, &denom);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_sweepable_denom"),
+        attr("denom", denom),
+    ]))
+}
+
+/// Withdraw an asset held by the contract, such as a CW20 sent by mistake.
+/// The amounts reserved for distributions which haven't been claimed can't be withdrawn.
+/// Only the owner can rescue assets.
+pub fn rescue_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: Asset,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    // Verify that the amount isn't reserved for distributions
+    let rescuable_amount = asset
+        .info
+        .query_pool(&deps.querier, deps.api, state.contract_addr)?
+        .checked_sub(read_total_distributed_unclaimed_fees(
+            deps.storage,
+            &asset.info,
+        )?)?;
+    if asset.amount > rescuable_amount {
+        return Err(ContractError::InsufficientRescuableBalance {});
+    }
+
+    Ok(Response::new()
+        .add_message(asset.clone().into_msg(&deps.querier, recipient.clone())?)
+        .add_attributes(vec![
+            attr("action", "rescue_token"),
+            attr("rescued", asset.to_string()),
+            attr("recipient", recipient.to_string()),
+        ]))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
     owner: Option<String>,
+    treasury: Option<String>,
+    terraswap_router: Option<String>,
+    paused: Option<bool>,
+    max_spread: Option<Decimal>,
+    distribution_weeks: Option<u64>,
 ) -> Result<Response, ContractError> {
     let api = deps.api;
     CONFIG.update(deps.storage, |mut config| {
@@ -289,6 +779,32 @@ pub fn update_config(
             config.owner = api.addr_validate(&owner)?;
         }
 
+        if let Some(treasury) = treasury {
+            config.treasury = Some(api.addr_validate(&treasury)?);
+        }
+
+        if let Some(terraswap_router) = terraswap_router {
+            config.terraswap_router = Some(api.addr_validate(&terraswap_router)?);
+        }
+
+        if let Some(paused) = paused {
+            config.paused = paused;
+        }
+
+        if let Some(max_spread) = max_spread {
+            validate_max_spread(max_spread)?;
+            config.max_spread = max_spread;
+        }
+
+        if let Some(distribution_weeks) = distribution_weeks {
+            if distribution_weeks == 0 || distribution_weeks > MAX_DISTRIBUTION_WEEKS {
+                return Err(ContractError::InvalidDistributionWeeks {
+                    max_weeks: MAX_DISTRIBUTION_WEEKS,
+                });
+            }
+            config.distribution_weeks = distribution_weeks;
+        }
+
         Ok(config)
     })?;
 
@@ -311,6 +827,34 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
             fee_limit,
             fee_start_after,
         )?)?),
+        QueryMsg::StakerClaims {
+            address,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_staker_claims(
+            deps,
+            env,
+            address,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::ScheduledDistributions {
+            asset_info,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_scheduled_distributions(
+            deps,
+            env,
+            asset_info,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::OrphanedWeeks { start_after, limit } => Ok(to_binary(&query_orphaned_weeks(
+            deps,
+            env,
+            start_after,
+            limit,
+        )?)?),
     }
 }
 
@@ -321,6 +865,15 @@ fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
         glow_token: config.glow_token.to_string(),
         ve_token: config.ve_token.to_string(),
         terraswap_factory: config.terraswap_factory.to_string(),
+        terraswap_router: config
+            .terraswap_router
+            .map(|terraswap_router| terraswap_router.to_string()),
+        treasury: config.treasury.map(|treasury| treasury.to_string()),
+        paused: config.paused,
+        max_spread: config.max_spread,
+        distribution_weeks: config.distribution_weeks,
+        distribution_assets: read_distribution_assets(deps.storage)?,
+        sweepable_denoms: read_sweepable_denoms(deps.storage)?,
     })
 }
 
@@ -328,7 +881,57 @@ fn query_state(deps: Deps) -> Result<StateResponse, ContractError> {
     let state = STATE.load(deps.storage)?;
     Ok(StateResponse {
         contract_addr: state.contract_addr.to_string(),
-        total_distributed_unclaimed_fees: state.total_distributed_unclaimed_fees,
+        total_distributed_unclaimed_fees: read_all_total_distributed_unclaimed_fees(deps.storage)?,
+    })
+}
+
+fn query_scheduled_distributions(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ScheduledDistributionsResponse, ContractError> {
+    // Distributions from the current week onwards can't be claimed yet
+    let week_timestamp = env.block.time.seconds() / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let start_time = match start_after {
+        Some(start_after) => week_timestamp.max(start_after + 1),
+        None => week_timestamp,
+    };
+
+    let distributions = read_weekly_distributions(deps.storage, &asset_info, start_time, limit)?
+        .into_iter()
+        .map(|(week_timestamp, amount)| WeeklyDistributionResponse {
+            week_timestamp,
+            amount,
+        })
+        .collect();
+
+    Ok(ScheduledDistributionsResponse { distributions })
+}
+
+fn query_orphaned_weeks(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<OrphanedWeeksResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let (orphaned_weeks, next_start_after) =
+        read_orphaned_weeks(deps, env, &config, start_after, limit)?;
+
+    let weeks = orphaned_weeks
+        .into_iter()
+        .map(|(week_timestamp, distributions)| OrphanedWeekResponse {
+            week_timestamp,
+            distributions,
+        })
+        .collect();
+
+    Ok(OrphanedWeeksResponse {
+        weeks,
+        next_start_after,
     })
 }
 
@@ -344,9 +947,24 @@ fn query_staker(
     let config = CONFIG.load(deps.storage)?;
 
     // Get the last_claimed_timestamp, and calculate a lower bound on the user's
-    // claimable fees.
-    let (initial_last_claimed_fee_timestamp, last_claimed_fee_timestamp, claim_amount) =
-        compute_claimable(deps, env, &config, &address, fee_limit, fee_start_after)?;
+    // claimable fees for each asset.
+    let claimable_fees = compute_claimable(
+        deps,
+        env,
+        &config,
+        &address,
+        read_distribution_assets(deps.storage)?,
+        fee_limit,
+        fee_start_after,
+    )?
+    .into_iter()
+    .map(|claimable| ClaimableFeesResponse {
+        asset_info: claimable.asset_info,
+        initial_last_claimed_fee_timestamp: claimable.initial_last_claimed_fee_timestamp,
+        last_claimed_fee_timestamp: claimable.last_claimed_fee_timestamp,
+        claimable_fees_lower_bound: claimable.claim_amount,
+    })
+    .collect();
 
     // Get the user's voting balance just to add it as more data to the response.
     let balance =
@@ -354,13 +972,83 @@ fn query_staker(
 
     Ok(StakerResponse {
         balance,
-        initial_last_claimed_fee_timestamp,
-        last_claimed_fee_timestamp,
-        claimable_fees_lower_bound: claim_amount,
+        claim_for_allowed: USER_CLAIM_FOR_ALLOWED
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default(),
+        claimable_fees,
     })
 }
 
+fn query_staker_claims(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<StakerClaimsResponse, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Get the distributions of the weeks and the voting balances at the start of each of them
+    let weekly_distributions = read_past_weekly_distributions(deps, env, start_after, Some(limit))?;
+    let voting_balances = query_voting_balances_at_weeks(
+        deps,
+        &config,
+        &address,
+        weekly_distributions.keys().copied().collect(),
+    )?;
+
+    let mut claims = vec![];
+    for (week_timestamp, assets) in weekly_distributions {
+        let (user_voting_balance, total_voting_balance) = voting_balances[&week_timestamp];
+
+        let distributions = assets
+            .into_iter()
+            .map(|asset| {
+                let last_claimed_fee_timestamp = USER_LAST_CLAIMED_FEE_TIMESTAMP
+                    .may_load(deps.storage, (&address, &asset_key(&asset.info)))?
+                    .unwrap_or_default();
+
+                // Nothing can be claimed from weeks in which nothing was staked
+                let claim_amount = if total_voting_balance.is_zero() {
+                    Uint128::zero()
+                } else {
+                    asset
+                        .amount
+                        .multiply_ratio(user_voting_balance, total_voting_balance)
+                };
+
+                Ok(WeeklyClaimDistributionResponse {
+                    asset_info: asset.info,
+                    distributed_amount: asset.amount,
+                    claim_amount,
+                    claimed: week_timestamp <= last_claimed_fee_timestamp,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        claims.push(WeeklyClaimResponse {
+            week_timestamp,
+            user_voting_balance,
+            total_voting_balance,
+            share: if total_voting_balance.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(user_voting_balance, total_voting_balance)
+            },
+            distributions,
+        });
+    }
+
+    Ok(StakerClaimsResponse { claims })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+
+    migrate_legacy_distributions(deps.storage, &config.glow_token)?;
+
     Ok(Response::default())
 }
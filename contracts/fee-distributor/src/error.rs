@@ -17,4 +17,31 @@ pub enum ContractError {
 
     #[error("Nothing to distribute")]
     NothingToDistribute {},
+
+    #[error("Distributions and claims are paused")]
+    Paused {},
+
+    #[error("Denom can't be swept")]
+    DenomNotSweepable {},
+
+    #[error("Amount exceeds the balance which isn't reserved for distributions")]
+    InsufficientRescuableBalance {},
+
+    #[error("Asset is not registered for distribution")]
+    AssetNotRegistered {},
+
+    #[error("Asset is already registered for distribution")]
+    AssetAlreadyRegistered {},
+
+    #[error("Distributions must span between 1 and {max_weeks} weeks")]
+    InvalidDistributionWeeks { max_weeks: u64 },
+
+    #[error("Week has no distributions which can't be claimed")]
+    WeekNotOrphaned {},
+
+    #[error("User has not allowed claiming on their behalf")]
+    ClaimForNotAllowed {},
+
+    #[error("Only the owner can sweep without a belief_price")]
+    BeliefPriceRequired {},
 }
@@ -1,40 +1,35 @@
 use crate::contract::{DEFAULT_CLAIM_LIMIT, SECONDS_PER_WEEK};
 use crate::querier::{
-    query_address_voting_balance_at_timestamp, query_total_voting_balance_at_timestamp,
+    query_address_voting_balances_at_timestamps, query_total_voting_balances_at_timestamps,
 };
-use crate::state::{Config, USER_LAST_CLAIMED_FEE_TIMESTAMP, WEEKLY_TOKEN_DISTRIBUTION};
+use crate::state::{
+    asset_key, read_distribution_assets, Config, DEFAULT_LIMIT, MAX_LIMIT, MAX_SCANNED_WEEKS,
+    USER_LAST_CLAIMED_FEE_TIMESTAMP, WEEKLY_TOKEN_DISTRIBUTION, WEEKLY_TOTAL_VOTING_BALANCE,
+};
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
+use terraswap::asset::{Asset, AssetInfo};
 
 use cosmwasm_std::{Addr, Deps, Env, Order, StdResult, Uint128};
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, U64Key};
+
+/// The amount of an asset a user can claim, along with the range of claimed distributions
+pub struct Claimable {
+    pub asset_info: AssetInfo,
+    pub initial_last_claimed_fee_timestamp: u64,
+    pub last_claimed_fee_timestamp: u64,
+    pub claim_amount: Uint128,
+}
 
 pub fn compute_claimable(
     deps: Deps,
     env: Env,
     config: &Config,
     user: &Addr,
+    asset_infos: Vec<AssetInfo>,
     limit: Option<u32>,
     start_after: Option<u64>,
-) -> StdResult<(u64, u64, Uint128)> {
-    // Set the initlal last claimed fee timestamp
-    // if the user has never claimed a fee before, it will be unwrapped to default
-    // which is 0.
-    let initial_last_claimed_fee_timestamp = start_after.unwrap_or(
-        USER_LAST_CLAIMED_FEE_TIMESTAMP
-            .may_load(deps.storage, user.clone())?
-            .unwrap_or_default(),
-    );
-
-    // Copy the initial_last_claimed_fee_timestamp.
-    // We don't want to mutate the initial_last_claimed_fee_timestamp
-    // so that we can send it back unchanged in the response..
-    let mut last_claimed_fee_timestamp = initial_last_claimed_fee_timestamp;
-
-    // Increaes the start_time by SECONDS_PER_WEEK to get to the next week.
-    // If the user has never collected a fee, this will be set to
-    // SECONDS_PER_WEEK
-    let start_time = last_claimed_fee_timestamp + SECONDS_PER_WEEK;
-
+) -> StdResult<Vec<Claimable>> {
     // Set the end time to the current week rounded down
     // minus SECONDS_PER_WEEK. This means it gets set to the rounded down version of one week ago.
 
@@ -47,66 +42,264 @@ pub fn compute_claimable(
     // Set limit, or DEFAULT_CLAIM_LIMIT if undefined.
     let limit = limit.unwrap_or(DEFAULT_CLAIM_LIMIT) as usize;
 
-    // Do a range query over WEEKLY_TOKEN_DISTRIBUTION
-    // starting with start_time inclusive (the week after the previous collection fee time)
-    // and ending with end time inclusive (the cutoff of the week before this one).
-    // Take a limit of the range query, map the key to the timestamp, and collect.
-    let token_distributions = WEEKLY_TOKEN_DISTRIBUTION
-        .range(
-            deps.storage,
-            Some(Bound::Inclusive(start_time.to_be_bytes().into())),
-            Some(Bound::Inclusive(end_time.to_be_bytes().into())),
-            Order::Ascending,
-        )
-        .take(limit)
-        .map(|item| {
-            let (k, v) = item?;
+    // Read the distributions of each asset which the user hasn't claimed yet
+    let mut asset_distributions = vec![];
+    for asset_info in asset_infos {
+        let asset_key = asset_key(&asset_info);
 
-            let timestamp = u64::from_be_bytes(k.try_into().unwrap());
+        // Set the initlal last claimed fee timestamp
+        // if the user has never claimed a fee of this asset before, it will be unwrapped to default
+        // which is 0.
+        let initial_last_claimed_fee_timestamp = start_after.unwrap_or(
+            USER_LAST_CLAIMED_FEE_TIMESTAMP
+                .may_load(deps.storage, (user, &asset_key))?
+                .unwrap_or_default(),
+        );
+
+        // Increaes the start_time by SECONDS_PER_WEEK to get to the next week.
+        // If the user has never collected a fee, this will be set to
+        // SECONDS_PER_WEEK
+        let start_time = initial_last_claimed_fee_timestamp + SECONDS_PER_WEEK;
+
+        // Do a range query over the asset's WEEKLY_TOKEN_DISTRIBUTION
+        // starting with start_time inclusive (the week after the previous collection fee time)
+        // and ending with end time inclusive (the cutoff of the week before this one).
+        // Take a limit of the range query, map the key to the timestamp, and collect.
+        let token_distributions = WEEKLY_TOKEN_DISTRIBUTION
+            .prefix(&asset_key)
+            .range(
+                deps.storage,
+                Some(Bound::Inclusive(start_time.to_be_bytes().into())),
+                Some(Bound::Inclusive(end_time.to_be_bytes().into())),
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| {
+                let (k, v) = item?;
+
+                let timestamp = u64::from_be_bytes(k.try_into().unwrap());
 
-            Ok((timestamp, v))
+                Ok((timestamp, v))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        asset_distributions.push((
+            asset_info,
+            initial_last_claimed_fee_timestamp,
+            token_distributions,
+        ));
+    }
+
+    // Get the user's and the total voting balance of every week with a distribution
+    let weeks = asset_distributions
+        .iter()
+        .flat_map(|(_, _, token_distributions)| {
+            token_distributions.iter().map(|(timestamp, _)| *timestamp)
         })
-        .collect::<StdResult<Vec<_>>>()?;
+        .collect::<BTreeSet<u64>>();
+    let voting_balances = query_voting_balances_at_weeks(deps, config, user, weeks)?;
 
-    // Initialize claim_amount as set to 0
-    let mut claim_amount = Uint128::zero();
+    let mut claimables = vec![];
+    for (asset_info, initial_last_claimed_fee_timestamp, token_distributions) in asset_distributions
+    {
+        // Copy the initial_last_claimed_fee_timestamp.
+        // We don't want to mutate the initial_last_claimed_fee_timestamp
+        // so that we can send it back unchanged in the response..
+        let mut last_claimed_fee_timestamp = initial_last_claimed_fee_timestamp;
 
-    for (timestamp, distributed_amount) in token_distributions {
-        // For each pair of timestamp and distributed_amount in token_distributions,
-        // - update last_claimed_fee_timestamp.
-        // - get the total voting balance at the corresponding time.
-        // - get the uer's voting balance at the corresponding time.
-        // - increase claim_amount by distributed_amount * (user_voting_balance / total_voting_balance)
+        // Initialize claim_amount as set to 0
+        let mut claim_amount = Uint128::zero();
 
-        // Update last_claimed_fee_timestamp
-        last_claimed_fee_timestamp = timestamp;
+        for (timestamp, distributed_amount) in token_distributions {
+            // For each pair of timestamp and distributed_amount in token_distributions,
+            // - update last_claimed_fee_timestamp.
+            // - increase claim_amount by distributed_amount * (user_voting_balance / total_voting_balance)
 
-        // Get the total voting balance at this point in time
-        let total_voting_balance = query_total_voting_balance_at_timestamp(
-            &deps.querier,
-            &config.ve_token,
-            Some(timestamp),
-        )?;
+            // Update last_claimed_fee_timestamp
+            last_claimed_fee_timestamp = timestamp;
+
+            // Get the user's and the total voting balance at this point in time
+            let (user_voting_balance, total_voting_balance) = voting_balances[&timestamp];
+
+            // Increment claim_ammount accordingly.
+            if !total_voting_balance.is_zero() {
+                claim_amount +=
+                    distributed_amount.multiply_ratio(user_voting_balance, total_voting_balance);
+            }
+        }
+
+        claimables.push(Claimable {
+            asset_info,
+            initial_last_claimed_fee_timestamp,
+            last_claimed_fee_timestamp,
+            claim_amount,
+        });
+    }
+
+    Ok(claimables)
+}
+
+/// Get the user's and the total voting balance at the start of each of the weeks.
+/// The total voting balance is read from WEEKLY_TOTAL_VOTING_BALANCE when it was cached,
+/// so at most one query is made for the user's balances and one for the remaining totals.
+pub fn query_voting_balances_at_weeks(
+    deps: Deps,
+    config: &Config,
+    user: &Addr,
+    weeks: BTreeSet<u64>,
+) -> StdResult<BTreeMap<u64, (Uint128, Uint128)>> {
+    if weeks.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let weeks: Vec<u64> = weeks.into_iter().collect();
+
+    // Get the user's voting balances with a single query
+    let user_voting_balances = query_address_voting_balances_at_timestamps(
+        &deps.querier,
+        &config.ve_token,
+        user,
+        weeks.clone(),
+    )?;
+
+    let total_voting_balances = query_total_voting_balances_at_weeks(deps, config, &weeks)?;
+
+    Ok(weeks
+        .into_iter()
+        .zip(user_voting_balances)
+        .zip(total_voting_balances)
+        .map(|((week, user_voting_balance), total_voting_balance)| {
+            (week, (user_voting_balance, total_voting_balance))
+        })
+        .collect())
+}
+
+/// Get the total voting balance at the start of each of the weeks.
+/// Cached total voting balances are read from WEEKLY_TOTAL_VOTING_BALANCE,
+/// and the ones which weren't cached are read with a single query.
+pub fn query_total_voting_balances_at_weeks(
+    deps: Deps,
+    config: &Config,
+    weeks: &[u64],
+) -> StdResult<Vec<Uint128>> {
+    let mut total_voting_balances = weeks
+        .iter()
+        .map(|week| WEEKLY_TOTAL_VOTING_BALANCE.may_load(deps.storage, U64Key::from(*week)))
+        .collect::<StdResult<Vec<Option<Uint128>>>>()?;
 
-        // Get the user's voting balance at this point in time
-        let user_voting_balance = query_address_voting_balance_at_timestamp(
+    let uncached_weeks: Vec<u64> = weeks
+        .iter()
+        .zip(total_voting_balances.iter())
+        .filter(|(_, total_voting_balance)| total_voting_balance.is_none())
+        .map(|(week, _)| *week)
+        .collect();
+
+    if !uncached_weeks.is_empty() {
+        let mut queried_total_voting_balances = query_total_voting_balances_at_timestamps(
             &deps.querier,
             &config.ve_token,
-            Some(timestamp),
-            user,
-        )?;
+            uncached_weeks,
+        )?
+        .into_iter();
+
+        for total_voting_balance in total_voting_balances.iter_mut() {
+            if total_voting_balance.is_none() {
+                *total_voting_balance = queried_total_voting_balances.next();
+            }
+        }
+    }
+
+    Ok(total_voting_balances
+        .into_iter()
+        .map(|total_voting_balance| total_voting_balance.unwrap_or_default())
+        .collect())
+}
+
+/// Get the distributions of every asset in the weeks before the current week, by week.
+/// When a limit is given, only the first limit weeks are returned.
+pub fn read_past_weekly_distributions(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<usize>,
+) -> StdResult<BTreeMap<u64, Vec<Asset>>> {
+    let week_timestamp = env.block.time.seconds() / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let start = start_after.map(|start_after| Bound::Exclusive(start_after.to_be_bytes().into()));
+
+    let mut weekly_distributions: BTreeMap<u64, Vec<Asset>> = BTreeMap::new();
+    for asset_info in read_distribution_assets(deps.storage)? {
+        // The first limit weeks overall are among the first limit weeks of each asset
+        let token_distributions = WEEKLY_TOKEN_DISTRIBUTION
+            .prefix(&asset_key(&asset_info))
+            .range(
+                deps.storage,
+                start.clone(),
+                Some(Bound::Exclusive(week_timestamp.to_be_bytes().into())),
+                Order::Ascending,
+            )
+            .take(limit.unwrap_or(usize::MAX));
+
+        for item in token_distributions {
+            let (k, amount) = item?;
+            let timestamp = u64::from_be_bytes(k.try_into().unwrap());
+
+            weekly_distributions
+                .entry(timestamp)
+                .or_default()
+                .push(Asset {
+                    info: asset_info.clone(),
+                    amount,
+                });
+        }
+    }
+
+    if let Some(limit) = limit {
+        weekly_distributions = weekly_distributions.into_iter().take(limit).collect();
+    }
+
+    Ok(weekly_distributions)
+}
+
+/// Get the weeks before the current week which have a distribution of any asset
+/// but no total voting balance, along with the distributions of each of them.
+/// These distributions can never be claimed.
+/// At most MAX_SCANNED_WEEKS weeks with distributions are read, so the last scanned week
+/// is returned to continue from when more weeks may remain.
+#[allow(clippy::type_complexity)]
+pub fn read_orphaned_weeks(
+    deps: Deps,
+    env: Env,
+    config: &Config,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<(Vec<(u64, Vec<Asset>)>, Option<u64>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let weekly_distributions =
+        read_past_weekly_distributions(deps, env, start_after, Some(MAX_SCANNED_WEEKS as usize))?;
+
+    let weeks: Vec<u64> = weekly_distributions.keys().copied().collect();
+    let total_voting_balances = query_total_voting_balances_at_weeks(deps, config, &weeks)?;
+
+    let mut orphaned_weeks = vec![];
+    let mut last_scanned_week = None;
+    for ((week, distributions), total_voting_balance) in
+        weekly_distributions.into_iter().zip(total_voting_balances)
+    {
+        if orphaned_weeks.len() == limit {
+            return Ok((orphaned_weeks, last_scanned_week));
+        }
+
+        last_scanned_week = Some(week);
+
+        if total_voting_balance.is_zero() {
+            orphaned_weeks.push((week, distributions));
+        }
+    }
 
-        // Increment claim_ammount accordingly.
-        claim_amount +=
-            distributed_amount.multiply_ratio(user_voting_balance, total_voting_balance);
+    // Weeks after the last scanned week may remain if the scan was cut short
+    if weeks.len() < MAX_SCANNED_WEEKS as usize {
+        last_scanned_week = None;
     }
 
-    // Return the initial_last_claimed_fee_timestamp,
-    // the last_claimed_fee_timestamp
-    // and the claimed_amount
-    Ok((
-        initial_last_claimed_fee_timestamp,
-        last_claimed_fee_timestamp,
-        claim_amount,
-    ))
+    Ok((orphaned_weeks, last_scanned_week))
 }
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use cw900::ve_token::{StakerResponse, StateResponse};
+use cw900::ve_token::{BalancesResponse, StakerResponse, StateResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -141,6 +141,15 @@ pub enum QueryMsg {
         address: String,
         timestamp: Option<u64>,
     },
+
+    StakerBalances {
+        address: String,
+        timestamps: Vec<u64>,
+    },
+
+    TotalBalances {
+        timestamps: Vec<u64>,
+    },
 }
 
 impl WasmMockQuerier {
@@ -219,6 +228,9 @@ impl WasmMockQuerier {
                             deposited_amount: balance,
                             balance,
                             locked_amount: balance,
+                            delegated_balance: Uint128::zero(),
+                            delegate: None,
+                            voting_power: balance,
                         })
                         .unwrap(),
                     ))
@@ -248,6 +260,43 @@ impl WasmMockQuerier {
                     ))
                 }
 
+                Ok(QueryMsg::StakerBalances {
+                    address,
+                    timestamps,
+                }) => {
+                    let balance = self
+                        .token_querier
+                        .balances
+                        .get(contract_addr)
+                        .and_then(|balances| balances.get(&address))
+                        .copied()
+                        .unwrap_or_default();
+
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&BalancesResponse {
+                            balances: vec![balance; timestamps.len()],
+                        })
+                        .unwrap(),
+                    ))
+                }
+
+                Ok(QueryMsg::TotalBalances { timestamps }) => {
+                    // Sum over the entire balance
+                    let balance = self
+                        .token_querier
+                        .balances
+                        .get(contract_addr)
+                        .map(|balances| balances.iter().fold(Uint128::zero(), |sum, x| sum + x.1))
+                        .unwrap_or_default();
+
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&BalancesResponse {
+                            balances: vec![balance; timestamps.len()],
+                        })
+                        .unwrap(),
+                    ))
+                }
+
                 _ => match from_binary(msg).unwrap() {
                     Cw20QueryMsg::Balance { address } => {
                         let balances: &HashMap<String, Uint128> =
@@ -1,7 +1,9 @@
 use cosmwasm_std::Uint128;
 use cosmwasm_std::{to_binary, Addr, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
 
-use cw900::ve_token::{QueryMsg as VEQueryMessage, StakerResponse, StateResponse};
+use cw900::ve_token::{
+    BalancesResponse, QueryMsg as VEQueryMessage, StakerResponse, StateResponse,
+};
 
 pub fn query_address_voting_balance_at_timestamp(
     querier: &QuerierWrapper,
@@ -35,3 +37,33 @@ pub fn query_total_voting_balance_at_timestamp(
 
     Ok(res)
 }
+
+pub fn query_address_voting_balances_at_timestamps(
+    querier: &QuerierWrapper,
+    ve_addr: &Addr,
+    address: &Addr,
+    timestamps: Vec<u64>,
+) -> StdResult<Vec<Uint128>> {
+    let res: BalancesResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: ve_addr.to_string(),
+        msg: to_binary(&VEQueryMessage::StakerBalances {
+            address: address.to_string(),
+            timestamps,
+        })?,
+    }))?;
+
+    Ok(res.balances)
+}
+
+pub fn query_total_voting_balances_at_timestamps(
+    querier: &QuerierWrapper,
+    ve_addr: &Addr,
+    timestamps: Vec<u64>,
+) -> StdResult<Vec<Uint128>> {
+    let res: BalancesResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: ve_addr.to_string(),
+        msg: to_binary(&VEQueryMessage::TotalBalances { timestamps })?,
+    }))?;
+
+    Ok(res.balances)
+}
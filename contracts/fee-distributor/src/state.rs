@@ -1,14 +1,38 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map, U64Key};
+use cosmwasm_std::{Addr, Decimal, Order, StdError, StdResult, Storage, Uint128};
+use cw900::sweep::default_max_spread;
+use cw_storage_plus::{Bound, Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use terraswap::asset::{Asset, AssetInfo};
 
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const STATE: Item<State> = Item::new("state");
 
-pub const WEEKLY_TOKEN_DISTRIBUTION: Map<U64Key, Uint128> = Map::new("distributed_tokens");
+/// Assets which can be distributed, keyed by asset_key
+pub const DISTRIBUTION_ASSETS: Map<&[u8], AssetInfo> = Map::new("distribution_assets");
 
-pub const USER_LAST_CLAIMED_FEE_TIMESTAMP: Map<Addr, u64> = Map::new("user_last_claimed_fee");
+/// Amount of each asset distributed in each week, keyed by asset_key and week timestamp
+pub const WEEKLY_TOKEN_DISTRIBUTION: Map<(&[u8], U64Key), Uint128> =
+    Map::new("weekly_token_distribution");
+
+/// Total voting balance at the start of each week, cached when distributing
+pub const WEEKLY_TOTAL_VOTING_BALANCE: Map<U64Key, Uint128> =
+    Map::new("weekly_total_voting_balance");
+
+/// Amount of each asset which has been distributed but not yet claimed, keyed by asset_key
+pub const TOTAL_DISTRIBUTED_UNCLAIMED_FEES: Map<&[u8], Uint128> =
+    Map::new("total_distributed_unclaimed_fees");
+
+/// Timestamp of the last distribution of each asset claimed by each user
+pub const USER_LAST_CLAIMED_FEE_TIMESTAMP: Map<(&Addr, &[u8]), u64> =
+    Map::new("user_last_claimed_fee_timestamp");
+
+/// Native denoms which can be swept to GLOW
+pub const SWEEPABLE_DENOMS: Map<&str, bool> = Map::new("sweepable_denoms");
+
+/// Whether each user allows anyone to claim fees on their behalf
+pub const USER_CLAIM_FOR_ALLOWED: Map<&Addr, bool> = Map::new("user_claim_for_allowed");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -16,10 +40,178 @@ pub struct Config {
     pub glow_token: Addr,
     pub ve_token: Addr,
     pub terraswap_factory: Addr,
+    pub terraswap_router: Option<Addr>,
+    /// Recipient of orphaned distributions
+    pub treasury: Option<Addr>,
+    /// Whether distributions and claims are paused
+    #[serde(default)]
+    pub paused: bool,
+    /// Maximum spread accepted by sweeps
+    #[serde(default = "default_max_spread")]
+    pub max_spread: Decimal,
+    /// Number of weeks over which `Distribute` and `DistributeGlow` spread distributions
+    #[serde(default = "default_distribution_weeks")]
+    pub distribution_weeks: u64,
+}
+
+fn default_distribution_weeks() -> u64 {
+    1
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub contract_addr: Addr,
-    pub total_distributed_unclaimed_fees: Uint128,
+}
+
+/// Storage key of an asset, which is its denom or contract address
+pub fn asset_key(asset_info: &AssetInfo) -> Vec<u8> {
+    asset_info.to_string().into_bytes()
+}
+
+/// State as stored before multiple assets could be distributed.
+/// total_distributed_unclaimed_fees is only set on contracts which haven't been migrated.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub contract_addr: Addr,
+    pub total_distributed_unclaimed_fees: Option<Uint128>,
+}
+
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+
+/// Amount of GLOW distributed in each week, before distributions were keyed by asset
+pub const LEGACY_WEEKLY_TOKEN_DISTRIBUTION: Map<U64Key, Uint128> = Map::new("distributed_tokens");
+
+/// Timestamp of the last GLOW distribution claimed by each user, before claims were keyed by asset
+pub const LEGACY_USER_LAST_CLAIMED_FEE_TIMESTAMP: Map<&Addr, u64> =
+    Map::new("user_last_claimed_fee");
+
+/// Move the GLOW distributions, claims and unclaimed fees of a contract deployed
+/// before multiple assets could be distributed under the GLOW asset key.
+/// Does nothing if the contract was already migrated.
+pub fn migrate_legacy_distributions(storage: &mut dyn Storage, glow_token: &Addr) -> StdResult<()> {
+    let legacy_state = LEGACY_STATE.load(storage)?;
+    let total_distributed_unclaimed_fees = if let Some(total_distributed_unclaimed_fees) =
+        legacy_state.total_distributed_unclaimed_fees
+    {
+        total_distributed_unclaimed_fees
+    } else {
+        return Ok(());
+    };
+
+    STATE.save(
+        storage,
+        &State {
+            contract_addr: legacy_state.contract_addr,
+        },
+    )?;
+
+    // Nothing could be distributed before the contracts were registered
+    if glow_token == &Addr::unchecked("") {
+        return Ok(());
+    }
+
+    let glow_asset_info = AssetInfo::Token {
+        contract_addr: glow_token.to_string(),
+    };
+    let glow_key = asset_key(&glow_asset_info);
+
+    DISTRIBUTION_ASSETS.save(storage, &glow_key, &glow_asset_info)?;
+    TOTAL_DISTRIBUTED_UNCLAIMED_FEES.save(storage, &glow_key, &total_distributed_unclaimed_fees)?;
+
+    let weekly_distributions = LEGACY_WEEKLY_TOKEN_DISTRIBUTION
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, amount) = item?;
+            Ok((u64::from_be_bytes(k.try_into().unwrap()), amount))
+        })
+        .collect::<StdResult<Vec<(u64, Uint128)>>>()?;
+
+    for (week, amount) in weekly_distributions {
+        LEGACY_WEEKLY_TOKEN_DISTRIBUTION.remove(storage, U64Key::from(week));
+        WEEKLY_TOKEN_DISTRIBUTION.save(storage, (&glow_key, U64Key::from(week)), &amount)?;
+    }
+
+    let user_last_claimed_fee_timestamps = LEGACY_USER_LAST_CLAIMED_FEE_TIMESTAMP
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, timestamp) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k)?), timestamp))
+        })
+        .collect::<StdResult<Vec<(Addr, u64)>>>()?;
+
+    for (user, timestamp) in user_last_claimed_fee_timestamps {
+        LEGACY_USER_LAST_CLAIMED_FEE_TIMESTAMP.remove(storage, &user);
+        USER_LAST_CLAIMED_FEE_TIMESTAMP.save(storage, (&user, &glow_key), &timestamp)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_distribution_assets(storage: &dyn Storage) -> StdResult<Vec<AssetInfo>> {
+    DISTRIBUTION_ASSETS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+pub fn read_sweepable_denoms(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    SWEEPABLE_DENOMS
+        .keys(storage, None, None, Order::Ascending)
+        .map(|k| String::from_utf8(k).map_err(|_| StdError::invalid_utf8("parsing denom")))
+        .collect()
+}
+
+pub fn read_total_distributed_unclaimed_fees(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+) -> StdResult<Uint128> {
+    Ok(TOTAL_DISTRIBUTED_UNCLAIMED_FEES
+        .may_load(storage, &asset_key(asset_info))?
+        .unwrap_or_default())
+}
+
+pub fn read_all_total_distributed_unclaimed_fees(storage: &dyn Storage) -> StdResult<Vec<Asset>> {
+    read_distribution_assets(storage)?
+        .into_iter()
+        .map(|asset_info| {
+            Ok(Asset {
+                amount: read_total_distributed_unclaimed_fees(storage, &asset_info)?,
+                info: asset_info,
+            })
+        })
+        .collect()
+}
+
+pub const MAX_LIMIT: u32 = 30;
+pub const DEFAULT_LIMIT: u32 = 10;
+
+/// Maximum number of weeks with distributions which the OrphanedWeeks query reads per page
+pub const MAX_SCANNED_WEEKS: u32 = 100;
+
+/// Read the distributions of an asset in weeks starting at or after start_time
+pub fn read_weekly_distributions(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+    start_time: u64,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Uint128)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    WEEKLY_TOKEN_DISTRIBUTION
+        .prefix(&asset_key(asset_info))
+        .range(
+            storage,
+            Some(Bound::Inclusive(start_time.to_be_bytes().into())),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+
+            let timestamp = u64::from_be_bytes(k.try_into().unwrap());
+
+            Ok((timestamp, v))
+        })
+        .collect()
 }
@@ -1,15 +1,30 @@
-use crate::contract::{execute, instantiate, query, SECONDS_PER_WEEK};
+use crate::contract::{
+    execute, instantiate, migrate, query, MAX_DISTRIBUTION_WEEKS, SECONDS_PER_WEEK,
+};
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
-use crate::state::{Config, State, CONFIG, STATE};
+use crate::state::{
+    Config, LegacyState, State, CONFIG, LEGACY_STATE, LEGACY_USER_LAST_CLAIMED_FEE_TIMESTAMP,
+    LEGACY_WEEKLY_TOKEN_DISTRIBUTION, MAX_SCANNED_WEEKS, STATE, WEEKLY_TOKEN_DISTRIBUTION,
+    WEEKLY_TOTAL_VOTING_BALANCE,
+};
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    coins, from_binary, to_binary, Addr, Api, CosmosMsg, DepsMut, Env, SubMsg, Timestamp, Uint128,
-    WasmMsg,
+    coins, from_binary, to_binary, Addr, Api, BankMsg, CosmosMsg, Decimal, DepsMut, Env, StdError,
+    SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use cw900::fee_distributor::{ExecuteMsg, InstantiateMsg, QueryMsg, StakerResponse};
+use cw900::fee_distributor::{
+    ClaimableFeesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    OrphanedWeekResponse, OrphanedWeeksResponse, QueryMsg, ScheduledDistributionsResponse,
+    StakerClaimsResponse, StakerResponse, StateResponse, WeeklyClaimDistributionResponse,
+    WeeklyClaimResponse, WeeklyDistributionResponse,
+};
+use cw900::ve_token::Cw20HookMsg as VECw20HookMsg;
+use cw_storage_plus::U64Key;
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
 
 const VOTING_TOKEN: &str = "voting_token";
 const VE_TOKEN: &str = "ve_token";
@@ -17,6 +32,7 @@ const TERRASWAP_FACTORY: &str = "terraswap_factory";
 const TEST_CREATOR: &str = "creator";
 const TEST_VOTER: &str = "voter1";
 const TEST_VOTER_2: &str = "voter2";
+const OTHER_TOKEN: &str = "other_token";
 const BLOCKS_PER_SECOND: f64 = 0.16;
 
 fn increase_env_time(env: &mut Env, increase_time: u64) {
@@ -70,6 +86,11 @@ fn proper_initialization() {
             glow_token: Addr::unchecked("".to_string()),
             ve_token: Addr::unchecked("".to_string()),
             terraswap_factory: Addr::unchecked("".to_string()),
+            terraswap_router: None,
+            treasury: None,
+            paused: false,
+            max_spread: Decimal::percent(1),
+            distribution_weeks: 1,
             owner: deps.api.addr_validate(TEST_CREATOR).unwrap(),
         }
     );
@@ -91,9 +112,18 @@ fn proper_initialization() {
         state,
         State {
             contract_addr: deps.api.addr_validate(MOCK_CONTRACT_ADDR).unwrap(),
-            total_distributed_unclaimed_fees: Uint128::zero(),
         }
     );
+
+    // GLOW is registered for distribution
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.distribution_assets,
+        vec![AssetInfo::Token {
+            contract_addr: VOTING_TOKEN.to_string(),
+        }]
+    );
 }
 
 #[test]
@@ -184,9 +214,15 @@ fn distribute_glow_to_voter() {
         response,
         StakerResponse {
             balance: Uint128::from(100u128),
-            initial_last_claimed_fee_timestamp: 0,
-            last_claimed_fee_timestamp: 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK,
-            claimable_fees_lower_bound: Uint128::from(10u128)
+            claim_for_allowed: false,
+            claimable_fees: vec![ClaimableFeesResponse {
+                asset_info: AssetInfo::Token {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                },
+                initial_last_claimed_fee_timestamp: 0,
+                last_claimed_fee_timestamp: 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK,
+                claimable_fees_lower_bound: Uint128::from(10u128)
+            }]
         }
     );
 
@@ -468,3 +504,1613 @@ fn many_distribute_glow_to_two_voters() {
 
     assert_eq!(execute_res.messages, vec![]);
 }
+
+#[test]
+fn register_distribution_asset() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+
+    // Only the owner can register assets
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::RegisterDistributionAsset {
+        asset_info: uusd.clone(),
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Unregistered assets can't be distributed
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+    )]);
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::Distribute {
+        asset_info: uusd.clone(),
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::AssetNotRegistered {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::RegisterDistributionAsset {
+        asset_info: uusd.clone(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    // Assets can't be registered twice
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::AssetAlreadyRegistered {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.distribution_assets,
+        vec![
+            uusd,
+            AssetInfo::Token {
+                contract_addr: VOTING_TOKEN.to_string(),
+            }
+        ]
+    );
+}
+
+#[test]
+fn distribute_many_assets_to_two_voters() {
+    let mut deps = mock_dependencies(&coins(1000, "uusd"));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let other_token = AssetInfo::Token {
+        contract_addr: OTHER_TOKEN.to_string(),
+    };
+    let glow = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    for asset_info in [uusd.clone(), other_token.clone()] {
+        let msg = ExecuteMsg::RegisterDistributionAsset { asset_info };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &OTHER_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(400u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(300u128)),
+            ],
+        ),
+    ]);
+
+    // Distribute each asset
+    let info = mock_info(TEST_VOTER, &[]);
+    for asset_info in [uusd.clone(), other_token.clone(), glow.clone()] {
+        let msg = ExecuteMsg::Distribute { asset_info };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    // Nothing more to distribute until the contract receives more
+    let msg = ExecuteMsg::Distribute {
+        asset_info: uusd.clone(),
+    };
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::NothingToDistribute {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.total_distributed_unclaimed_fees,
+        vec![
+            Asset {
+                info: other_token.clone(),
+                amount: Uint128::from(400u128),
+            },
+            Asset {
+                info: uusd.clone(),
+                amount: Uint128::from(1000u128),
+            },
+            Asset {
+                info: glow.clone(),
+                amount: Uint128::from(100u128),
+            },
+        ]
+    );
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    // Each voter claims their share of every asset
+    let info = mock_info(TEST_VOTER, &[]);
+    let claim_msg = ExecuteMsg::Claim { limit: None };
+    let execute_res = execute(deps.as_mut(), env.clone(), info, claim_msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: OTHER_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_VOTER.to_string(),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: TEST_VOTER.to_string(),
+                amount: coins(250, "uusd"),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_VOTER.to_string(),
+                    amount: Uint128::from(25u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Staker {
+            address: TEST_VOTER_2.to_string(),
+            fee_start_after: None,
+            fee_limit: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response
+            .claimable_fees
+            .iter()
+            .map(|claimable| claimable.claimable_fees_lower_bound)
+            .collect::<Vec<_>>(),
+        vec![
+            Uint128::from(300u128),
+            Uint128::from(750u128),
+            Uint128::from(75u128)
+        ]
+    );
+
+    let res = query(deps.as_ref(), env, QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response
+            .total_distributed_unclaimed_fees
+            .iter()
+            .map(|asset| asset.amount)
+            .collect::<Vec<_>>(),
+        vec![
+            Uint128::from(300u128),
+            Uint128::from(750u128),
+            Uint128::from(75u128)
+        ]
+    );
+}
+
+#[test]
+fn claim_and_lock() {
+    let mut deps = mock_dependencies(&coins(100, "uusd"));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::RegisterDistributionAsset {
+        asset_info: uusd.clone(),
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Distribute { asset_info: uusd };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    // The GLOW is sent to the ve token to increase the lock, other assets are sent to the voter
+    let msg = ExecuteMsg::ClaimAndLock {
+        lock_id: 1,
+        limit: None,
+    };
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: TEST_VOTER.to_string(),
+                amount: coins(100, "uusd"),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: VE_TOKEN.to_string(),
+                    amount: Uint128::from(10u128),
+                    msg: to_binary(&VECw20HookMsg::IncreaseLockAmount {
+                        lock_id: 1,
+                        address: Some(TEST_VOTER.to_string()),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+}
+
+#[test]
+fn claim_for() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    // Claiming for a user who hasn't allowed it fails
+    let keeper_info = mock_info(TEST_VOTER_2, &[]);
+    let msg = ExecuteMsg::ClaimFor {
+        address: TEST_VOTER.to_string(),
+        limit: None,
+    };
+    match execute(deps.as_mut(), env.clone(), keeper_info.clone(), msg.clone()) {
+        Err(ContractError::ClaimForNotAllowed {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let allow_msg = ExecuteMsg::AllowClaimFor { allowed: true };
+    execute(deps.as_mut(), env.clone(), info, allow_msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+            fee_start_after: None,
+            fee_limit: None,
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert!(response.claim_for_allowed);
+
+    // The claimed fees go to the user, not the keeper
+    let execute_res = execute(deps.as_mut(), env, keeper_info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(10u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn claim_uses_cached_total_voting_balance() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&"others".to_string(), &Uint128::from(100u128)),
+            ],
+        ),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    let week_timestamp = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    assert_eq!(
+        WEEKLY_TOTAL_VOTING_BALANCE
+            .load(deps.as_ref().storage, U64Key::from(week_timestamp))
+            .unwrap(),
+        Uint128::from(200u128)
+    );
+
+    // The total voting balance reported by the ve token changes,
+    // but the claim uses the total cached when distributing
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&"others".to_string(), &Uint128::from(300u128)),
+            ],
+        ),
+    ]);
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let claim_msg = ExecuteMsg::Claim { limit: None };
+    let execute_res = execute(deps.as_mut(), env, info, claim_msg).unwrap();
+
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn distribute_over_weeks() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(103u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+                (&"others".to_string(), &Uint128::from(100u128)),
+            ],
+        ),
+    ]);
+
+    let glow = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+
+    // Only the owner can spread distributions over several weeks
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeOverWeeks {
+            asset_info: glow.clone(),
+            weeks: 4,
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Distributions can't span zero weeks or more than the maximum
+    for weeks in [0u64, MAX_DISTRIBUTION_WEEKS + 1] {
+        let info = mock_info(TEST_CREATOR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::DistributeOverWeeks {
+                asset_info: glow.clone(),
+                weeks,
+            },
+        );
+        match res {
+            Err(ContractError::InvalidDistributionWeeks { max_weeks }) => {
+                assert_eq!(max_weeks, MAX_DISTRIBUTION_WEEKS)
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeOverWeeks {
+            asset_info: glow.clone(),
+            weeks: 4,
+        },
+    )
+    .unwrap();
+
+    // The remainder is distributed in the current week
+    let week_timestamp = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let scheduled: ScheduledDistributionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ScheduledDistributions {
+                asset_info: glow.clone(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        scheduled.distributions,
+        vec![
+            WeeklyDistributionResponse {
+                week_timestamp,
+                amount: Uint128::from(28u128),
+            },
+            WeeklyDistributionResponse {
+                week_timestamp: week_timestamp + SECONDS_PER_WEEK,
+                amount: Uint128::from(25u128),
+            },
+            WeeklyDistributionResponse {
+                week_timestamp: week_timestamp + 2 * SECONDS_PER_WEEK,
+                amount: Uint128::from(25u128),
+            },
+            WeeklyDistributionResponse {
+                week_timestamp: week_timestamp + 3 * SECONDS_PER_WEEK,
+                amount: Uint128::from(25u128),
+            },
+        ]
+    );
+
+    // Paginate with start_after
+    let scheduled: ScheduledDistributionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ScheduledDistributions {
+                asset_info: glow.clone(),
+                start_after: Some(week_timestamp + SECONDS_PER_WEEK),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        scheduled.distributions,
+        vec![WeeklyDistributionResponse {
+            week_timestamp: week_timestamp + 2 * SECONDS_PER_WEEK,
+            amount: Uint128::from(25u128),
+        }]
+    );
+
+    // Only the first two weeks can be claimed after two weeks
+    increase_env_time(&mut env, 2 * SECONDS_PER_WEEK);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Claim { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(26u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // Past weeks are no longer scheduled
+    let scheduled: ScheduledDistributionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::ScheduledDistributions {
+                asset_info: glow,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(scheduled.distributions.len(), 2);
+
+    // The remaining weeks can be claimed once they have passed
+    increase_env_time(&mut env, 2 * SECONDS_PER_WEEK);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), env, info, ExecuteMsg::Claim { limit: None }).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(24u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn distribute_over_configured_weeks() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let glow = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+    let update_weeks_msg = |weeks: u64| ExecuteMsg::UpdateConfig {
+        owner: None,
+        treasury: None,
+        terraswap_router: None,
+        paused: None,
+        max_spread: None,
+        distribution_weeks: Some(weeks),
+    };
+
+    // Only the owner can set the number of weeks
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), env.clone(), info, update_weeks_msg(4)) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for weeks in [0u64, MAX_DISTRIBUTION_WEEKS + 1] {
+        let info = mock_info(TEST_CREATOR, &[]);
+        match execute(deps.as_mut(), env.clone(), info, update_weeks_msg(weeks)) {
+            Err(ContractError::InvalidDistributionWeeks { max_weeks }) => {
+                assert_eq!(max_weeks, MAX_DISTRIBUTION_WEEKS)
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, update_weeks_msg(4)).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.distribution_weeks, 4);
+
+    // Anyone can distribute, but the funds are still streamed over the configured weeks
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Distribute {
+            asset_info: glow.clone(),
+        },
+    )
+    .unwrap();
+
+    let week_timestamp = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let scheduled: ScheduledDistributionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::ScheduledDistributions {
+                asset_info: glow,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        scheduled.distributions,
+        (0..4)
+            .map(|week| WeeklyDistributionResponse {
+                week_timestamp: week_timestamp + week * SECONDS_PER_WEEK,
+                amount: Uint128::from(25u128),
+            })
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn recover_orphaned_week() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(90u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let glow = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeOverWeeks {
+            asset_info: glow.clone(),
+            weeks: 3,
+        },
+    )
+    .unwrap();
+
+    // Everything is unstaked before the following weeks
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(90u128))],
+    )]);
+
+    let week_timestamp = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    increase_env_time(&mut env, 3 * SECONDS_PER_WEEK);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::OrphanedWeeks {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: OrphanedWeeksResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.weeks,
+        vec![
+            OrphanedWeekResponse {
+                week_timestamp: week_timestamp + SECONDS_PER_WEEK,
+                distributions: vec![Asset {
+                    info: glow.clone(),
+                    amount: Uint128::from(30u128),
+                }],
+            },
+            OrphanedWeekResponse {
+                week_timestamp: week_timestamp + 2 * SECONDS_PER_WEEK,
+                distributions: vec![Asset {
+                    info: glow.clone(),
+                    amount: Uint128::from(30u128),
+                }],
+            },
+        ]
+    );
+
+    // The first week had voting balance, and the current week can still be claimed
+    for week in [week_timestamp, week_timestamp + 3 * SECONDS_PER_WEEK] {
+        let info = mock_info(TEST_VOTER, &[]);
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::RecoverOrphanedWeek { week },
+        );
+        match res {
+            Err(ContractError::WeekNotOrphaned {}) => {}
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    // Without a treasury, the distributions can only be moved to a week with voting balance
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::RecoverOrphanedWeek {
+            week: week_timestamp + SECONDS_PER_WEEK,
+        },
+    );
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Cache a voting balance for the current week,
+    // as a distribution in the current week would
+    let current_week_timestamp = week_timestamp + 3 * SECONDS_PER_WEEK;
+    WEEKLY_TOTAL_VOTING_BALANCE
+        .save(
+            deps.as_mut().storage,
+            U64Key::from(current_week_timestamp),
+            &Uint128::from(100u128),
+        )
+        .unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::RecoverOrphanedWeek {
+            week: week_timestamp + SECONDS_PER_WEEK,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::ScheduledDistributions {
+            asset_info: glow.clone(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: ScheduledDistributionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.distributions,
+        vec![WeeklyDistributionResponse {
+            week_timestamp: current_week_timestamp,
+            amount: Uint128::from(30u128),
+        }]
+    );
+
+    // Only the owner can set the treasury
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        treasury: Some("treasury".to_string()),
+        terraswap_router: None,
+        paused: None,
+        max_spread: None,
+        distribution_weeks: None,
+    };
+    match execute(deps.as_mut(), env.clone(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // With a treasury, the distributions are sent to it
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::RecoverOrphanedWeek {
+            week: week_timestamp + 2 * SECONDS_PER_WEEK,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::from(30u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::State {}).unwrap();
+    let response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.total_distributed_unclaimed_fees,
+        vec![Asset {
+            info: glow,
+            amount: Uint128::from(60u128),
+        }]
+    );
+
+    // Nothing is left to recover
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::OrphanedWeeks {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: OrphanedWeeksResponse = from_binary(&res).unwrap();
+    assert_eq!(response.weeks, vec![]);
+    assert_eq!(response.next_start_after, None);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::RecoverOrphanedWeek {
+            week: week_timestamp + 2 * SECONDS_PER_WEEK,
+        },
+    );
+    match res {
+        Err(ContractError::WeekNotOrphaned {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn orphaned_weeks_scan_bounded_number_of_weeks() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let glow_key = VOTING_TOKEN.as_bytes();
+    let scanned_weeks = MAX_SCANNED_WEEKS as u64;
+
+    // Distribute in 150 weeks. Only the last 30 of them are orphaned.
+    for week in 1..=150u64 {
+        WEEKLY_TOKEN_DISTRIBUTION
+            .save(
+                deps.as_mut().storage,
+                (glow_key, U64Key::from(week * SECONDS_PER_WEEK)),
+                &Uint128::from(10u128),
+            )
+            .unwrap();
+
+        if week <= 120 {
+            WEEKLY_TOTAL_VOTING_BALANCE
+                .save(
+                    deps.as_mut().storage,
+                    U64Key::from(week * SECONDS_PER_WEEK),
+                    &Uint128::from(100u128),
+                )
+                .unwrap();
+        }
+    }
+
+    let env = mock_env_height(0, 151 * SECONDS_PER_WEEK);
+    let query_orphaned_weeks = |start_after: Option<u64>| -> OrphanedWeeksResponse {
+        from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::OrphanedWeeks {
+                    start_after,
+                    limit: Some(30),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // The first page stops after MAX_SCANNED_WEEKS weeks without finding any orphaned week
+    let response = query_orphaned_weeks(None);
+    assert_eq!(response.weeks, vec![]);
+    assert_eq!(
+        response.next_start_after,
+        Some(scanned_weeks * SECONDS_PER_WEEK)
+    );
+
+    // The second page finds every orphaned week
+    let response = query_orphaned_weeks(response.next_start_after);
+    assert_eq!(
+        response
+            .weeks
+            .iter()
+            .map(|week| week.week_timestamp)
+            .collect::<Vec<_>>(),
+        (121..=150u64)
+            .map(|week| week * SECONDS_PER_WEEK)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(response.next_start_after, None);
+}
+
+#[test]
+fn staker_claims() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    let ve_balances: &[(&String, &Uint128)] = &[
+        (&TEST_VOTER.to_string(), &Uint128::from(100u128)),
+        (&"others".to_string(), &Uint128::from(300u128)),
+    ];
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (&VE_TOKEN.to_string(), ve_balances),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(140u128))],
+        ),
+        (&VE_TOKEN.to_string(), ve_balances),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    // Distributions of the current week aren't included
+    let week_timestamp = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let glow = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+    let weekly_claim =
+        |week_timestamp: u64, distributed_amount: u128, claimed: bool| WeeklyClaimResponse {
+            week_timestamp,
+            user_voting_balance: Uint128::from(100u128),
+            total_voting_balance: Uint128::from(400u128),
+            share: Decimal::percent(25),
+            distributions: vec![WeeklyClaimDistributionResponse {
+                asset_info: glow.clone(),
+                distributed_amount: Uint128::from(distributed_amount),
+                claim_amount: Uint128::from(distributed_amount / 4),
+                claimed,
+            }],
+        };
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerClaims {
+            address: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: StakerClaimsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.claims,
+        vec![weekly_claim(week_timestamp, 100, false)]
+    );
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerClaims {
+            address: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: StakerClaimsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.claims,
+        vec![
+            weekly_claim(week_timestamp, 100, false),
+            weekly_claim(week_timestamp + SECONDS_PER_WEEK, 40, false),
+        ]
+    );
+
+    // Claimed distributions are marked as claimed
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Claim { limit: Some(1) },
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::StakerClaims {
+            address: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let response: StakerClaimsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.claims,
+        vec![
+            weekly_claim(week_timestamp, 100, true),
+            weekly_claim(week_timestamp + SECONDS_PER_WEEK, 40, false),
+        ]
+    );
+
+    // Paginate with start_after and limit
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::StakerClaims {
+            address: TEST_VOTER.to_string(),
+            start_after: Some(week_timestamp),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let response: StakerClaimsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.claims,
+        vec![weekly_claim(week_timestamp + SECONDS_PER_WEEK, 40, false)]
+    );
+}
+
+#[test]
+fn pause_distributions_and_claims() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+    let mut env = mock_env_height(0, 1000000);
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let pause_msg = |paused: bool| ExecuteMsg::UpdateConfig {
+        owner: None,
+        treasury: None,
+        terraswap_router: None,
+        paused: Some(paused),
+        max_spread: None,
+        distribution_weeks: None,
+    };
+
+    // Only the owner can pause
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), env.clone(), info, pause_msg(true)) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, pause_msg(true)).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let response: ConfigResponse = from_binary(&res).unwrap();
+    assert!(response.paused);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    ) {
+        Err(ContractError::Paused {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, pause_msg(false)).unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, pause_msg(true)).unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Claim { limit: None },
+    ) {
+        Err(ContractError::Paused {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, pause_msg(false)).unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Claim { limit: None }).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn sweep_allowed_denoms() {
+    let mut deps = mock_dependencies(&coins(1000, "uusd"));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier
+        .with_terraswap_pairs(&[(&format!("uusd{}", VOTING_TOKEN), &"pair".to_string())]);
+
+    let sweep_msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+        max_spread: None,
+        belief_price: None,
+        intermediate_asset: None,
+    };
+
+    // Denoms can't be swept until the owner allows them
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), mock_env(), info, sweep_msg.clone()) {
+        Err(ContractError::DenomNotSweepable {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::AddSweepableDenom {
+        denom: "uusd".to_string(),
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(response.sweepable_denoms, vec!["uusd".to_string()]);
+
+    // The swap is limited to the default max spread
+    let swap_msg = |max_spread: Decimal, belief_price: Option<Decimal>| {
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair".to_string(),
+            msg: to_binary(&TerraswapExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+                max_spread: Some(max_spread),
+                belief_price,
+                to: None,
+            })
+            .unwrap(),
+            funds: coins(1000, "uusd"),
+        }))]
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, sweep_msg.clone()).unwrap();
+    assert_eq!(res.messages, swap_msg(Decimal::percent(1), None));
+
+    // Anyone else must give a belief_price
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), mock_env(), info, sweep_msg.clone()) {
+        Err(ContractError::BeliefPriceRequired {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let priced_sweep_msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+        max_spread: Some(Decimal::percent(50)),
+        belief_price: Some(Decimal::percent(10)),
+        intermediate_asset: None,
+    };
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, priced_sweep_msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        swap_msg(Decimal::percent(1), Some(Decimal::percent(10)))
+    );
+
+    // The owner can change the max spread, which caps the one of the sweeper
+    let update_max_spread_msg = |max_spread: Decimal| ExecuteMsg::UpdateConfig {
+        owner: None,
+        treasury: None,
+        terraswap_router: None,
+        paused: None,
+        max_spread: Some(max_spread),
+        distribution_weeks: None,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_max_spread_msg(Decimal::percent(101)),
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "max_spread must be 0 to 1")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_max_spread_msg(Decimal::percent(2)),
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(response.max_spread, Decimal::percent(2));
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, priced_sweep_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        swap_msg(Decimal::percent(2), Some(Decimal::percent(10)))
+    );
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::RemoveSweepableDenom {
+        denom: "uusd".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::DenomNotSweepable {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), mock_env(), info, sweep_msg) {
+        Err(ContractError::DenomNotSweepable {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn rescue_token() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DistributeGlow {},
+    )
+    .unwrap();
+
+    // GLOW and another token are sent by mistake
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+        ),
+        (
+            &OTHER_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10u128))],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+        ),
+    ]);
+
+    let rescue_msg = |contract_addr: &str, amount: u128| ExecuteMsg::RescueToken {
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: contract_addr.to_string(),
+            },
+            amount: Uint128::from(amount),
+        },
+        recipient: TEST_CREATOR.to_string(),
+    };
+
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), mock_env(), info, rescue_msg(OTHER_TOKEN, 10)) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The distributed GLOW can't be rescued
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        rescue_msg(VOTING_TOKEN, 51),
+    ) {
+        Err(ContractError::InsufficientRescuableBalance {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for (contract_addr, amount) in [(VOTING_TOKEN, 50u128), (OTHER_TOKEN, 10u128)] {
+        let info = mock_info(TEST_CREATOR, &[]);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            rescue_msg(contract_addr, amount),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_CREATOR.to_string(),
+                    amount: Uint128::from(amount),
+                })
+                .unwrap(),
+            }))]
+        );
+    }
+}
+
+#[test]
+fn migrate_legacy_distributions() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env_height(0, 1000000);
+    let first_week = 1000000 / SECONDS_PER_WEEK * SECONDS_PER_WEEK;
+    let second_week = first_week + SECONDS_PER_WEEK;
+
+    // Store the state of a contract which could only distribute GLOW.
+    // The voter claimed the first week, leaving 20 GLOW of the second week and
+    // 5 GLOW of the first week unclaimed.
+
+    CONFIG
+        .save(
+            deps.as_mut().storage,
+            &Config {
+                owner: Addr::unchecked(TEST_CREATOR),
+                glow_token: Addr::unchecked(VOTING_TOKEN),
+                ve_token: Addr::unchecked(VE_TOKEN),
+                terraswap_factory: Addr::unchecked(TERRASWAP_FACTORY),
+                terraswap_router: None,
+                treasury: None,
+                paused: false,
+                max_spread: Decimal::percent(1),
+                distribution_weeks: 1,
+            },
+        )
+        .unwrap();
+    LEGACY_STATE
+        .save(
+            deps.as_mut().storage,
+            &LegacyState {
+                contract_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+                total_distributed_unclaimed_fees: Some(Uint128::from(25u128)),
+            },
+        )
+        .unwrap();
+    for (week, amount) in [(first_week, 15u128), (second_week, 20u128)] {
+        LEGACY_WEEKLY_TOKEN_DISTRIBUTION
+            .save(
+                deps.as_mut().storage,
+                U64Key::from(week),
+                &Uint128::from(amount),
+            )
+            .unwrap();
+    }
+    LEGACY_USER_LAST_CLAIMED_FEE_TIMESTAMP
+        .save(
+            deps.as_mut().storage,
+            &Addr::unchecked(TEST_VOTER),
+            &first_week,
+        )
+        .unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[(&TEST_VOTER.to_string(), &Uint128::from(100u128))],
+    )]);
+
+    let glow_asset_info = AssetInfo::Token {
+        contract_addr: VOTING_TOKEN.to_string(),
+    };
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK * 2);
+
+    for _ in 0..2 {
+        // Migrating an already migrated contract changes nothing
+        let _res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        assert_eq!(
+            STATE.load(deps.as_ref().storage).unwrap(),
+            State {
+                contract_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            }
+        );
+        assert_eq!(
+            LEGACY_WEEKLY_TOKEN_DISTRIBUTION
+                .may_load(deps.as_ref().storage, U64Key::from(first_week))
+                .unwrap(),
+            None
+        );
+
+        // GLOW is registered along with its unclaimed fees
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+        let response: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(response.distribution_assets, vec![glow_asset_info.clone()]);
+
+        let res = query(deps.as_ref(), env.clone(), QueryMsg::State {}).unwrap();
+        let response: StateResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            response.total_distributed_unclaimed_fees,
+            vec![Asset {
+                info: glow_asset_info.clone(),
+                amount: Uint128::from(25u128),
+            }]
+        );
+
+        // The voter can only claim the second week
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Staker {
+                address: TEST_VOTER.to_string(),
+                fee_start_after: None,
+                fee_limit: None,
+            },
+        )
+        .unwrap();
+        let response: StakerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            response.claimable_fees,
+            vec![ClaimableFeesResponse {
+                asset_info: glow_asset_info.clone(),
+                initial_last_claimed_fee_timestamp: first_week,
+                last_claimed_fee_timestamp: second_week,
+                claimable_fees_lower_bound: Uint128::from(20u128),
+            }]
+        );
+    }
+}
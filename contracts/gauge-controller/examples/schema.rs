@@ -0,0 +1,25 @@
+use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use cw900::gauge_controller::{
+    ConfigResponse, ExecuteMsg, GaugeWeightResponse, GaugesResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, TotalWeightResponse, UserVotesResponse,
+};
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(InstantiateMsg), &out_dir);
+    export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(GaugesResponse), &out_dir);
+    export_schema(&schema_for!(GaugeWeightResponse), &out_dir);
+    export_schema(&schema_for!(TotalWeightResponse), &out_dir);
+    export_schema(&schema_for!(UserVotesResponse), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+}
@@ -0,0 +1,467 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cw_storage_plus::U64Key;
+
+use crate::error::ContractError;
+use crate::querier::{query_staker_locks, query_ve_config};
+use crate::state::{
+    apply_pending_coefficient_changes, load_weight_at_timestamp, read_gauges, read_user_votes,
+    Config, UserVote, VoteSlope, Weight, CONFIG, GAUGES, GAUGE_COEFFICIENT_CHANGES, GAUGE_WEIGHTS,
+    LAST_USER_VOTE, TOTAL_COEFFICIENT_CHANGES, TOTAL_WEIGHT, USER_POWER_USED, USER_VOTES,
+};
+
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, Uint128,
+};
+
+use cw900::curve::QuadraticEquationCoefficients;
+use cw900::gauge_controller::{
+    ConfigResponse, ExecuteMsg, GaugeWeightResponse, GaugesResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, TotalWeightResponse, UserVoteResponse, UserVotesResponse, MAX_WEIGHT_BPS,
+    WEIGHT_VOTE_DELAY,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let config = Config {
+        owner: info.sender,
+        pending_owner: None,
+        ve_token: deps.api.addr_validate(&msg.ve_token)?,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    // Save an initial empty total weight
+    let total_weight = Weight {
+        timestamp: env.block.time.seconds(),
+        ..Weight::default()
+    };
+    TOTAL_WEIGHT.save(deps.storage, &total_weight, env.block.time.seconds())?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::AddGauge { gauge } => add_gauge(deps, env, info, gauge),
+        ExecuteMsg::VoteForGaugeWeight { gauge, weight_bps } => {
+            vote_for_gauge_weight(deps, env, info, gauge, weight_bps)
+        }
+        ExecuteMsg::ProposeNewOwner { new_owner } => propose_new_owner(deps, info, new_owner),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, info),
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, info),
+    }
+}
+
+pub fn add_gauge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    gauge: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let gauge = deps.api.addr_validate(&gauge)?;
+    if GAUGES.has(deps.storage, &gauge) {
+        return Err(ContractError::GaugeAlreadyExists {});
+    }
+
+    GAUGES.save(deps.storage, &gauge, &true)?;
+
+    let gauge_weight = Weight {
+        timestamp: env.block.time.seconds(),
+        ..Weight::default()
+    };
+    GAUGE_WEIGHTS.save(
+        deps.storage,
+        &gauge,
+        &gauge_weight,
+        env.block.time.seconds(),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_gauge"),
+        attr("gauge", gauge.to_string()),
+    ]))
+}
+
+pub fn vote_for_gauge_weight(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    gauge: String,
+    weight_bps: u16,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let user = info.sender;
+    let current_time = env.block.time.seconds();
+
+    let gauge = deps.api.addr_validate(&gauge)?;
+    if !GAUGES.has(deps.storage, &gauge) {
+        return Err(ContractError::GaugeNotFound {});
+    }
+
+    if weight_bps > MAX_WEIGHT_BPS {
+        return Err(ContractError::InvalidWeight {
+            max_weight_bps: MAX_WEIGHT_BPS,
+        });
+    }
+
+    // Validate that the user hasn't voted for the gauge too recently
+    if let Some(last_vote_time) = LAST_USER_VOTE.may_load(deps.storage, (&user, &gauge))? {
+        if current_time < last_vote_time + WEIGHT_VOTE_DELAY {
+            return Err(ContractError::VoteTooOften {
+                next_vote_time: last_vote_time + WEIGHT_VOTE_DELAY,
+            });
+        }
+    }
+
+    // Validate that the user doesn't allocate more than all of its voting power
+    let prev_vote = USER_VOTES.may_load(deps.storage, (&user, &gauge))?;
+    let prev_weight_bps = prev_vote.as_ref().map_or(0, |vote| vote.weight_bps);
+    let power_used_bps = USER_POWER_USED
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default()
+        - prev_weight_bps
+        + weight_bps;
+
+    if power_used_bps > MAX_WEIGHT_BPS {
+        return Err(ContractError::UsedTooMuchPower {});
+    }
+
+    let ve_config = query_ve_config(&deps.querier, &config.ve_token)?;
+
+    // Bring the gauge and total weights up to date
+    let mut gauge_weight = GAUGE_WEIGHTS.load(deps.storage, &gauge)?;
+    apply_pending_coefficient_changes(
+        deps.storage,
+        Some(&gauge),
+        &mut gauge_weight,
+        current_time,
+        ve_config.seconds_per_week,
+    )?;
+
+    let mut total_weight = TOTAL_WEIGHT.load(deps.storage)?;
+    apply_pending_coefficient_changes(
+        deps.storage,
+        None,
+        &mut total_weight,
+        current_time,
+        ve_config.seconds_per_week,
+    )?;
+
+    // Remove the slopes of the previous vote which haven't ended yet.
+    // Slopes which have ended were already removed as part of the coefficient changes.
+    if let Some(prev_vote) = prev_vote {
+        for slope in prev_vote.slopes {
+            if slope.end_lock_time <= current_time {
+                continue;
+            }
+
+            gauge_weight.coefficients -= slope.coefficients.clone();
+            total_weight.coefficients -= slope.coefficients.clone();
+
+            update_coefficient_changes(deps.storage, &gauge, slope.end_lock_time, |changes| {
+                changes - slope.coefficients.clone()
+            })?;
+        }
+    }
+
+    // Add a slope for each of the user's active locks
+    let mut slopes = vec![];
+    if weight_bps > 0 {
+        let locks = query_staker_locks(&deps.querier, &config.ve_token, &user)?;
+
+        for lock in locks {
+            if lock.end_lock_time <= current_time {
+                continue;
+            }
+
+            let coefficients = QuadraticEquationCoefficients::for_lock(
+                lock.deposited_amount,
+                lock.start_lock_time,
+                lock.end_lock_time,
+                ve_config.decay_model,
+            )
+            .multiply_ratio(weight_bps as u64, MAX_WEIGHT_BPS as u64);
+
+            gauge_weight.coefficients += coefficients.clone();
+            total_weight.coefficients += coefficients.clone();
+
+            update_coefficient_changes(deps.storage, &gauge, lock.end_lock_time, |changes| {
+                changes + coefficients.clone()
+            })?;
+
+            slopes.push(VoteSlope {
+                end_lock_time: lock.end_lock_time,
+                coefficients,
+            });
+        }
+
+        if slopes.is_empty() {
+            return Err(ContractError::NoActiveLocks {});
+        }
+
+        USER_VOTES.save(
+            deps.storage,
+            (&user, &gauge),
+            &UserVote {
+                weight_bps,
+                timestamp: current_time,
+                slopes,
+            },
+        )?;
+    } else {
+        USER_VOTES.remove(deps.storage, (&user, &gauge));
+    }
+
+    LAST_USER_VOTE.save(deps.storage, (&user, &gauge), &current_time)?;
+    USER_POWER_USED.save(deps.storage, &user, &power_used_bps)?;
+    GAUGE_WEIGHTS.save(deps.storage, &gauge, &gauge_weight, current_time)?;
+    TOTAL_WEIGHT.save(deps.storage, &total_weight, current_time)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "vote_for_gauge_weight"),
+        attr("user", user.to_string()),
+        attr("gauge", gauge.to_string()),
+        attr("weight_bps", weight_bps.to_string()),
+    ]))
+}
+
+/// Update the coefficient changes scheduled at end_lock_time for both a gauge and the total
+fn update_coefficient_changes<F>(
+    storage: &mut dyn Storage,
+    gauge: &Addr,
+    end_lock_time: u64,
+    update: F,
+) -> StdResult<()>
+where
+    F: Fn(QuadraticEquationCoefficients) -> QuadraticEquationCoefficients,
+{
+    GAUGE_COEFFICIENT_CHANGES.update(
+        storage,
+        (gauge, U64Key::from(end_lock_time)),
+        |changes| -> StdResult<_> { Ok(update(changes.unwrap_or_default())) },
+    )?;
+
+    TOTAL_COEFFICIENT_CHANGES.update(
+        storage,
+        U64Key::from(end_lock_time),
+        |changes| -> StdResult<_> { Ok(update(changes.unwrap_or_default())) },
+    )?;
+
+    Ok(())
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    config.pending_owner = Some(new_owner.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("new_owner", new_owner),
+    ]))
+}
+
+pub fn cancel_ownership_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.pending_owner.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+
+    config.pending_owner = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "cancel_ownership_proposal")]))
+}
+
+pub fn accept_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    match config.pending_owner {
+        Some(ref pending_owner) if *pending_owner == info.sender => {}
+        Some(_) => return Err(ContractError::Unauthorized {}),
+        None => return Err(ContractError::NoPendingOwner {}),
+    }
+
+    config.owner = info.sender;
+    config.pending_owner = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_ownership"),
+        attr("owner", config.owner),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Gauges { start_after, limit } => {
+            to_binary(&query_gauges(deps, start_after, limit)?)
+        }
+        QueryMsg::GaugeWeight { gauge, timestamp } => {
+            to_binary(&query_gauge_weight(deps, env, gauge, timestamp)?)
+        }
+        QueryMsg::TotalWeight { timestamp } => {
+            to_binary(&query_total_weight(deps, env, timestamp)?)
+        }
+        QueryMsg::UserVotes { address } => to_binary(&query_user_votes(deps, address)?),
+    }
+}
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: config.owner.to_string(),
+        pending_owner: config
+            .pending_owner
+            .map(|pending_owner| pending_owner.to_string()),
+        ve_token: config.ve_token.to_string(),
+    })
+}
+
+pub fn query_gauges(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<GaugesResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_validate(&start_after)?)
+    } else {
+        None
+    };
+
+    let gauges = read_gauges(deps.storage, start_after, limit)?;
+
+    Ok(GaugesResponse {
+        gauges: gauges.iter().map(|addr| addr.to_string()).collect(),
+    })
+}
+
+/// Evaluate the weight of a gauge, or of the total if no gauge is given,
+/// at the start of the week containing the timestamp.
+/// Returns the start of the week along with the weight.
+fn weight_at_week_start(
+    deps: Deps,
+    env: Env,
+    gauge: Option<&Addr>,
+    timestamp: Option<u64>,
+) -> StdResult<(u64, Uint128)> {
+    let config = CONFIG.load(deps.storage)?;
+    let ve_config = query_ve_config(&deps.querier, &config.ve_token)?;
+
+    let timestamp = timestamp.unwrap_or_else(|| env.block.time.seconds());
+    let week_timestamp = timestamp / ve_config.seconds_per_week * ve_config.seconds_per_week;
+
+    let weight = load_weight_at_timestamp(
+        deps.storage,
+        gauge,
+        week_timestamp,
+        ve_config.seconds_per_week,
+    )?;
+
+    Ok((
+        week_timestamp,
+        weight.coefficients.evaluate_voting_power_at_timestamp(
+            week_timestamp,
+            ve_config.voting_power_constant_divisor,
+        ),
+    ))
+}
+
+pub fn query_gauge_weight(
+    deps: Deps,
+    env: Env,
+    gauge: String,
+    timestamp: Option<u64>,
+) -> StdResult<GaugeWeightResponse> {
+    let gauge = deps.api.addr_validate(&gauge)?;
+
+    let (week_timestamp, weight) =
+        weight_at_week_start(deps, env.clone(), Some(&gauge), timestamp)?;
+    let (_, total_weight) = weight_at_week_start(deps, env, None, timestamp)?;
+
+    let relative_weight = if total_weight.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(weight, total_weight)
+    };
+
+    Ok(GaugeWeightResponse {
+        week_timestamp,
+        weight,
+        relative_weight,
+    })
+}
+
+pub fn query_total_weight(
+    deps: Deps,
+    env: Env,
+    timestamp: Option<u64>,
+) -> StdResult<TotalWeightResponse> {
+    let (week_timestamp, weight) = weight_at_week_start(deps, env, None, timestamp)?;
+
+    Ok(TotalWeightResponse {
+        week_timestamp,
+        weight,
+    })
+}
+
+pub fn query_user_votes(deps: Deps, address: String) -> StdResult<UserVotesResponse> {
+    let user = deps.api.addr_validate(&address)?;
+
+    let votes = read_user_votes(deps.storage, &user)?
+        .into_iter()
+        .map(|(gauge, user_vote)| UserVoteResponse {
+            gauge: gauge.to_string(),
+            weight_bps: user_vote.weight_bps,
+            timestamp: user_vote.timestamp,
+        })
+        .collect();
+
+    Ok(UserVotesResponse {
+        power_used_bps: USER_POWER_USED
+            .may_load(deps.storage, &user)?
+            .unwrap_or_default(),
+        votes,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
@@ -0,0 +1,32 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("There is no pending ownership proposal")]
+    NoPendingOwner {},
+
+    #[error("Gauge already exists")]
+    GaugeAlreadyExists {},
+
+    #[error("Gauge does not exist")]
+    GaugeNotFound {},
+
+    #[error("Weight must be at most {max_weight_bps} basis points")]
+    InvalidWeight { max_weight_bps: u16 },
+
+    #[error("Used too much voting power")]
+    UsedTooMuchPower {},
+
+    #[error("No active locks to vote with")]
+    NoActiveLocks {},
+
+    #[error("Cannot vote for the gauge again until {next_vote_time}")]
+    VoteTooOften { next_vote_time: u64 },
+}
@@ -0,0 +1,111 @@
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
+};
+use cw900::ve_token::{
+    ConfigResponse as VEConfigResponse, DecayModel, LockResponse, QueryMsg as VEQueryMsg,
+    StakerLocksResponse,
+};
+
+use std::collections::HashMap;
+
+pub const SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60;
+pub const MAX_WEEKS: u64 = 52;
+pub const VOTING_POWER_CONSTANT_DIVISOR: u64 = MAX_WEEKS * SECONDS_PER_WEEK;
+
+/// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
+/// this uses our CustomQuerier.
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let custom_querier: WasmMockQuerier =
+        WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
+
+    OwnedDeps {
+        api: MockApi::default(),
+        storage: MockStorage::default(),
+        querier: custom_querier,
+    }
+}
+
+pub struct WasmMockQuerier {
+    base: MockQuerier<Empty>,
+    ve_querier: VEQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct VEQuerier {
+    // locks of each staker
+    locks: HashMap<String, Vec<LockResponse>>,
+}
+
+impl Querier for WasmMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        self.handle_query(&request)
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
+        match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => match from_binary(msg) {
+                Ok(VEQueryMsg::Config {}) => {
+                    let res = VEConfigResponse {
+                        owner: "creator".to_string(),
+                        pending_owner: None,
+                        cw20_address: Some("voting_token".to_string()),
+                        penalty_recipient: None,
+                        early_withdraw_penalty_rate: Decimal::zero(),
+                        seconds_per_week: SECONDS_PER_WEEK,
+                        max_weeks: MAX_WEEKS,
+                        voting_power_constant_divisor: VOTING_POWER_CONSTANT_DIVISOR,
+                        decay_model: DecayModel::LinearUnlock,
+                        tokenless_production: Decimal::percent(40),
+                        gauge_controller: Some(MOCK_CONTRACT_ADDR.to_string()),
+                        fee_distributor: None,
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                }
+                Ok(VEQueryMsg::StakerLocks { address, .. }) => {
+                    let res = StakerLocksResponse {
+                        locks: self
+                            .ve_querier
+                            .locks
+                            .get(&address)
+                            .cloned()
+                            .unwrap_or_default(),
+                    };
+                    SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
+            _ => self.base.handle_query(request),
+        }
+    }
+}
+
+impl WasmMockQuerier {
+    pub fn new(base: MockQuerier<Empty>) -> Self {
+        WasmMockQuerier {
+            base,
+            ve_querier: VEQuerier::default(),
+        }
+    }
+
+    // configure the locks of a staker
+    pub fn with_locks(&mut self, address: &str, locks: &[LockResponse]) {
+        self.ve_querier
+            .locks
+            .insert(address.to_string(), locks.to_vec());
+    }
+}
@@ -0,0 +1,29 @@
+use cosmwasm_std::{to_binary, Addr, QuerierWrapper, QueryRequest, StdResult, WasmQuery};
+
+use cw900::ve_token::{
+    ConfigResponse as VEConfigResponse, LockResponse, QueryMsg as VEQueryMessage,
+    StakerLocksResponse,
+};
+
+pub fn query_ve_config(querier: &QuerierWrapper, ve_addr: &Addr) -> StdResult<VEConfigResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: ve_addr.to_string(),
+        msg: to_binary(&VEQueryMessage::Config {})?,
+    }))
+}
+
+pub fn query_staker_locks(
+    querier: &QuerierWrapper,
+    ve_addr: &Addr,
+    address: &Addr,
+) -> StdResult<Vec<LockResponse>> {
+    let res: StakerLocksResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: ve_addr.to_string(),
+        msg: to_binary(&VEQueryMessage::StakerLocks {
+            address: address.to_string(),
+            timestamp: None,
+        })?,
+    }))?;
+
+    Ok(res.locks)
+}
@@ -0,0 +1,156 @@
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cw900::curve::QuadraticEquationCoefficients;
+use cw_storage_plus::{Bound, Item, Map, SnapshotItem, SnapshotMap, U64Key};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Gauges which the owner has registered
+pub const GAUGES: Map<&Addr, bool> = Map::new("gauges");
+
+/// Weight curve of each gauge
+pub const GAUGE_WEIGHTS: SnapshotMap<&Addr, Weight> = SnapshotMap::new(
+    "gauge_weights",
+    "gauge_weights__checkpoint",
+    "gauge_weights__changelog",
+    cw_storage_plus::Strategy::EveryBlock,
+);
+
+/// Sum of the weight curves of all gauges
+pub const TOTAL_WEIGHT: SnapshotItem<Weight> = SnapshotItem::new(
+    "total_weight",
+    "total_weight__checkpoint",
+    "total_weight__changelog",
+    cw_storage_plus::Strategy::EveryBlock,
+);
+
+/// Coefficient changes scheduled for each gauge, keyed by gauge and the end lock time of the votes
+pub const GAUGE_COEFFICIENT_CHANGES: Map<(&Addr, U64Key), QuadraticEquationCoefficients> =
+    Map::new("gauge_coefficient_changes");
+
+/// Coefficient changes scheduled for the total weight, keyed by the end lock time of the votes
+pub const TOTAL_COEFFICIENT_CHANGES: Map<U64Key, QuadraticEquationCoefficients> =
+    Map::new("total_coefficient_changes");
+
+/// Each user's vote for each gauge, keyed by user and gauge
+pub const USER_VOTES: Map<(&Addr, &Addr), UserVote> = Map::new("user_votes");
+
+/// Time of each user's last vote for each gauge, keyed by user and gauge.
+/// Kept when a vote is removed so that removing a vote doesn't reset the vote delay.
+pub const LAST_USER_VOTE: Map<(&Addr, &Addr), u64> = Map::new("last_user_vote");
+
+/// Basis points of voting power each user has allocated across all gauges
+pub const USER_POWER_USED: Map<&Addr, u16> = Map::new("user_power_used");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub ve_token: Addr,
+}
+
+/// A weight curve, up to date as of timestamp.
+/// Coefficient changes scheduled after timestamp haven't been applied yet.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Weight {
+    pub coefficients: QuadraticEquationCoefficients,
+    pub timestamp: u64,
+}
+
+/// The part of a vote which follows a single lock of the voter
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteSlope {
+    pub end_lock_time: u64,
+    pub coefficients: QuadraticEquationCoefficients,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserVote {
+    pub weight_bps: u16,
+    pub timestamp: u64,
+    pub slopes: Vec<VoteSlope>,
+}
+
+/// Apply the coefficient changes scheduled between weight.timestamp and timestamp to weight.
+/// Pass a gauge to apply the gauge's changes, or None to apply the changes to the total weight.
+pub fn apply_pending_coefficient_changes(
+    storage: &dyn Storage,
+    gauge: Option<&Addr>,
+    weight: &mut Weight,
+    timestamp: u64,
+    seconds_per_week: u64,
+) -> StdResult<()> {
+    // Go to the week after the weight's timestamp
+    // because all weeks at or before it have already been processed
+    let mut week_iterator_timestamp =
+        weight.timestamp / seconds_per_week * seconds_per_week + seconds_per_week;
+
+    while week_iterator_timestamp <= timestamp {
+        let coefficient_changes = match gauge {
+            Some(gauge) => GAUGE_COEFFICIENT_CHANGES
+                .may_load(storage, (gauge, U64Key::from(week_iterator_timestamp)))?,
+            None => TOTAL_COEFFICIENT_CHANGES
+                .may_load(storage, U64Key::from(week_iterator_timestamp))?,
+        }
+        .unwrap_or_default();
+
+        weight.coefficients -= coefficient_changes;
+
+        week_iterator_timestamp += seconds_per_week;
+    }
+
+    if weight.timestamp < timestamp {
+        weight.timestamp = timestamp;
+    }
+
+    Ok(())
+}
+
+/// Load the weight curve of a gauge, or of the total if no gauge is given,
+/// as of the start of the given timestamp.
+pub fn load_weight_at_timestamp(
+    storage: &dyn Storage,
+    gauge: Option<&Addr>,
+    timestamp: u64,
+    seconds_per_week: u64,
+) -> StdResult<Weight> {
+    let mut weight = match gauge {
+        Some(gauge) => GAUGE_WEIGHTS.may_load_at_height(storage, gauge, timestamp)?,
+        None => TOTAL_WEIGHT.may_load_at_height(storage, timestamp)?,
+    }
+    .unwrap_or_default();
+
+    apply_pending_coefficient_changes(storage, gauge, &mut weight, timestamp, seconds_per_week)?;
+
+    Ok(weight)
+}
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_gauges(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_bytes()));
+
+    GAUGES
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|k| Ok(Addr::unchecked(String::from_utf8(k)?)))
+        .collect()
+}
+
+/// Load all of a user's votes, along with the gauge each vote is for
+pub fn read_user_votes(storage: &dyn Storage, user: &Addr) -> StdResult<Vec<(Addr, UserVote)>> {
+    USER_VOTES
+        .prefix(user)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, user_vote) = item?;
+            Ok((Addr::unchecked(String::from_utf8(k)?), user_vote))
+        })
+        .collect()
+}
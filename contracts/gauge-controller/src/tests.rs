@@ -0,0 +1,536 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use crate::mock_querier::{mock_dependencies, SECONDS_PER_WEEK};
+
+use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::{from_binary, Decimal, Deps, DepsMut, Env, Timestamp, Uint128};
+use cw900::gauge_controller::{
+    ConfigResponse, ExecuteMsg, GaugeWeightResponse, GaugesResponse, InstantiateMsg, QueryMsg,
+    TotalWeightResponse, UserVoteResponse, UserVotesResponse, WEIGHT_VOTE_DELAY,
+};
+use cw900::ve_token::LockResponse;
+
+const VE_TOKEN: &str = "ve_token";
+const TEST_CREATOR: &str = "creator";
+const TEST_VOTER: &str = "voter1";
+const TEST_VOTER_2: &str = "voter2";
+const GAUGE_1: &str = "gauge1";
+const GAUGE_2: &str = "gauge2";
+
+fn mock_env_time(time: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(time);
+    env
+}
+
+fn increase_env_time(env: &mut Env, increase_time: u64) {
+    env.block.time = Timestamp::from_seconds(env.block.time.seconds() + increase_time);
+}
+
+fn mock_instantiate(deps: DepsMut, env: Env) {
+    let msg = InstantiateMsg {
+        ve_token: VE_TOKEN.to_string(),
+    };
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let _res =
+        instantiate(deps, env, info, msg).expect("contract successfully executes instantiateMsg");
+}
+
+fn mock_add_gauge(deps: DepsMut, env: Env, gauge: &str) {
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::AddGauge {
+        gauge: gauge.to_string(),
+    };
+    let _res = execute(deps, env, info, msg).expect("contract successfully executes AddGauge");
+}
+
+fn mock_vote(
+    deps: DepsMut,
+    env: Env,
+    voter: &str,
+    gauge: &str,
+    weight_bps: u16,
+) -> Result<(), ContractError> {
+    let info = mock_info(voter, &[]);
+    let msg = ExecuteMsg::VoteForGaugeWeight {
+        gauge: gauge.to_string(),
+        weight_bps,
+    };
+    execute(deps, env, info, msg).map(|_| ())
+}
+
+fn mock_lock(deposited_amount: u128, start_lock_time: u64, end_lock_time: u64) -> LockResponse {
+    LockResponse {
+        lock_id: 0,
+        deposited_amount: Uint128::from(deposited_amount),
+        locked_amount: Uint128::from(deposited_amount),
+        balance: Uint128::zero(),
+        start_lock_time,
+        end_lock_time,
+    }
+}
+
+fn query_gauge_weight(deps: Deps, env: Env, gauge: &str) -> GaugeWeightResponse {
+    from_binary(
+        &query(
+            deps,
+            env,
+            QueryMsg::GaugeWeight {
+                gauge: gauge.to_string(),
+                timestamp: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn query_total_weight(deps: Deps, env: Env) -> TotalWeightResponse {
+    from_binary(&query(deps, env, QueryMsg::TotalWeight { timestamp: None }).unwrap()).unwrap()
+}
+
+fn query_user_votes(deps: Deps, env: Env, address: &str) -> UserVotesResponse {
+    from_binary(
+        &query(
+            deps,
+            env,
+            QueryMsg::UserVotes {
+                address: address.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn assert_approx_eq(a: Decimal, b: Decimal) {
+    let diff = if a > b { a - b } else { b - a };
+    assert!(
+        diff < Decimal::from_ratio(1u64, 1_000_000u64),
+        "{} is not approximately {}",
+        a,
+        b
+    );
+}
+
+#[test]
+fn proper_initialization() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        ConfigResponse {
+            owner: TEST_CREATOR.to_string(),
+            pending_owner: None,
+            ve_token: VE_TOKEN.to_string(),
+        }
+    );
+}
+
+#[test]
+fn transfer_ownership() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+
+    // Only the owner can propose a new owner
+    let msg = ExecuteMsg::ProposeNewOwner {
+        new_owner: TEST_VOTER.to_string(),
+    };
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    ) {
+        Err(ContractError::NoPendingOwner {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // The owner doesn't change until the proposal is accepted
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.owner, TEST_CREATOR.to_string());
+    assert_eq!(res.pending_owner, Some(TEST_VOTER.to_string()));
+
+    // A proposal can be canceled
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    ) {
+        Err(ContractError::NoPendingOwner {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only the proposed owner can accept the proposal
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    match execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    ) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.owner, TEST_VOTER.to_string());
+    assert_eq!(res.pending_owner, None);
+}
+
+#[test]
+fn add_gauge() {
+    let env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+
+    // Only the owner can add gauges
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::AddGauge {
+        gauge: GAUGE_1.to_string(),
+    };
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_2);
+
+    // Gauges can't be added twice
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::AddGauge {
+        gauge: GAUGE_1.to_string(),
+    };
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::GaugeAlreadyExists {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: GaugesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Gauges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.gauges, vec![GAUGE_1.to_string(), GAUGE_2.to_string()]);
+
+    let res: GaugesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Gauges {
+                start_after: Some(GAUGE_1.to_string()),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.gauges, vec![GAUGE_2.to_string()]);
+}
+
+#[test]
+fn vote_validation() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_2);
+
+    // Voting for an unregistered gauge fails
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, "gauge3", 5000) {
+        Err(ContractError::GaugeNotFound {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Voting with more than all of the voting power fails
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 10001) {
+        Err(ContractError::InvalidWeight {
+            max_weight_bps: 10000,
+        }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Voting without any lock fails
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 5000) {
+        Err(ContractError::NoActiveLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Voting with only expired locks fails
+    deps.querier.with_locks(
+        TEST_VOTER,
+        &[mock_lock(1_000_000_000_000, 0, SECONDS_PER_WEEK)],
+    );
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 5000) {
+        Err(ContractError::NoActiveLocks {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_locks(
+        TEST_VOTER,
+        &[mock_lock(
+            1_000_000_000_000,
+            SECONDS_PER_WEEK,
+            10 * SECONDS_PER_WEEK,
+        )],
+    );
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 6000).unwrap();
+
+    // Allocating more than the remaining voting power fails
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_2, 4001) {
+        Err(ContractError::UsedTooMuchPower {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Voting for the same gauge again has to wait for the vote delay
+    let vote_time = env.block.time.seconds();
+    increase_env_time(&mut env, WEIGHT_VOTE_DELAY - 1);
+    match mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 5000) {
+        Err(ContractError::VoteTooOften { next_vote_time }) => {
+            assert_eq!(next_vote_time, vote_time + WEIGHT_VOTE_DELAY)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Lowering the existing allocation frees up voting power
+    increase_env_time(&mut env, 1);
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 5000).unwrap();
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_2, 5000).unwrap();
+
+    let res = query_user_votes(deps.as_ref(), env.clone(), TEST_VOTER);
+    assert_eq!(
+        res,
+        UserVotesResponse {
+            power_used_bps: 10000,
+            votes: vec![
+                UserVoteResponse {
+                    gauge: GAUGE_1.to_string(),
+                    weight_bps: 5000,
+                    timestamp: env.block.time.seconds(),
+                },
+                UserVoteResponse {
+                    gauge: GAUGE_2.to_string(),
+                    weight_bps: 5000,
+                    timestamp: env.block.time.seconds(),
+                },
+            ]
+        }
+    );
+
+    // Voting zero removes the vote
+    increase_env_time(&mut env, WEIGHT_VOTE_DELAY);
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_2, 0).unwrap();
+
+    let res = query_user_votes(deps.as_ref(), env.clone(), TEST_VOTER);
+    assert_eq!(res.power_used_bps, 5000);
+    assert_eq!(res.votes.len(), 1);
+    assert_eq!(res.votes[0].gauge, GAUGE_1.to_string());
+
+    // Removing a vote doesn't reset the vote delay
+    increase_env_time(&mut env, 1);
+    match mock_vote(deps.as_mut(), env, TEST_VOTER, GAUGE_2, 5000) {
+        Err(ContractError::VoteTooOften { .. }) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn gauge_weights() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_2);
+
+    let end_lock_time = 11 * SECONDS_PER_WEEK;
+    deps.querier.with_locks(
+        TEST_VOTER,
+        &[mock_lock(
+            1_000_000_000_000,
+            SECONDS_PER_WEEK,
+            end_lock_time,
+        )],
+    );
+    deps.querier.with_locks(
+        TEST_VOTER_2,
+        &[mock_lock(
+            1_000_000_000_000,
+            SECONDS_PER_WEEK,
+            end_lock_time,
+        )],
+    );
+
+    // Vote in the middle of the week
+    increase_env_time(&mut env, 3 * 24 * 60 * 60);
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 6000).unwrap();
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_2, 4000).unwrap();
+
+    // Votes don't affect the current week
+    let res = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    assert_eq!(
+        res,
+        GaugeWeightResponse {
+            week_timestamp: SECONDS_PER_WEEK,
+            weight: Uint128::zero(),
+            relative_weight: Decimal::zero(),
+        }
+    );
+
+    // Votes take effect from the next week
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    let res_2 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_2);
+    let total = query_total_weight(deps.as_ref(), env.clone());
+    assert_eq!(res_1.week_timestamp, 2 * SECONDS_PER_WEEK);
+    assert_eq!(total.week_timestamp, 2 * SECONDS_PER_WEEK);
+    assert!(!res_1.weight.is_zero());
+    assert!(res_1.weight + res_2.weight <= total.weight);
+    assert_approx_eq(res_1.relative_weight, Decimal::percent(60));
+    assert_approx_eq(res_2.relative_weight, Decimal::percent(40));
+
+    // Weights decay over time
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let next_res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    assert!(next_res_1.weight < res_1.weight);
+    assert_approx_eq(next_res_1.relative_weight, Decimal::percent(60));
+
+    // A second voter moves the relative weights
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER_2, GAUGE_2, 10000).unwrap();
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    let res_2 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_2);
+    assert_approx_eq(res_1.relative_weight, Decimal::percent(30));
+    assert_approx_eq(res_2.relative_weight, Decimal::percent(70));
+
+    // Past weeks can still be queried
+    let res: GaugeWeightResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::GaugeWeight {
+                gauge: GAUGE_1.to_string(),
+                timestamp: Some(2 * SECONDS_PER_WEEK + 1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_approx_eq(res.relative_weight, Decimal::percent(60));
+
+    // Weights drop to zero once the locks expire
+    env.block.time = Timestamp::from_seconds(end_lock_time);
+    let res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    let total = query_total_weight(deps.as_ref(), env.clone());
+    assert_eq!(res_1.weight, Uint128::zero());
+    assert_eq!(res_1.relative_weight, Decimal::zero());
+    assert_eq!(total.weight, Uint128::zero());
+
+    // Re-voting after expiry keeps the stored weights consistent
+    deps.querier.with_locks(
+        TEST_VOTER,
+        &[mock_lock(
+            1_000_000_000_000,
+            end_lock_time,
+            end_lock_time + 10 * SECONDS_PER_WEEK,
+        )],
+    );
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 6000).unwrap();
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    let res_2 = query_gauge_weight(deps.as_ref(), env, GAUGE_2);
+    assert_eq!(res_1.relative_weight, Decimal::one());
+    assert_eq!(res_2.weight, Uint128::zero());
+}
+
+#[test]
+fn revote_replaces_previous_vote() {
+    let mut env = mock_env_time(SECONDS_PER_WEEK);
+    let mut deps = mock_dependencies(&[]);
+
+    mock_instantiate(deps.as_mut(), env.clone());
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_1);
+    mock_add_gauge(deps.as_mut(), env.clone(), GAUGE_2);
+
+    deps.querier.with_locks(
+        TEST_VOTER,
+        &[mock_lock(
+            1_000_000_000_000,
+            SECONDS_PER_WEEK,
+            11 * SECONDS_PER_WEEK,
+        )],
+    );
+
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 10000).unwrap();
+    increase_env_time(&mut env, WEIGHT_VOTE_DELAY);
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_1, 0).unwrap();
+    mock_vote(deps.as_mut(), env.clone(), TEST_VOTER, GAUGE_2, 10000).unwrap();
+
+    increase_env_time(&mut env, SECONDS_PER_WEEK);
+    let res_1 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_1);
+    let res_2 = query_gauge_weight(deps.as_ref(), env.clone(), GAUGE_2);
+    let total = query_total_weight(deps.as_ref(), env);
+    assert_eq!(res_1.weight, Uint128::zero());
+    assert_eq!(res_2.weight, total.weight);
+    assert_eq!(res_2.relative_weight, Decimal::one());
+}
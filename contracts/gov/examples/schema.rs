@@ -3,7 +3,8 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use cw900::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollResponse, QueryMsg, StateResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, ExecutionResultsResponse, InstantiateMsg,
+    PollResponse, QueryMsg, StateResponse,
 };
 
 fn main() {
@@ -19,4 +20,5 @@ fn main() {
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(PollResponse), &out_dir);
+    export_schema(&schema_for!(ExecutionResultsResponse), &out_dir);
 }
@@ -7,26 +7,28 @@ use crate::querier::{
 };
 use crate::state::{
     config_read, config_store, poll_indexer_store, poll_read, poll_store, poll_voter_read,
-    poll_voter_store, read_poll_voters, read_polls, state_read, state_store, Config, ExecuteData,
-    Poll, State,
+    poll_voter_store, read_poll_voters, read_polls, state_read, state_store, Config, ExecuteAction,
+    ExecuteData, ExecutionResult, Poll, State,
 };
 
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut,
-    Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Api, BankMsg, Binary, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError,
+    StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 use cw900::common::OrderBy;
 use cw900::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PollExecuteMsg,
-    PollResponse, PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo,
-    VotersResponse, VotersResponseItem,
+    ConfigResponse, Cw20HookMsg, DepositDestination, ExecuteMsg, ExecutionResultResponse,
+    ExecutionResultsResponse, InstantiateMsg, MigrateMsg, PollAction, PollExecuteMsg, PollResponse,
+    PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo, VotersResponse,
+    VotersResponseItem,
 };
+use cw900::sweep::{default_max_spread, sweep_msg, validate_max_spread};
 
-use terraswap::asset::{Asset, AssetInfo, PairInfo};
-use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
-use terraswap::querier::{query_balance, query_pair_info};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::querier::query_balance;
 
 const MIN_TITLE_LENGTH: usize = 4;
 const MAX_TITLE_LENGTH: usize = 64;
@@ -49,6 +51,7 @@ pub fn instantiate(
         glow_token: CanonicalAddr::from(vec![]),
         ve_token: CanonicalAddr::from(vec![]),
         terraswap_factory: CanonicalAddr::from(vec![]),
+        terraswap_router: None,
         owner: deps.api.addr_canonicalize(info.sender.as_str())?,
         quorum: msg.quorum,
         threshold: msg.threshold,
@@ -57,6 +60,10 @@ pub fn instantiate(
         expiration_period: msg.expiration_period,
         proposal_deposit: msg.proposal_deposit,
         snapshot_period: msg.snapshot_period,
+        deposit_destination: None,
+        fee_distributor: None,
+        veto_threshold: None,
+        max_spread: default_max_spread(),
     };
 
     let state = State {
@@ -64,6 +71,7 @@ pub fn instantiate(
         poll_count: 0,
         total_share: Uint128::zero(),
         total_deposit: Uint128::zero(),
+        total_slashed: Uint128::zero(),
     };
 
     config_store(deps.storage).save(&config)?;
@@ -86,7 +94,20 @@ pub fn execute(
             ve_token,
             terraswap_factory,
         } => register_contracts(deps, glow_token, ve_token, terraswap_factory),
-        ExecuteMsg::Sweep { denom } => sweep(deps, env, denom),
+        ExecuteMsg::Sweep {
+            denom,
+            max_spread,
+            belief_price,
+            intermediate_asset,
+        } => sweep(
+            deps,
+            env,
+            info,
+            denom,
+            max_spread,
+            belief_price,
+            intermediate_asset,
+        ),
         ExecuteMsg::UpdateConfig {
             owner,
             quorum,
@@ -96,6 +117,12 @@ pub fn execute(
             expiration_period,
             proposal_deposit,
             snapshot_period,
+            terraswap_router,
+            deposit_destination,
+            fee_distributor,
+            veto_threshold,
+            remove_veto_threshold,
+            max_spread,
         } => update_config(
             deps,
             info,
@@ -107,6 +134,12 @@ pub fn execute(
             expiration_period,
             proposal_deposit,
             snapshot_period,
+            terraswap_router,
+            deposit_destination,
+            fee_distributor,
+            veto_threshold,
+            remove_veto_threshold,
+            max_spread,
         ),
         ExecuteMsg::CastVote { poll_id, vote } => cast_vote(deps, env, info, poll_id, vote),
         ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
@@ -169,23 +202,28 @@ pub fn register_contracts(
 /// Sweep
 /// Anyone can execute sweep function to swap
 /// asset native denom => GLOW token
-pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, ContractError> {
+/// Only the owner can sweep without a belief_price
+pub fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    max_spread: Option<Decimal>,
+    belief_price: Option<Decimal>,
+    intermediate_asset: Option<AssetInfo>,
+) -> Result<Response, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
+    // Without a belief_price the spread is measured against the pool,
+    // which anyone can move in the same block before sweeping
+    if belief_price.is_none() && config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::BeliefPriceRequired {});
+    }
     let glow_token = deps.api.addr_humanize(&config.glow_token)?;
     let terraswap_factory_addr = deps.api.addr_humanize(&config.terraswap_factory)?;
-
-    let pair_info: PairInfo = query_pair_info(
-        &deps.querier,
-        terraswap_factory_addr,
-        &[
-            AssetInfo::NativeToken {
-                denom: denom.to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: glow_token.to_string(),
-            },
-        ],
-    )?;
+    let terraswap_router_addr = config
+        .terraswap_router
+        .map(|terraswap_router| deps.api.addr_humanize(&terraswap_router))
+        .transpose()?;
 
     let amount = query_balance(&deps.querier, env.contract.address, denom.to_string())?;
     let swap_asset = Asset {
@@ -198,22 +236,20 @@ pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, Contrac
     // deduct tax first
     let amount = (swap_asset.deduct_tax(&deps.querier)?).amount;
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: pair_info.contract_addr,
-            msg: to_binary(&TerraswapExecuteMsg::Swap {
-                offer_asset: Asset {
-                    amount,
-                    ..swap_asset
-                },
-                max_spread: None,
-                belief_price: None,
-                to: None,
-            })?,
-            funds: vec![Coin {
+        .add_message(sweep_msg(
+            &deps.querier,
+            &terraswap_factory_addr,
+            terraswap_router_addr.as_ref(),
+            &glow_token,
+            Coin {
                 denom: denom.to_string(),
                 amount,
-            }],
-        }))
+            },
+            max_spread,
+            config.max_spread,
+            belief_price,
+            intermediate_asset,
+        )?)
         .add_attributes(vec![
             attr("action", "sweep"),
             attr(
@@ -235,6 +271,12 @@ pub fn update_config(
     expiration_period: Option<u64>,
     proposal_deposit: Option<Uint128>,
     snapshot_period: Option<u64>,
+    terraswap_router: Option<String>,
+    deposit_destination: Option<DepositDestination>,
+    fee_distributor: Option<String>,
+    veto_threshold: Option<Decimal>,
+    remove_veto_threshold: Option<bool>,
+    max_spread: Option<Decimal>,
 ) -> Result<Response, ContractError> {
     let api = deps.api;
     config_store(deps.storage).update(|mut config| {
@@ -251,6 +293,7 @@ pub fn update_config(
         }
 
         if let Some(threshold) = threshold {
+            validate_threshold(threshold)?;
             config.threshold = threshold;
         }
 
@@ -274,6 +317,47 @@ pub fn update_config(
             config.snapshot_period = period;
         }
 
+        if let Some(terraswap_router) = terraswap_router {
+            config.terraswap_router = Some(api.addr_canonicalize(&terraswap_router)?);
+        }
+
+        if let Some(deposit_destination) = deposit_destination {
+            config.deposit_destination = Some(deposit_destination);
+        }
+
+        if let Some(fee_distributor) = fee_distributor {
+            config.fee_distributor = Some(api.addr_canonicalize(&fee_distributor)?);
+        }
+
+        if remove_veto_threshold == Some(true) {
+            if veto_threshold.is_some() {
+                return Err(ContractError::Std(StdError::generic_err(
+                    "veto_threshold can't be set and removed at once",
+                )));
+            }
+            config.veto_threshold = None;
+        }
+
+        if let Some(veto_threshold) = veto_threshold {
+            config.veto_threshold = Some(veto_threshold);
+        }
+
+        // Also revalidate the current veto threshold against a new threshold
+        if let Some(veto_threshold) = config.veto_threshold {
+            validate_veto_threshold(veto_threshold, config.threshold)?;
+        }
+
+        if let Some(max_spread) = max_spread {
+            validate_max_spread(max_spread)?;
+            config.max_spread = max_spread;
+        }
+
+        if config.deposit_destination == Some(DepositDestination::FeeDistributor)
+            && config.fee_distributor.is_none()
+        {
+            return Err(ContractError::FeeDistributorNotSet {});
+        }
+
         Ok(config)
     })?;
 
@@ -337,6 +421,161 @@ fn validate_threshold(threshold: Decimal) -> StdResult<()> {
     }
 }
 
+/// validate_veto_threshold returns an error if the veto threshold is invalid.
+/// A poll with more than 1 - threshold of no votes can't pass, so a lower
+/// veto threshold would slash the deposit of polls which pass.
+fn validate_veto_threshold(veto_threshold: Decimal, threshold: Decimal) -> StdResult<()> {
+    if veto_threshold > Decimal::one() || veto_threshold < Decimal::one() - threshold {
+        Err(StdError::generic_err(
+            "veto_threshold must be 1 - threshold to 1",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// canonicalize_action validates a poll action and canonicalizes its addresses
+fn canonicalize_action(api: &dyn Api, action: PollAction) -> Result<ExecuteAction, ContractError> {
+    let has_zero_coin = |coins: &[Coin]| coins.iter().any(|coin| coin.amount.is_zero());
+
+    Ok(match action {
+        PollAction::WasmExecute {
+            contract,
+            msg,
+            funds,
+        } => {
+            if has_zero_coin(&funds) {
+                return Err(ContractError::InvalidPollAction {});
+            }
+            ExecuteAction::WasmExecute {
+                contract: api.addr_canonicalize(&contract)?,
+                msg,
+                funds,
+            }
+        }
+        PollAction::WasmMigrate {
+            contract,
+            new_code_id,
+            msg,
+        } => ExecuteAction::WasmMigrate {
+            contract: api.addr_canonicalize(&contract)?,
+            new_code_id,
+            msg,
+        },
+        PollAction::UpdateAdmin { contract, admin } => ExecuteAction::UpdateAdmin {
+            contract: api.addr_canonicalize(&contract)?,
+            admin: api.addr_canonicalize(&admin)?,
+        },
+        PollAction::BankSend { to_address, amount } => {
+            if amount.is_empty() || has_zero_coin(&amount) {
+                return Err(ContractError::InvalidPollAction {});
+            }
+            ExecuteAction::BankSend {
+                to_address: api.addr_canonicalize(&to_address)?,
+                amount,
+            }
+        }
+        PollAction::Cw20Transfer {
+            token,
+            recipient,
+            amount,
+        } => {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidPollAction {});
+            }
+            ExecuteAction::Cw20Transfer {
+                token: api.addr_canonicalize(&token)?,
+                recipient: api.addr_canonicalize(&recipient)?,
+                amount,
+            }
+        }
+    })
+}
+
+fn humanize_action(api: &dyn Api, action: ExecuteAction) -> StdResult<PollAction> {
+    Ok(match action {
+        ExecuteAction::WasmExecute {
+            contract,
+            msg,
+            funds,
+        } => PollAction::WasmExecute {
+            contract: api.addr_humanize(&contract)?.to_string(),
+            msg,
+            funds,
+        },
+        ExecuteAction::WasmMigrate {
+            contract,
+            new_code_id,
+            msg,
+        } => PollAction::WasmMigrate {
+            contract: api.addr_humanize(&contract)?.to_string(),
+            new_code_id,
+            msg,
+        },
+        ExecuteAction::UpdateAdmin { contract, admin } => PollAction::UpdateAdmin {
+            contract: api.addr_humanize(&contract)?.to_string(),
+            admin: api.addr_humanize(&admin)?.to_string(),
+        },
+        ExecuteAction::BankSend { to_address, amount } => PollAction::BankSend {
+            to_address: api.addr_humanize(&to_address)?.to_string(),
+            amount,
+        },
+        ExecuteAction::Cw20Transfer {
+            token,
+            recipient,
+            amount,
+        } => PollAction::Cw20Transfer {
+            token: api.addr_humanize(&token)?.to_string(),
+            recipient: api.addr_humanize(&recipient)?.to_string(),
+            amount,
+        },
+    })
+}
+
+/// action_msg builds the message which executes a poll action
+fn action_msg(api: &dyn Api, action: ExecuteAction) -> StdResult<CosmosMsg> {
+    Ok(match action {
+        ExecuteAction::WasmExecute {
+            contract,
+            msg,
+            funds,
+        } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&contract)?.to_string(),
+            msg,
+            funds,
+        }),
+        ExecuteAction::WasmMigrate {
+            contract,
+            new_code_id,
+            msg,
+        } => CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: api.addr_humanize(&contract)?.to_string(),
+            new_code_id,
+            msg,
+        }),
+        ExecuteAction::UpdateAdmin { contract, admin } => CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+            contract_addr: api.addr_humanize(&contract)?.to_string(),
+            admin: api.addr_humanize(&admin)?.to_string(),
+        }),
+        ExecuteAction::BankSend { to_address, amount } => CosmosMsg::Bank(BankMsg::Send {
+            to_address: api.addr_humanize(&to_address)?.to_string(),
+            amount,
+        }),
+        ExecuteAction::Cw20Transfer {
+            token,
+            recipient,
+            amount,
+        } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&token)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: api.addr_humanize(&recipient)?.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 /// create a new poll
 pub fn create_poll(
@@ -372,8 +611,7 @@ pub fn create_poll(
         for msgs in exe_msgs {
             let execute_data = ExecuteData {
                 order: msgs.order,
-                contract: deps.api.addr_canonicalize(&msgs.contract)?,
-                msg: msgs.msg,
+                action: canonicalize_action(deps.api, msgs.action)?,
             };
             data_list.push(execute_data)
         }
@@ -395,8 +633,10 @@ pub fn create_poll(
         status: PollStatus::InProgress,
         yes_votes: Uint128::zero(),
         no_votes: Uint128::zero(),
+        abstain_votes: Uint128::zero(),
         start_time: env.block.time.seconds(),
-        end_height: env.block.height + config.voting_period,
+        end_height: None,
+        end_time: env.block.time.seconds() + config.voting_period,
         title,
         description,
         link,
@@ -404,6 +644,7 @@ pub fn create_poll(
         deposit_amount,
         total_balance_at_end_poll: None,
         staked_amount: Some(staked_amount),
+        execution_results: vec![],
     };
 
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &new_poll)?;
@@ -422,7 +663,7 @@ pub fn create_poll(
                 .as_str(),
         ),
         ("poll_id", &poll_id.to_string()),
-        ("end_height", new_poll.end_height.to_string().as_str()),
+        ("end_time", new_poll.end_time.to_string().as_str()),
     ]))
 }
 
@@ -436,14 +677,15 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
         return Err(ContractError::PollNotInProgress {});
     }
 
-    if a_poll.end_height > env.block.height {
+    if a_poll.end_time > env.block.time.seconds() {
         return Err(ContractError::PollVotingPeriod {});
     }
 
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
+    let abstain = a_poll.abstain_votes.u128();
 
-    let tallied_weight = yes + no;
+    let tallied_weight = yes + no + abstain;
 
     let mut poll_status = PollStatus::Rejected;
     let mut rejected_reason = "";
@@ -464,12 +706,20 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
         )
     };
 
+    let mut refund = false;
     if tallied_weight == 0 || quorum < config.quorum {
         // Quorum: More than quorum of the total staked tokens at the end of the voting
         // period need to have participated in the vote.
         rejected_reason = "Quorum not reached";
+    } else if config
+        .veto_threshold
+        .is_some_and(|veto_threshold| Decimal::from_ratio(no, tallied_weight) > veto_threshold)
+    {
+        // Veto: More than veto_threshold of the tokens that participated in the vote
+        // voted against the proposal, which slashes the deposit.
+        rejected_reason = "Vetoed";
     } else {
-        if Decimal::from_ratio(yes, tallied_weight) > config.threshold {
+        if yes + no != 0 && Decimal::from_ratio(yes, yes + no) > config.threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
             poll_status = PollStatus::Passed;
@@ -478,16 +728,51 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Contr
             rejected_reason = "Threshold not reached";
         }
 
-        // Refunds deposit only when quorum is reached
-        if !a_poll.deposit_amount.is_zero() {
+        // Refunds deposit only when quorum is reached and the poll isn't vetoed
+        refund = true;
+    }
+
+    if !a_poll.deposit_amount.is_zero() {
+        let glow_token = deps.api.addr_humanize(&config.glow_token)?.to_string();
+
+        if refund {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.glow_token)?.to_string(),
+                contract_addr: glow_token,
                 funds: vec![],
                 msg: to_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: deps.api.addr_humanize(&a_poll.creator)?.to_string(),
                     amount: a_poll.deposit_amount,
                 })?,
             }))
+        } else {
+            // Deposits which are not refunded are slashed
+            state.total_slashed += a_poll.deposit_amount;
+
+            match config.deposit_destination {
+                Some(DepositDestination::Burn) => {
+                    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: glow_token,
+                        funds: vec![],
+                        msg: to_binary(&Cw20ExecuteMsg::Burn {
+                            amount: a_poll.deposit_amount,
+                        })?,
+                    }))
+                }
+                Some(DepositDestination::FeeDistributor) => {
+                    let fee_distributor = config
+                        .fee_distributor
+                        .ok_or(ContractError::FeeDistributorNotSet {})?;
+                    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: glow_token,
+                        funds: vec![],
+                        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                            recipient: deps.api.addr_humanize(&fee_distributor)?.to_string(),
+                            amount: a_poll.deposit_amount,
+                        })?,
+                    }))
+                }
+                None => {}
+            }
         }
     }
 
@@ -523,7 +808,7 @@ pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, C
         return Err(ContractError::PollNotPassed {});
     }
 
-    if a_poll.end_height + config.timelock_period > env.block.height {
+    if a_poll.end_time + config.timelock_period > env.block.time.seconds() {
         return Err(ContractError::TimelockNotExpired {});
     }
 
@@ -533,25 +818,27 @@ pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, C
     a_poll.status = PollStatus::Executed;
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    let mut messages: Vec<CosmosMsg> = vec![];
+    // Each message replies with its result, so a failing message doesn't revert the others
+    let mut messages: Vec<SubMsg> = vec![];
     if let Some(all_msgs) = a_poll.execute_data {
         let mut msgs = all_msgs;
         msgs.sort();
         for msg in msgs {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                msg: msg.msg,
-                funds: vec![],
-            }))
+            messages.push(SubMsg::reply_always(
+                action_msg(deps.api, msg.action)?,
+                poll_id,
+            ))
         }
     } else {
         return Err(ContractError::NoExecuteData {});
     }
 
-    Ok(Response::new().add_messages(messages).add_attributes(vec![
-        ("action", "execute_poll"),
-        ("poll_id", poll_id.to_string().as_str()),
-    ]))
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "execute_poll"),
+            ("poll_id", poll_id.to_string().as_str()),
+        ]))
 }
 
 /// ExpirePoll is used to make the poll as expired state for querying purpose
@@ -567,7 +854,7 @@ pub fn expire_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, Co
         return Err(ContractError::NoExecuteData {});
     }
 
-    if a_poll.end_height + config.expiration_period > env.block.height {
+    if a_poll.end_time + config.expiration_period > env.block.time.seconds() {
         return Err(ContractError::PollNotExpired {});
     }
 
@@ -598,18 +885,10 @@ pub fn cast_vote(
     }
 
     let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
-    if a_poll.status != PollStatus::InProgress || env.block.height > a_poll.end_height {
+    if a_poll.status != PollStatus::InProgress || env.block.time.seconds() > a_poll.end_time {
         return Err(ContractError::PollNotInProgress {});
     }
 
-    // Check the voter already has a vote on the poll
-    if poll_voter_read(deps.storage, poll_id)
-        .load(sender_address_raw.as_slice())
-        .is_ok()
-    {
-        return Err(ContractError::AlreadyVoted {});
-    }
-
     let amount = query_address_voting_balance_at_timestamp(
         &deps.querier,
         &deps.api.addr_humanize(&config.ve_token)?,
@@ -617,11 +896,23 @@ pub fn cast_vote(
         &info.sender,
     )?;
 
+    // Remove the previous vote from the tally when the voter changes their vote
+    if let Some(previous_vote) =
+        poll_voter_read(deps.storage, poll_id).may_load(sender_address_raw.as_slice())?
+    {
+        let tally = match previous_vote.vote {
+            VoteOption::Yes => &mut a_poll.yes_votes,
+            VoteOption::No => &mut a_poll.no_votes,
+            VoteOption::Abstain => &mut a_poll.abstain_votes,
+        };
+        *tally = tally.checked_sub(previous_vote.balance)?;
+    }
+
     // update tally info
-    if VoteOption::Yes == vote {
-        a_poll.yes_votes += amount;
-    } else {
-        a_poll.no_votes += amount;
+    match vote {
+        VoteOption::Yes => a_poll.yes_votes += amount,
+        VoteOption::No => a_poll.no_votes += amount,
+        VoteOption::Abstain => a_poll.abstain_votes += amount,
     }
 
     let vote_info = VoterInfo {
@@ -643,6 +934,49 @@ pub fn cast_vote(
     ]))
 }
 
+/// Records the result of a message of an executed poll. The replies arrive in execution order,
+/// with the poll id as reply id. The poll is marked as failed when any of its messages fails.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let poll_id = msg.id;
+    let mut a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+
+    let mut execute_data = a_poll
+        .execute_data
+        .clone()
+        .ok_or(ContractError::NoExecuteData {})?;
+    execute_data.sort();
+    let executed_msg = execute_data
+        .get(a_poll.execution_results.len())
+        .ok_or(ContractError::NoExecuteData {})?;
+
+    let error = match msg.result {
+        ContractResult::Ok(_) => None,
+        ContractResult::Err(err) => Some(err),
+    };
+
+    if error.is_some() && a_poll.status == PollStatus::Executed {
+        poll_indexer_store(deps.storage, &PollStatus::Executed).remove(&poll_id.to_be_bytes());
+        poll_indexer_store(deps.storage, &PollStatus::Failed)
+            .save(&poll_id.to_be_bytes(), &true)?;
+        a_poll.status = PollStatus::Failed;
+    }
+
+    let order = executed_msg.order;
+    let success = error.is_none();
+    a_poll
+        .execution_results
+        .push(ExecutionResult { order, error });
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "execute_poll_msg"),
+        ("poll_id", poll_id.to_string().as_str()),
+        ("order", order.to_string().as_str()),
+        ("success", success.to_string().as_str()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -673,6 +1007,9 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             limit,
             order_by,
         )?)?),
+        QueryMsg::ExecutionResults { poll_id } => {
+            Ok(to_binary(&query_execution_results(deps, poll_id)?)?)
+        }
     }
 }
 
@@ -685,6 +1022,11 @@ fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
             .api
             .addr_humanize(&config.terraswap_factory)?
             .to_string(),
+        terraswap_router: config
+            .terraswap_router
+            .map(|terraswap_router| deps.api.addr_humanize(&terraswap_router))
+            .transpose()?
+            .map(|terraswap_router| terraswap_router.to_string()),
         quorum: config.quorum,
         threshold: config.threshold,
         voting_period: config.voting_period,
@@ -692,6 +1034,14 @@ fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
         expiration_period: config.expiration_period,
         proposal_deposit: config.proposal_deposit,
         snapshot_period: config.snapshot_period,
+        deposit_destination: config.deposit_destination,
+        fee_distributor: config
+            .fee_distributor
+            .map(|fee_distributor| deps.api.addr_humanize(&fee_distributor))
+            .transpose()?
+            .map(|fee_distributor| fee_distributor.to_string()),
+        veto_threshold: config.veto_threshold,
+        max_spread: config.max_spread,
     })
 }
 
@@ -701,6 +1051,7 @@ fn query_state(deps: Deps) -> Result<StateResponse, ContractError> {
         poll_count: state.poll_count,
         total_share: state.total_share,
         total_deposit: state.total_deposit,
+        total_slashed: state.total_slashed,
     })
 }
 
@@ -719,6 +1070,7 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
         status: poll.status,
         start_time: poll.start_time,
         end_height: poll.end_height,
+        end_time: poll.end_time,
         title: poll.title,
         description: poll.description,
         link: poll.link,
@@ -727,8 +1079,7 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
             for msg in exe_msgs {
                 let execute_data = PollExecuteMsg {
                     order: msg.order,
-                    contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                    msg: msg.msg,
+                    action: humanize_action(deps.api, msg.action)?,
                 };
                 data_list.push(execute_data)
             }
@@ -738,6 +1089,7 @@ fn query_poll(deps: Deps, poll_id: u64) -> Result<PollResponse, ContractError> {
         },
         yes_votes: poll.yes_votes,
         no_votes: poll.no_votes,
+        abstain_votes: poll.abstain_votes,
         staked_amount: poll.staked_amount,
         total_balance_at_end_poll: poll.total_balance_at_end_poll,
     })
@@ -761,6 +1113,7 @@ fn query_polls(
                 status: poll.status.clone(),
                 start_time: poll.start_time,
                 end_height: poll.end_height,
+                end_time: poll.end_time,
                 title: poll.title.to_string(),
                 description: poll.description.to_string(),
                 link: poll.link.clone(),
@@ -771,8 +1124,7 @@ fn query_polls(
                     for msg in exe_msgs {
                         let execute_data = PollExecuteMsg {
                             order: msg.order,
-                            contract: deps.api.addr_humanize(&msg.contract)?.to_string(),
-                            msg: msg.msg,
+                            action: humanize_action(deps.api, msg.action)?,
                         };
                         data_list.push(execute_data)
                     }
@@ -782,6 +1134,7 @@ fn query_polls(
                 },
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
+                abstain_votes: poll.abstain_votes,
                 staked_amount: poll.staked_amount,
                 total_balance_at_end_poll: poll.total_balance_at_end_poll,
             })
@@ -793,6 +1146,33 @@ fn query_polls(
     })
 }
 
+fn query_execution_results(
+    deps: Deps,
+    poll_id: u64,
+) -> Result<ExecutionResultsResponse, ContractError> {
+    let poll: Poll = match poll_read(deps.storage).may_load(&poll_id.to_be_bytes())? {
+        Some(poll) => poll,
+        None => return Err(ContractError::PollNotFound {}),
+    };
+
+    let results = poll
+        .execution_results
+        .into_iter()
+        .map(|result| {
+            Ok(ExecutionResultResponse {
+                order: result.order,
+                success: result.error.is_none(),
+                error: result.error,
+            })
+        })
+        .collect::<StdResult<Vec<ExecutionResultResponse>>>()?;
+
+    Ok(ExecutionResultsResponse {
+        status: poll.status,
+        results,
+    })
+}
+
 fn query_voters(
     deps: Deps,
     poll_id: u64,
@@ -837,6 +1217,59 @@ fn query_voters(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    if msg.block_time.is_zero() {
+        return Err(StdError::generic_err("block_time must be positive"));
+    }
+
+    // Periods which aren't given are converted from blocks to seconds
+    let mut config: Config = config_read(deps.storage).load()?;
+    config.voting_period = msg
+        .voting_period
+        .unwrap_or_else(|| blocks_to_seconds(config.voting_period, msg.block_time));
+    config.timelock_period = msg
+        .timelock_period
+        .unwrap_or_else(|| blocks_to_seconds(config.timelock_period, msg.block_time));
+    config.expiration_period = msg
+        .expiration_period
+        .unwrap_or_else(|| blocks_to_seconds(config.expiration_period, msg.block_time));
+    config_store(deps.storage).save(&config)?;
+
+    // Only polls which can still be ended or executed depend on their end time
+    let mut poll_ids: Vec<Vec<u8>> = vec![];
+    for status in [PollStatus::InProgress, PollStatus::Passed] {
+        poll_ids.extend(
+            poll_indexer_store(deps.storage, &status)
+                .range(None, None, Order::Ascending)
+                .map(|item| item.map(|(k, _)| k))
+                .collect::<StdResult<Vec<Vec<u8>>>>()?,
+        );
+    }
+
+    // Estimate the end time of these polls from the blocks between their end height
+    // and the current height
+    let now = env.block.time.seconds();
+    for poll_id in poll_ids {
+        let mut poll: Poll = poll_read(deps.storage).load(&poll_id)?;
+        let end_height = match poll.end_height {
+            Some(end_height) if poll.end_time == 0 => end_height,
+            _ => continue,
+        };
+
+        poll.end_time = if end_height >= env.block.height {
+            now + blocks_to_seconds(end_height - env.block.height, msg.block_time)
+        } else {
+            now.saturating_sub(blocks_to_seconds(
+                env.block.height - end_height,
+                msg.block_time,
+            ))
+        };
+        poll_store(deps.storage).save(&poll.id.to_be_bytes(), &poll)?;
+    }
+
     Ok(Response::default())
 }
+
+fn blocks_to_seconds(blocks: u64, block_time: Decimal) -> u64 {
+    (Uint128::from(blocks) * block_time).u128() as u64
+}
@@ -57,15 +57,21 @@ pub enum ContractError {
     #[error("Cannot snapshot at this height")]
     SnapshotHeight {},
 
-    #[error("User has already voted")]
-    AlreadyVoted {},
+    #[error("Fee distributor is not set")]
+    FeeDistributorNotSet {},
+
+    #[error("Invalid poll action")]
+    InvalidPollAction {},
 
     #[error("Cannot make a text proposal to expired state")]
     NoExecuteData {},
 
-    #[error("Expire height has not been reached")]
+    #[error("Expire time has not been reached")]
     PollNotExpired {},
 
     #[error("Voting period has not expired")]
     PollVotingPeriod {},
+
+    #[error("Only the owner can sweep without a belief_price")]
+    BeliefPriceRequired {},
 }
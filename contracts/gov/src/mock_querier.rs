@@ -219,6 +219,9 @@ impl WasmMockQuerier {
                             deposited_amount: balance,
                             balance,
                             locked_amount: balance,
+                            delegated_balance: Uint128::zero(),
+                            delegate: None,
+                            voting_power: balance,
                         })
                         .unwrap(),
                     ))
@@ -17,7 +17,7 @@ pub fn query_address_voting_balance_at_timestamp(
         })?,
     }));
 
-    Ok(balance.map_or(Uint128::zero(), |s| s.balance))
+    Ok(balance.map_or(Uint128::zero(), |s| s.voting_power))
 }
 
 pub fn query_total_voting_balance_at_timestamp(
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{Binary, CanonicalAddr, Coin, Decimal, StdError, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
@@ -7,8 +7,10 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cw900::common::OrderBy;
-use cw900::gov::{PollStatus, VoterInfo};
+use cw900::gov::{DepositDestination, PollStatus, VoterInfo};
+use cw900::sweep::default_max_spread;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_STATE: &[u8] = b"state";
@@ -23,6 +25,7 @@ pub struct Config {
     pub glow_token: CanonicalAddr,
     pub ve_token: CanonicalAddr,
     pub terraswap_factory: CanonicalAddr,
+    pub terraswap_router: Option<CanonicalAddr>,
     pub quorum: Decimal,
     pub threshold: Decimal,
     pub voting_period: u64,
@@ -30,6 +33,13 @@ pub struct Config {
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub deposit_destination: Option<DepositDestination>,
+    pub fee_distributor: Option<CanonicalAddr>,
+    /// Share of no votes above which a poll is vetoed and its deposit slashed
+    pub veto_threshold: Option<Decimal>,
+    /// Maximum spread accepted by sweeps
+    #[serde(default = "default_max_spread")]
+    pub max_spread: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -38,12 +48,8 @@ pub struct State {
     pub poll_count: u64,
     pub total_share: Uint128,
     pub total_deposit: Uint128,
-}
-
-#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct TokenManager {
-    pub share: Uint128,                        // total staked balance
-    pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to weight voted
+    #[serde(default)]
+    pub total_slashed: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,8 +59,13 @@ pub struct Poll {
     pub status: PollStatus,
     pub yes_votes: Uint128,
     pub no_votes: Uint128,
+    #[serde(default)]
+    pub abstain_votes: Uint128,
     pub start_time: u64,
-    pub end_height: u64,
+    /// Only set for polls created before the migration to an end time
+    pub end_height: Option<u64>,
+    #[serde(default)]
+    pub end_time: u64,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
@@ -63,16 +74,84 @@ pub struct Poll {
     /// Total balance at the end poll
     pub total_balance_at_end_poll: Option<Uint128>,
     pub staked_amount: Option<Uint128>,
+    /// Results of the executed messages, in execution order
+    #[serde(default)]
+    pub execution_results: Vec<ExecutionResult>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(try_from = "ExecuteDataRecord")]
 pub struct ExecuteData {
     pub order: u64,
-    pub contract: CanonicalAddr,
-    pub msg: Binary,
+    pub action: ExecuteAction,
 }
 impl Eq for ExecuteData {}
 
+/// Execute data is read through a record which also accepts the contract and message
+/// of polls created before poll actions
+#[derive(Deserialize)]
+struct ExecuteDataRecord {
+    order: u64,
+    action: Option<ExecuteAction>,
+    contract: Option<CanonicalAddr>,
+    msg: Option<Binary>,
+}
+
+impl TryFrom<ExecuteDataRecord> for ExecuteData {
+    type Error = StdError;
+
+    fn try_from(record: ExecuteDataRecord) -> StdResult<Self> {
+        let action = match (record.action, record.contract, record.msg) {
+            (Some(action), _, _) => action,
+            (None, Some(contract), Some(msg)) => ExecuteAction::WasmExecute {
+                contract,
+                msg,
+                funds: vec![],
+            },
+            _ => return Err(StdError::parse_err("ExecuteData", "missing action")),
+        };
+
+        Ok(ExecuteData {
+            order: record.order,
+            action,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteAction {
+    WasmExecute {
+        contract: CanonicalAddr,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+    WasmMigrate {
+        contract: CanonicalAddr,
+        new_code_id: u64,
+        msg: Binary,
+    },
+    UpdateAdmin {
+        contract: CanonicalAddr,
+        admin: CanonicalAddr,
+    },
+    BankSend {
+        to_address: CanonicalAddr,
+        amount: Vec<Coin>,
+    },
+    Cw20Transfer {
+        token: CanonicalAddr,
+        recipient: CanonicalAddr,
+        amount: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutionResult {
+    pub order: u64,
+    pub error: Option<String>,
+}
+
 impl Ord for ExecuteData {
     fn cmp(&self, other: &Self) -> Ordering {
         self.order.cmp(&other.order)
@@ -91,27 +170,27 @@ impl PartialEq for ExecuteData {
     }
 }
 
-pub fn config_store(storage: &mut dyn Storage) -> Singleton<Config> {
+pub fn config_store(storage: &mut dyn Storage) -> Singleton<'_, Config> {
     singleton(storage, KEY_CONFIG)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<Config> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Config> {
     singleton_read(storage, KEY_CONFIG)
 }
 
-pub fn state_store(storage: &mut dyn Storage) -> Singleton<State> {
+pub fn state_store(storage: &mut dyn Storage) -> Singleton<'_, State> {
     singleton(storage, KEY_STATE)
 }
 
-pub fn state_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn state_read(storage: &dyn Storage) -> ReadonlySingleton<'_, State> {
     singleton_read(storage, KEY_STATE)
 }
 
-pub fn poll_store(storage: &mut dyn Storage) -> Bucket<Poll> {
+pub fn poll_store(storage: &mut dyn Storage) -> Bucket<'_, Poll> {
     bucket(storage, PREFIX_POLL)
 }
 
-pub fn poll_read(storage: &dyn Storage) -> ReadonlyBucket<Poll> {
+pub fn poll_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Poll> {
     bucket_read(storage, PREFIX_POLL)
 }
 
@@ -125,11 +204,11 @@ pub fn poll_indexer_store<'a>(
     )
 }
 
-pub fn poll_voter_store(storage: &mut dyn Storage, poll_id: u64) -> Bucket<VoterInfo> {
+pub fn poll_voter_store(storage: &mut dyn Storage, poll_id: u64) -> Bucket<'_, VoterInfo> {
     Bucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
 }
 
-pub fn poll_voter_read(storage: &dyn Storage, poll_id: u64) -> ReadonlyBucket<VoterInfo> {
+pub fn poll_voter_read(storage: &dyn Storage, poll_id: u64) -> ReadonlyBucket<'_, VoterInfo> {
     ReadonlyBucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
 }
 
@@ -1,18 +1,24 @@
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
-use crate::state::{config_read, poll_voter_read, state_read, Config, State};
+use crate::state::{
+    config_read, poll_indexer_store, poll_read, poll_store, poll_voter_read, state_read, Config,
+    ExecuteAction, ExecuteData, Poll, State,
+};
 
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, Api, CanonicalAddr, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, from_slice, to_binary, to_vec, Addr, Api, BankMsg, Binary,
+    CanonicalAddr, ContractResult, CosmosMsg, Decimal, Deps, DepsMut, Env, Reply, Response,
+    StdError, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw900::common::OrderBy;
 use cw900::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
-    PollStatus, PollsResponse, QueryMsg, VoteOption, VoterInfo, VotersResponse, VotersResponseItem,
+    ConfigResponse, Cw20HookMsg, DepositDestination, ExecuteMsg, ExecutionResultResponse,
+    ExecutionResultsResponse, InstantiateMsg, MigrateMsg, PollAction, PollExecuteMsg, PollResponse,
+    PollStatus, PollsResponse, QueryMsg, StateResponse, VoteOption, VoterInfo, VotersResponse,
+    VotersResponseItem,
 };
 
 const VOTING_TOKEN: &str = "voting_token";
@@ -92,6 +98,7 @@ fn proper_initialization() {
             glow_token: CanonicalAddr::from(vec![]),
             ve_token: CanonicalAddr::from(vec![]),
             terraswap_factory: CanonicalAddr::from(vec![]),
+            terraswap_router: None,
             owner: deps.api.addr_canonicalize(TEST_CREATOR).unwrap(),
             quorum: Decimal::percent(DEFAULT_QUORUM),
             threshold: Decimal::percent(DEFAULT_THRESHOLD),
@@ -99,7 +106,11 @@ fn proper_initialization() {
             timelock_period: DEFAULT_TIMELOCK_PERIOD,
             expiration_period: DEFAULT_EXPIRATION_PERIOD,
             proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            snapshot_period: DEFAULT_FIX_PERIOD
+            snapshot_period: DEFAULT_FIX_PERIOD,
+            deposit_destination: None,
+            fee_distributor: None,
+            veto_threshold: None,
+            max_spread: Decimal::percent(1),
         }
     );
 
@@ -123,6 +134,7 @@ fn proper_initialization() {
             poll_count: 0,
             total_deposit: Uint128::zero(),
             total_share: Uint128::zero(),
+            total_slashed: Uint128::zero(),
         }
     );
 }
@@ -381,7 +393,7 @@ fn happy_days_create_poll() {
     let execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_create_poll_result(
         1,
-        env.block.height + DEFAULT_VOTING_PERIOD,
+        env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -414,18 +426,27 @@ fn query_polls() {
     let execute_msgs: Vec<PollExecuteMsg> = vec![
         PollExecuteMsg {
             order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 3u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz3,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz3,
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz2,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz2,
+                funds: vec![],
+            },
         },
     ];
 
@@ -460,7 +481,8 @@ fn query_polls() {
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
                 start_time: 10000u64,
-                end_height: 10000u64,
+                end_height: None,
+                end_time: 20000u64,
                 title: "test".to_string(),
                 description: "test".to_string(),
                 link: Some("http://google.com".to_string()),
@@ -468,6 +490,7 @@ fn query_polls() {
                 execute_data: Some(execute_msgs.clone()),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
                 staked_amount: Some(Uint128::zero()),
                 total_balance_at_end_poll: None,
             },
@@ -476,7 +499,8 @@ fn query_polls() {
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
                 start_time: 10000u64,
-                end_height: 10000u64,
+                end_height: None,
+                end_time: 20000u64,
                 title: "test2".to_string(),
                 description: "test2".to_string(),
                 link: None,
@@ -484,6 +508,7 @@ fn query_polls() {
                 execute_data: None,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
                 staked_amount: Some(Uint128::zero()),
                 total_balance_at_end_poll: None,
             },
@@ -509,7 +534,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end_height: None,
+            end_time: 20000u64,
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -517,6 +543,7 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
         },]
@@ -541,7 +568,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end_height: None,
+            end_time: 20000u64,
             title: "test".to_string(),
             description: "test".to_string(),
             link: Some("http://google.com".to_string()),
@@ -549,6 +577,7 @@ fn query_polls() {
             execute_data: Some(execute_msgs),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
         }]
@@ -573,7 +602,8 @@ fn query_polls() {
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
             start_time: 10000u64,
-            end_height: 10000u64,
+            end_height: None,
+            end_time: 20000u64,
             title: "test2".to_string(),
             description: "test2".to_string(),
             link: None,
@@ -581,6 +611,7 @@ fn query_polls() {
             execute_data: None,
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
             staked_amount: Some(Uint128::zero()),
             total_balance_at_end_poll: None,
         },]
@@ -601,6 +632,204 @@ fn query_polls() {
     assert_eq!(response.polls, vec![]);
 }
 
+#[test]
+fn fails_create_poll_invalid_action() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let invalid_actions = vec![
+        PollAction::BankSend {
+            to_address: TEST_VOTER.to_string(),
+            amount: vec![],
+        },
+        PollAction::BankSend {
+            to_address: TEST_VOTER.to_string(),
+            amount: coins(0, "uusd"),
+        },
+        PollAction::WasmExecute {
+            contract: VOTING_TOKEN.to_string(),
+            msg: Binary::default(),
+            funds: coins(0, "uusd"),
+        },
+        PollAction::Cw20Transfer {
+            token: VOTING_TOKEN.to_string(),
+            recipient: TEST_VOTER.to_string(),
+            amount: Uint128::zero(),
+        },
+    ];
+
+    for action in invalid_actions {
+        let msg = create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            None,
+            Some(vec![PollExecuteMsg { order: 1, action }]),
+        );
+        let info = mock_info(VOTING_TOKEN, &[]);
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidPollAction {}) => (),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+}
+
+#[test]
+fn execute_poll_actions() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(123),
+    })
+    .unwrap();
+    let execute_msgs = vec![
+        PollExecuteMsg {
+            order: 1,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz.clone(),
+                funds: coins(100, "uusd"),
+            },
+        },
+        PollExecuteMsg {
+            order: 2,
+            action: PollAction::WasmMigrate {
+                contract: VE_TOKEN.to_string(),
+                new_code_id: 7,
+                msg: exec_msg_bz.clone(),
+            },
+        },
+        PollExecuteMsg {
+            order: 3,
+            action: PollAction::UpdateAdmin {
+                contract: VE_TOKEN.to_string(),
+                admin: TEST_CREATOR.to_string(),
+            },
+        },
+        PollExecuteMsg {
+            order: 4,
+            action: PollAction::BankSend {
+                to_address: TEST_VOTER.to_string(),
+                amount: coins(200, "uusd"),
+            },
+        },
+        PollExecuteMsg {
+            order: 5,
+            action: PollAction::Cw20Transfer {
+                token: VOTING_TOKEN.to_string(),
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::new(300),
+            },
+        },
+    ];
+
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs.clone()),
+    );
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.execute_data, Some(execute_msgs));
+
+    // Pass the poll
+    let mut poll: Poll = poll_read(&deps.storage).load(&1u64.to_be_bytes()).unwrap();
+    poll.status = PollStatus::Passed;
+    poll_store(&mut deps.storage)
+        .save(&1u64.to_be_bytes(), &poll)
+        .unwrap();
+    poll_indexer_store(&mut deps.storage, &PollStatus::InProgress).remove(&1u64.to_be_bytes());
+    poll_indexer_store(&mut deps.storage, &PollStatus::Passed)
+        .save(&1u64.to_be_bytes(), &true)
+        .unwrap();
+
+    let env = mock_env_height(0, poll.end_time + DEFAULT_TIMELOCK_PERIOD);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz.clone(),
+                    funds: coins(100, "uusd"),
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VE_TOKEN.to_string(),
+                    new_code_id: 7,
+                    msg: exec_msg_bz,
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+                    contract_addr: VE_TOKEN.to_string(),
+                    admin: TEST_CREATOR.to_string(),
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: TEST_VOTER.to_string(),
+                    amount: coins(200, "uusd"),
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: TEST_VOTER.to_string(),
+                        amount: Uint128::new(300),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                1
+            ),
+        ]
+    );
+}
+
+#[test]
+fn read_legacy_execute_data() {
+    let contract = CanonicalAddr::from(vec![1, 2, 3]);
+    let msg = Binary::from(b"{}".to_vec());
+
+    let legacy = format!(
+        r#"{{"order":1,"contract":"{}","msg":"{}"}}"#,
+        Binary::from(contract.as_slice()).to_base64(),
+        msg.to_base64()
+    );
+    let execute_data: ExecuteData = from_slice(legacy.as_bytes()).unwrap();
+    assert_eq!(execute_data.order, 1);
+    assert_eq!(
+        execute_data.action,
+        ExecuteAction::WasmExecute {
+            contract,
+            msg,
+            funds: vec![],
+        }
+    );
+
+    // Execute data is written with its action
+    let execute_data: ExecuteData = from_slice(&to_vec(&execute_data).unwrap()).unwrap();
+    assert_eq!(execute_data.order, 1);
+}
+
 #[test]
 fn create_poll_no_quorum() {
     let mut deps = mock_dependencies(&[]);
@@ -615,7 +844,7 @@ fn create_poll_no_quorum() {
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_create_poll_result(
         1,
-        DEFAULT_VOTING_PERIOD,
+        10000 + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -623,7 +852,7 @@ fn create_poll_no_quorum() {
 }
 
 #[test]
-fn fails_end_poll_before_end_height() {
+fn fails_end_poll_before_end_time() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
@@ -635,7 +864,7 @@ fn fails_end_poll_before_end_height() {
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_create_poll_result(
         1,
-        DEFAULT_VOTING_PERIOD,
+        10000 + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -643,7 +872,7 @@ fn fails_end_poll_before_end_height() {
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let value: PollResponse = from_binary(&res).unwrap();
-    assert_eq!(DEFAULT_VOTING_PERIOD, value.end_height);
+    assert_eq!(10000 + DEFAULT_VOTING_PERIOD, value.end_time);
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
     let env = mock_env_height(0, 10000);
@@ -672,15 +901,12 @@ fn happy_days_end_poll() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER.to_string(),
-                &Uint128::from((stake_amount) as u128),
-            )],
+            &[(&TEST_VOTER.to_string(), &Uint128::from(stake_amount))],
         ),
     ]);
 
@@ -706,18 +932,27 @@ fn happy_days_end_poll() {
     let execute_msgs: Vec<PollExecuteMsg> = vec![
         PollExecuteMsg {
             order: 3u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz3.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz3.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz2.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz2.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz.clone(),
+                funds: vec![],
+            },
         },
     ];
 
@@ -738,7 +973,7 @@ fn happy_days_end_poll() {
 
     assert_create_poll_result(
         1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        creator_env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -786,7 +1021,7 @@ fn happy_days_end_poll() {
     }
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
     let execute_res = execute(
@@ -824,7 +1059,7 @@ fn happy_days_end_poll() {
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(stake_amount as u128),
+            &Uint128::from(stake_amount),
         )],
     )]);
 
@@ -842,27 +1077,36 @@ fn happy_days_end_poll() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
     let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
     let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            }))
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                1
+            )
         ]
     );
     assert_eq!(
@@ -957,15 +1201,12 @@ fn expire_poll() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER.to_string(),
-                &Uint128::from((stake_amount) as u128),
-            )],
+            &[(&TEST_VOTER.to_string(), &Uint128::from(stake_amount))],
         ),
     ]);
 
@@ -978,8 +1219,11 @@ fn expire_poll() {
     .unwrap();
     let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
         order: 1u64,
-        contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz,
+        action: PollAction::WasmExecute {
+            contract: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz,
+            funds: vec![],
+        },
     }];
     let msg = create_poll_msg(
         "test".to_string(),
@@ -998,7 +1242,7 @@ fn expire_poll() {
 
     assert_create_poll_result(
         1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        creator_env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1032,7 +1276,7 @@ fn expire_poll() {
     );
 
     // Poll is not in passed status
-    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
     let msg = ExecuteMsg::ExpirePoll { poll_id: 1 };
     let execute_res = execute(
         deps.as_mut(),
@@ -1089,7 +1333,10 @@ fn expire_poll() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    creator_env.block.height += DEFAULT_EXPIRATION_PERIOD;
+    creator_env.block.time = creator_env
+        .block
+        .time
+        .plus_seconds(DEFAULT_EXPIRATION_PERIOD);
     let msg = ExecuteMsg::ExpirePoll { poll_id: 1 };
     let _execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
 
@@ -1122,11 +1369,14 @@ fn end_poll_zero_quorum() {
 
     let execute_msgs: Vec<PollExecuteMsg> = vec![PollExecuteMsg {
         order: 1u64,
-        contract: VOTING_TOKEN.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: Uint128::new(123),
-        })
-        .unwrap(),
+        action: PollAction::WasmExecute {
+            contract: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(123),
+            })
+            .unwrap(),
+            funds: vec![],
+        },
     }];
 
     let msg = create_poll_msg(
@@ -1145,7 +1395,7 @@ fn end_poll_zero_quorum() {
     .unwrap();
     assert_create_poll_result(
         1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        creator_env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1157,7 +1407,7 @@ fn end_poll_zero_quorum() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
@@ -1171,7 +1421,7 @@ fn end_poll_zero_quorum() {
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
 
     let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
 
@@ -1232,81 +1482,70 @@ fn end_poll_zero_quorum() {
 }
 
 #[test]
-fn end_poll_quorum_rejected() {
-    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+fn fails_update_config_fee_distributor_not_set() {
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
-    let mut creator_env = mock_env();
-    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(
-        deps.as_mut(),
-        creator_env.clone(),
-        creator_info.clone(),
-        msg,
-    )
-    .unwrap();
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "create_poll"),
-            attr("creator", TEST_CREATOR),
-            attr("poll_id", "1"),
-            attr("end_height", "22345"),
-        ]
-    );
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: Some(DepositDestination::FeeDistributor),
+        fee_distributor: None,
+        veto_threshold: None,
+        remove_veto_threshold: None,
+        max_spread: None,
+    };
 
-    let stake_amount = 100;
-    deps.querier.with_token_balances(&[
-        (
-            &VOTING_TOKEN.to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
-            )],
-        ),
-        (
-            &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER.to_string(),
-                &Uint128::from((stake_amount) as u128),
-            )],
-        ),
-    ]);
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::FeeDistributorNotSet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
 
-    assert_stake_tokens_result(
-        stake_amount,
-        DEFAULT_PROPOSAL_DEPOSIT,
-        stake_amount,
-        1,
-        deps.as_ref(),
-    );
+#[test]
+fn end_poll_quorum_rejected_burns_deposit() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
 
-    let msg = ExecuteMsg::CastVote {
-        poll_id: 1,
-        vote: VoteOption::Yes,
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: Some(DepositDestination::Burn),
+        fee_distributor: None,
+        veto_threshold: None,
+        remove_veto_threshold: None,
+        max_spread: None,
     };
-    let info = mock_info(TEST_VOTER, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", "1"),
-            attr("amount", "100"),
-            attr("voter", TEST_VOTER),
-            attr("vote_option", "yes"),
-        ]
-    );
+    let mut creator_env = mock_env();
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
 
+    // Nobody votes, so the quorum is not reached
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
-
-    creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
-
-    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env, info, msg).unwrap();
     assert_eq!(
         execute_res.attributes,
         vec![
@@ -1316,15 +1555,296 @@ fn end_poll_quorum_rejected() {
             attr("passed", "false"),
         ]
     );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+    assert_eq!(state.total_slashed, Uint128::from(DEFAULT_PROPOSAL_DEPOSIT));
 }
 
 #[test]
-fn end_poll_quorum_rejected_noting_staked() {
-    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+fn end_poll_vetoed_sends_deposit_to_fee_distributor() {
+    let voter1_stake = 100u128;
+    let voter2_stake = 1000u128;
+    let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
 
-    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: Some(DepositDestination::FeeDistributor),
+        fee_distributor: Some("fee_distributor".to_string()),
+        veto_threshold: Some(Decimal::percent(80)),
+        remove_veto_threshold: None,
+        max_spread: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[
+            (&TEST_VOTER.to_string(), &Uint128::from(voter1_stake)),
+            (&TEST_VOTER_2.to_string(), &Uint128::from(voter2_stake)),
+        ],
+    )]);
+
+    let mut creator_env = mock_env();
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    for (voter, vote) in [
+        (TEST_VOTER, VoteOption::Yes),
+        (TEST_VOTER_2, VoteOption::No),
+    ] {
+        let info = mock_info(voter, &[]);
+        let msg = ExecuteMsg::CastVote { poll_id: 1, vote };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // More than 80% of the votes are against the poll
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Vetoed"),
+            attr("passed", "false"),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "fee_distributor".to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_slashed, Uint128::from(DEFAULT_PROPOSAL_DEPOSIT));
+}
+
+#[test]
+fn end_poll_passed_is_not_vetoed() {
+    let voter1_stake = 600u128;
+    let voter2_stake = 400u128;
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let update_veto_msg = |veto_threshold: Decimal| ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        timelock_period: None,
+        expiration_period: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: None,
+        fee_distributor: None,
+        veto_threshold: Some(veto_threshold),
+        remove_veto_threshold: None,
+        max_spread: None,
+    };
+
+    // A veto threshold of 33% would veto polls which pass the 50% threshold
+    let info = mock_info(TEST_CREATOR, &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_veto_msg(Decimal::percent(33)),
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "veto_threshold must be 1 - threshold to 1")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_veto_msg(Decimal::percent(50)),
+    )
+    .unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VE_TOKEN.to_string(),
+        &[
+            (&TEST_VOTER.to_string(), &Uint128::from(voter1_stake)),
+            (&TEST_VOTER_2.to_string(), &Uint128::from(voter2_stake)),
+        ],
+    )]);
+
+    let mut creator_env = mock_env();
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), creator_env.clone(), info, msg).unwrap();
+
+    for (voter, vote) in [
+        (TEST_VOTER, VoteOption::Yes),
+        (TEST_VOTER_2, VoteOption::No),
+    ] {
+        let info = mock_info(voter, &[]);
+        let msg = ExecuteMsg::CastVote { poll_id: 1, vote };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // 40% of the votes are against the poll, which passes and refunds the deposit
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let info = mock_info(TEST_CREATOR, &[]);
+    let execute_res = execute(deps.as_mut(), creator_env, info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_slashed, Uint128::zero());
+}
+
+#[test]
+fn end_poll_quorum_rejected() {
+    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", TEST_CREATOR),
+            attr("poll_id", "1"),
+            attr("end_time", "1571807419"),
+        ]
+    );
+
+    let stake_amount = 100;
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(stake_amount))],
+        ),
+    ]);
+
+    assert_stake_tokens_result(
+        stake_amount,
+        DEFAULT_PROPOSAL_DEPOSIT,
+        stake_amount,
+        1,
+        deps.as_ref(),
+    );
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let info = mock_info(TEST_VOTER, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+        ]
+    );
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn end_poll_quorum_rejected_noting_staked() {
+    let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
     let mut creator_env = mock_env();
     let mut creator_info = mock_info(VOTING_TOKEN, &[]);
     let execute_res = execute(
@@ -1340,14 +1860,14 @@ fn end_poll_quorum_rejected_noting_staked() {
             attr("action", "create_poll"),
             attr("creator", TEST_CREATOR),
             attr("poll_id", "1"),
-            attr("end_height", "22345"),
+            attr("end_time", "1571807419"),
         ]
     );
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
 
     let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
     assert_eq!(
@@ -1381,15 +1901,12 @@ fn end_poll_nay_rejected() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER_2.to_string(),
-                &Uint128::from((voter2_stake) as u128),
-            )],
+            &[(&TEST_VOTER_2.to_string(), &Uint128::from(voter2_stake))],
         ),
     ]);
 
@@ -1411,7 +1928,7 @@ fn end_poll_nay_rejected() {
             attr("action", "create_poll"),
             attr("creator", TEST_CREATOR),
             attr("poll_id", "1"),
-            attr("end_height", "22345"),
+            attr("end_time", "1571807419"),
         ]
     );
 
@@ -1426,7 +1943,7 @@ fn end_poll_nay_rejected() {
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
     let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
     assert_eq!(
         execute_res.attributes,
@@ -1452,7 +1969,7 @@ fn happy_days_cast_vote() {
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_create_poll_result(
         1,
-        DEFAULT_VOTING_PERIOD,
+        10000 + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1463,7 +1980,7 @@ fn happy_days_cast_vote() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
@@ -1531,7 +2048,143 @@ fn happy_days_cast_vote() {
 }
 
 #[test]
-fn fails_cast_vote_twice() {
+fn end_poll_abstain_counts_toward_quorum() {
+    let voter1_stake = 100;
+    let voter2_stake = 1000;
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VE_TOKEN.to_string(),
+            &[
+                (&TEST_VOTER.to_string(), &Uint128::from(voter1_stake)),
+                (&TEST_VOTER_2.to_string(), &Uint128::from(voter2_stake)),
+            ],
+        ),
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+    ]);
+
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, voter1_stake, 1, VoteOption::Yes, execute_res);
+
+    let info = mock_info(TEST_VOTER_2, &[]);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Abstain,
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_cast_vote_success(
+        TEST_VOTER_2,
+        voter2_stake,
+        1,
+        VoteOption::Abstain,
+        execute_res,
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::from(voter1_stake));
+    assert_eq!(value.no_votes, Uint128::zero());
+    assert_eq!(value.abstain_votes, Uint128::from(voter2_stake));
+
+    // The abstain vote reaches the quorum, and the threshold only counts the yes vote
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+}
+
+#[test]
+fn end_poll_only_abstain_rejected() {
+    let voter1_stake = 100u128;
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    deps.querier.with_token_balances(&[
+        (
+            &VE_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(voter1_stake))],
+        ),
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        ),
+    ]);
+
+    let mut creator_env = mock_env();
+    let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Abstain,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    creator_info.sender = Addr::unchecked(TEST_CREATOR);
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+}
+
+#[test]
+fn change_cast_vote() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_register_contracts(deps.as_mut());
@@ -1544,7 +2197,7 @@ fn fails_cast_vote_twice() {
 
     assert_create_poll_result(
         1,
-        env.block.height + DEFAULT_VOTING_PERIOD,
+        env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1555,7 +2208,7 @@ fn fails_cast_vote_twice() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
@@ -1575,15 +2228,67 @@ fn fails_cast_vote_twice() {
     let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::Yes, execute_res);
 
+    // Change the vote to no
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+    };
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::No, execute_res);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::zero());
+    assert_eq!(value.no_votes, Uint128::from(11u128));
+    assert_eq!(value.abstain_votes, Uint128::zero());
+
+    // Change the vote to abstain
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Abstain,
+    };
+    let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 11u128, 1, VoteOption::Abstain, execute_res);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(value.yes_votes, Uint128::zero());
+    assert_eq!(value.no_votes, Uint128::zero());
+    assert_eq!(value.abstain_votes, Uint128::from(11u128));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1u64,
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: TEST_VOTER.to_string(),
+            vote: VoteOption::Abstain,
+            balance: Uint128::from(11u128),
+        }]
+    );
+
+    // The vote can't be changed once the voting period is over
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
     };
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD + 1);
     let res = execute(deps.as_mut(), env, info, msg);
 
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(ContractError::AlreadyVoted {}) => (),
+        Err(ContractError::PollNotInProgress {}) => (),
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -1615,7 +2320,7 @@ fn share_calculation() {}
 // helper to confirm the expected create_poll response
 fn assert_create_poll_result(
     poll_id: u64,
-    end_height: u64,
+    end_time: u64,
     creator: &str,
     execute_res: Response,
     deps: Deps,
@@ -1626,7 +2331,7 @@ fn assert_create_poll_result(
             attr("action", "create_poll"),
             attr("creator", creator),
             attr("poll_id", poll_id.to_string()),
-            attr("end_height", end_height.to_string()),
+            attr("end_time", end_time.to_string()),
         ]
     );
 
@@ -1638,7 +2343,8 @@ fn assert_create_poll_result(
             contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
             poll_count: 1,
             total_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
-            total_share: Uint128::zero()
+            total_share: Uint128::zero(),
+            total_slashed: Uint128::zero()
         }
     );
 }
@@ -1657,7 +2363,8 @@ fn assert_stake_tokens_result(
             contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
             poll_count,
             total_deposit: Uint128::from(total_deposit),
-            total_share: Uint128::zero()
+            total_share: Uint128::zero(),
+            total_slashed: Uint128::zero()
         }
     );
 }
@@ -1698,6 +2405,12 @@ fn update_config() {
         expiration_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: None,
+        fee_distributor: None,
+        veto_threshold: None,
+        remove_veto_threshold: None,
+        max_spread: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1724,6 +2437,12 @@ fn update_config() {
         expiration_period: Some(30000u64),
         proposal_deposit: Some(Uint128::from(123u128)),
         snapshot_period: Some(11),
+        terraswap_router: Some("terraswap_router".to_string()),
+        deposit_destination: Some(DepositDestination::FeeDistributor),
+        fee_distributor: Some("fee_distributor".to_string()),
+        veto_threshold: Some(Decimal::percent(33)),
+        remove_veto_threshold: None,
+        max_spread: Some(Decimal::percent(2)),
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1740,6 +2459,91 @@ fn update_config() {
     assert_eq!(30000u64, config.expiration_period);
     assert_eq!(123u128, config.proposal_deposit.u128());
     assert_eq!(11u64, config.snapshot_period);
+    assert_eq!(
+        Some("terraswap_router".to_string()),
+        config.terraswap_router
+    );
+    assert_eq!(
+        Some(DepositDestination::FeeDistributor),
+        config.deposit_destination
+    );
+    assert_eq!(Some("fee_distributor".to_string()), config.fee_distributor);
+    assert_eq!(Some(Decimal::percent(33)), config.veto_threshold);
+    assert_eq!(Decimal::percent(2), config.max_spread);
+
+    // The veto threshold can't be below 1 - threshold, including after a threshold change
+    let update_veto_msg = |threshold: Option<Decimal>,
+                           veto_threshold: Option<Decimal>,
+                           remove_veto_threshold: Option<bool>| {
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            quorum: None,
+            threshold,
+            voting_period: None,
+            timelock_period: None,
+            expiration_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            terraswap_router: None,
+            deposit_destination: None,
+            fee_distributor: None,
+            veto_threshold,
+            remove_veto_threshold,
+            max_spread: None,
+        }
+    };
+    for msg in [
+        update_veto_msg(None, Some(Decimal::percent(20)), None),
+        update_veto_msg(None, Some(Decimal::percent(101)), None),
+        update_veto_msg(Some(Decimal::percent(60)), None, None),
+    ] {
+        let info = mock_info("addr0001", &[]);
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+                assert_eq!(msg, "veto_threshold must be 1 - threshold to 1")
+            }
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+    let info = mock_info("addr0001", &[]);
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_veto_msg(None, Some(Decimal::percent(50)), Some(true)),
+    ) {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "veto_threshold can't be set and removed at once")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Removing the veto threshold allows any threshold
+    let info = mock_info("addr0001", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_veto_msg(Some(Decimal::percent(60)), None, Some(true)),
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Decimal::percent(60), config.threshold);
+    assert_eq!(None, config.veto_threshold);
+
+    // Only the owner can sweep without a belief_price
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::Sweep {
+        denom: "uusd".to_string(),
+        max_spread: None,
+        belief_price: None,
+        intermediate_asset: None,
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::BeliefPriceRequired {}) => (),
+        _ => panic!("Must return belief price required error"),
+    }
 
     // Unauthorzied err
     let info = mock_info(TEST_CREATOR, &[]);
@@ -1752,6 +2556,12 @@ fn update_config() {
         expiration_period: None,
         proposal_deposit: None,
         snapshot_period: None,
+        terraswap_router: None,
+        deposit_destination: None,
+        fee_distributor: None,
+        veto_threshold: None,
+        remove_veto_threshold: None,
+        max_spread: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -1788,18 +2598,27 @@ fn add_several_execute_msgs() {
     let execute_msgs: Vec<PollExecuteMsg> = vec![
         PollExecuteMsg {
             order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 3u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz3,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz3,
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz2,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz2,
+                funds: vec![],
+            },
         },
     ];
 
@@ -1813,7 +2632,7 @@ fn add_several_execute_msgs() {
     let execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_create_poll_result(
         1,
-        env.block.height + DEFAULT_VOTING_PERIOD,
+        env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1842,15 +2661,12 @@ fn execute_poll_with_order() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER.to_string(),
-                &Uint128::from((stake_amount) as u128),
-            )],
+            &[(&TEST_VOTER.to_string(), &Uint128::from(stake_amount))],
         ),
     ]);
 
@@ -1884,28 +2700,43 @@ fn execute_poll_with_order() {
     let execute_msgs: Vec<PollExecuteMsg> = vec![
         PollExecuteMsg {
             order: 3u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz3.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz3.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 4u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz4.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz4.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz2.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz2.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 5u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz5.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz5.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz.clone(),
+                funds: vec![],
+            },
         },
     ];
 
@@ -1926,7 +2757,7 @@ fn execute_poll_with_order() {
 
     assert_create_poll_result(
         1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        creator_env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -1960,7 +2791,7 @@ fn execute_poll_with_order() {
     );
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += DEFAULT_VOTING_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
 
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
     let execute_res = execute(
@@ -1998,47 +2829,142 @@ fn execute_poll_with_order() {
         &VOTING_TOKEN.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(stake_amount as u128),
+            &Uint128::from(stake_amount),
         )],
     )]);
 
-    creator_env.block.height += DEFAULT_TIMELOCK_PERIOD;
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
     let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
     let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz4,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz5,
-                funds: vec![],
-            })),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz4,
+                    funds: vec![],
+                }),
+                1
+            ),
+            SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz5,
+                    funds: vec![],
+                }),
+                1
+            ),
         ]
     );
     assert_eq!(
         execute_res.attributes,
         vec![attr("action", "execute_poll"), attr("poll_id", "1"),]
     );
+
+    // Every message replies in execution order, and the second one fails
+    let success = ContractResult::Ok(SubMsgExecutionResponse {
+        events: vec![],
+        data: None,
+    });
+    for (order, result) in [
+        (1u64, success.clone()),
+        (2u64, ContractResult::Err("burn failed".to_string())),
+        (3u64, success),
+    ] {
+        let execute_res = reply(deps.as_mut(), mock_env(), Reply { id: 1, result }).unwrap();
+        assert_eq!(
+            execute_res.attributes,
+            vec![
+                attr("action", "execute_poll_msg"),
+                attr("poll_id", "1"),
+                attr("order", order.to_string()),
+                attr("success", (order != 2).to_string()),
+            ]
+        );
+    }
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ExecutionResults { poll_id: 1 },
+    )
+    .unwrap();
+    let response: ExecutionResultsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.status, PollStatus::Failed);
+    assert_eq!(
+        response.results,
+        vec![
+            ExecutionResultResponse {
+                order: 1,
+                success: true,
+                error: None,
+            },
+            ExecutionResultResponse {
+                order: 2,
+                success: false,
+                error: Some("burn failed".to_string()),
+            },
+            ExecutionResultResponse {
+                order: 3,
+                success: true,
+                error: None,
+            },
+        ]
+    );
+
+    // The poll moves from the executed polls to the failed polls
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Executed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Failed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
 }
 
 #[test]
@@ -2056,15 +2982,12 @@ fn happy_days_end_poll_with_controlled_quorum() {
             &VOTING_TOKEN.to_string(),
             &[(
                 &MOCK_CONTRACT_ADDR.to_string(),
-                &Uint128::from((DEFAULT_PROPOSAL_DEPOSIT) as u128),
+                &Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             )],
         ),
         (
             &VE_TOKEN.to_string(),
-            &[(
-                &TEST_VOTER.to_string(),
-                &Uint128::from((stake_amount) as u128),
-            )],
+            &[(&TEST_VOTER.to_string(), &Uint128::from(stake_amount))],
         ),
     ]);
 
@@ -2080,13 +3003,19 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let execute_msgs: Vec<PollExecuteMsg> = vec![
         PollExecuteMsg {
             order: 1u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz.clone(),
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz.clone(),
+                funds: vec![],
+            },
         },
         PollExecuteMsg {
             order: 2u64,
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz,
+            action: PollAction::WasmExecute {
+                contract: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            },
         },
     ];
 
@@ -2107,7 +3036,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
 
     assert_create_poll_result(
         1,
-        creator_env.block.height + DEFAULT_VOTING_PERIOD,
+        creator_env.block.time.seconds() + DEFAULT_VOTING_PERIOD,
         TEST_CREATOR,
         execute_res,
         deps.as_ref(),
@@ -2140,21 +3069,21 @@ fn happy_days_end_poll_with_controlled_quorum() {
         ]
     );
 
-    creator_env.block.height += DEFAULT_VOTING_PERIOD - 10;
+    creator_env.block.time = creator_env
+        .block
+        .time
+        .plus_seconds(DEFAULT_VOTING_PERIOD - 10);
 
     deps.querier.with_token_balances(&[(
         &VE_TOKEN.to_string(),
-        &[(
-            &TEST_VOTER_2.to_string(),
-            &Uint128::from((8 * stake_amount) as u128),
-        )],
+        &[(&TEST_VOTER_2.to_string(), &Uint128::from(8 * stake_amount))],
     )]);
 
     let msg = ExecuteMsg::CastVote {
         poll_id: 1,
         vote: VoteOption::Yes,
     };
-    let env = mock_env_height(creator_env.block.height, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, creator_env.block.time.seconds());
     let info = mock_info(TEST_VOTER_2, &[]);
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -2170,7 +3099,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
     );
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
-    creator_env.block.height += 10;
+    creator_env.block.time = creator_env.block.time.plus_seconds(10);
 
     // quorum must reach
     let msg = ExecuteMsg::EndPoll { poll_id: 1 };
@@ -2219,3 +3148,96 @@ fn happy_days_end_poll_with_controlled_quorum() {
 
     // assert_eq!(actual_staked_weight.u128(), (10 * stake_amount))
 }
+
+#[test]
+fn migrate_polls_to_end_time() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_register_contracts(deps.as_mut());
+
+    let env = mock_env_height(0, 10000);
+    for _ in 0..4 {
+        let info = mock_info(VOTING_TOKEN, &[]);
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    // Polls 1, 2 and 4 were created with an end height before the migration
+    for (poll_id, end_height) in [(1u64, 900u64), (2u64, 1100u64), (4u64, 800u64)] {
+        let mut poll: Poll = poll_read(&deps.storage)
+            .load(&poll_id.to_be_bytes())
+            .unwrap();
+        poll.end_height = Some(end_height);
+        poll.end_time = 0;
+        poll_store(&mut deps.storage)
+            .save(&poll_id.to_be_bytes(), &poll)
+            .unwrap();
+    }
+
+    // Poll 4 was already rejected, so it is left untouched
+    let mut poll: Poll = poll_read(&deps.storage).load(&4u64.to_be_bytes()).unwrap();
+    poll.status = PollStatus::Rejected;
+    poll_store(&mut deps.storage)
+        .save(&4u64.to_be_bytes(), &poll)
+        .unwrap();
+    poll_indexer_store(&mut deps.storage, &PollStatus::InProgress).remove(&4u64.to_be_bytes());
+    poll_indexer_store(&mut deps.storage, &PollStatus::Rejected)
+        .save(&4u64.to_be_bytes(), &true)
+        .unwrap();
+
+    // The block time is required to convert the end heights
+    let env = mock_env_height(1000, 50000);
+    let msg = MigrateMsg {
+        voting_period: Some(7 * 86400),
+        timelock_period: None,
+        expiration_period: Some(2 * 86400),
+        block_time: Decimal::zero(),
+    };
+    match migrate(deps.as_mut(), env.clone(), msg.clone()) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "block_time must be positive"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Periods which aren't given are converted from blocks to seconds
+    migrate(
+        deps.as_mut(),
+        env,
+        MigrateMsg {
+            block_time: Decimal::from_ratio(13u64, 2u64),
+            ..msg
+        },
+    )
+    .unwrap();
+
+    let config: Config = config_read(&deps.storage).load().unwrap();
+    assert_eq!(config.voting_period, 7 * 86400);
+    assert_eq!(config.timelock_period, DEFAULT_TIMELOCK_PERIOD * 13 / 2);
+    assert_eq!(config.expiration_period, 2 * 86400);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: None,
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Asc),
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    let end_times: Vec<(Option<u64>, u64)> = response
+        .polls
+        .iter()
+        .map(|poll| (poll.end_height, poll.end_time))
+        .collect();
+    assert_eq!(
+        end_times,
+        vec![
+            (Some(900), 50000 - 650),
+            (Some(1100), 50000 + 650),
+            (None, 10000 + DEFAULT_VOTING_PERIOD),
+            (Some(800), 0),
+        ]
+    );
+}
@@ -0,0 +1,216 @@
+use core::fmt;
+use std::convert::TryFrom;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+use cosmwasm_std::{Decimal256, Fraction, Uint128, Uint256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::ve_token::DecayModel;
+
+/// Coefficients of a quadratic function of time, used to represent voting power curves.
+/// Voting power curves can be summed by summing their coefficients,
+/// which makes it possible to track a total without iterating over every lock.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuadraticEquationCoefficients {
+    pub quad_coefficient: Decimal256,
+    pub linear_coefficient: Decimal256,
+    pub constant_coefficient: Decimal256,
+}
+
+impl QuadraticEquationCoefficients {
+    // The following function specifies the coefficients
+    // of the quadratic function specifying the voting power for a given locked balance
+
+    // The formula is:
+    // voting_power = remaining_locked_amount * remaining_lock_time / voting_power_constant_divisor
+    // where remaining_locked_amount = deposited_amount * remaining_lock_time / (end_lock_time - start_lock_time)
+
+    // But we wait until evaluating the quadratic coefficients to divide by voting_power_constant_divisor
+    // This is to increase the sig figs of the quadratic coefficients
+    // Also, rla is calculated as da * rlt / (elt - slt) instead of da - da * (t - slt) / (elt - slt)
+    // as is done in the locked_amount function, but this is fine for a voting power calculation.
+
+    // i.e.
+    // vp = rla * rlt
+    // rla = da * rlt / (elt - slt)
+    // => vp = da / (elt - slt) * (elt - t)^2
+    // = da / (elt - slt) * t^2
+    // - 2 * elt * da / (elt - slt) * t
+    // + elt^2 * da / (elt - slt)
+
+    // Notice that we can also express rla as a linear function:
+    // and that
+    // - the linear coefficient of this function is the negative quadratic coefficient for vp
+    // - the constant coefficient of this function is the negative linear coefficient over two for vp
+    // This means we can calculate the corresponding locked amount without storing more coefficients separately!
+
+    // rla is da * rlt / (elt - slt)
+    // da * (elt - t) / (elt - slt)
+    // da * elt / (elt - slt)
+    // - da / (elt - slt) * t
+
+    // With the cliff unlock decay model, rla is da until the lock expires, so
+    // vp = da * (elt - t)
+    // = da * elt
+    // - da * t
+
+    /// Return the coefficients of the voting power curve of a lock.
+    /// end_lock_time must be greater than start_lock_time.
+    pub fn for_lock(
+        deposited_amount: Uint128,
+        start_lock_time: u64,
+        end_lock_time: u64,
+        decay_model: DecayModel,
+    ) -> Self {
+        if decay_model == DecayModel::CliffUnlock {
+            return QuadraticEquationCoefficients {
+                constant_coefficient: Decimal256::from_ratio(
+                    Uint128::from(end_lock_time) * deposited_amount,
+                    1u128,
+                ),
+                linear_coefficient: Decimal256::from_ratio(deposited_amount, 1u128),
+                quad_coefficient: Decimal256::zero(),
+            };
+        }
+
+        // Denominator is always positive
+        let initial_lock_duration = Uint128::from(end_lock_time - start_lock_time);
+
+        // First do all multiplications, then divisions
+        QuadraticEquationCoefficients {
+            constant_coefficient: Decimal256::from_ratio(
+                Uint128::from(end_lock_time) * Uint128::from(end_lock_time) * deposited_amount,
+                initial_lock_duration,
+            ),
+            linear_coefficient: Decimal256::from_ratio(
+                Uint128::from(2 * end_lock_time) * deposited_amount,
+                initial_lock_duration,
+            ),
+            quad_coefficient: Decimal256::from_ratio(deposited_amount, initial_lock_duration),
+        }
+    }
+
+    /// Scale the curve by numerator / denominator, such as a share of voting power
+    pub fn multiply_ratio(&self, numerator: u64, denominator: u64) -> Self {
+        let scale = |coefficient: Decimal256| {
+            Decimal256::from_ratio(
+                coefficient.numerator() * Uint256::from(numerator),
+                coefficient.denominator() * Uint256::from(denominator),
+            )
+        };
+
+        QuadraticEquationCoefficients {
+            quad_coefficient: scale(self.quad_coefficient),
+            linear_coefficient: scale(self.linear_coefficient),
+            constant_coefficient: scale(self.constant_coefficient),
+        }
+    }
+
+    pub fn evaluate_voting_power_at_timestamp(
+        &self,
+        timestamp: u64,
+        voting_power_constant_divisor: u64,
+    ) -> Uint128 {
+        Uint128::try_from(
+            ((
+                // Floor
+                Uint256::from(1u128) * self.constant_coefficient
+                // Floor
+                + Uint256::from(timestamp) * Uint256::from(timestamp) * self.quad_coefficient)
+                // Subtracts a truncated value value
+                .checked_sub(Uint256::from(timestamp) * self.linear_coefficient))
+            // In the event of an underflow
+            // which can happen because of truncation
+            // default to 0
+            .unwrap_or_default()
+            // Scales everything down by voting_power_constant_divisor
+                / Uint256::from(voting_power_constant_divisor),
+        )
+        .unwrap()
+    }
+
+    // Notice that we can also express rla as a linear function and that:
+    // - the linear coefficient of this function is the negative quadratic coefficient for vp
+    // - the constant coefficient of this function is the negative linear coefficient over two for vp
+    // This means we can calculate the corresponding locked amount without storing more coefficients separately!
+    // With the cliff unlock decay model, the whole deposit stays locked until the end lock time,
+    // so the locked amount is simply the linear coefficient.
+    pub fn evaluate_locked_balance_at_timestamp(
+        &self,
+        timestamp: u64,
+        decay_model: DecayModel,
+    ) -> Uint128 {
+        if decay_model == DecayModel::CliffUnlock {
+            // Floor
+            return Uint128::try_from(Uint256::from(1u128) * self.linear_coefficient).unwrap();
+        }
+
+        Uint128::try_from(
+            // Floor
+            (Uint256::from(1u128) * self.linear_coefficient / Uint256::from(2u128))
+                // Subtracts a truncated value value
+                .checked_sub(Uint256::from(timestamp) * self.quad_coefficient)
+                // In the event of an underflow
+                // which can happen because of truncation
+                // default to 0
+                .unwrap_or_default(),
+        )
+        .unwrap()
+    }
+}
+
+// Implement Display in order to make testing easier.
+impl fmt::Display for QuadraticEquationCoefficients {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Quad_coefficient: {}, Linear_coefficient: {}, Constant_coefficient: {}",
+            self.quad_coefficient, self.linear_coefficient, self.constant_coefficient
+        )
+    }
+}
+
+impl Add for QuadraticEquationCoefficients {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            quad_coefficient: self.quad_coefficient + other.quad_coefficient,
+            linear_coefficient: self.linear_coefficient + other.linear_coefficient,
+            constant_coefficient: self.constant_coefficient + other.constant_coefficient,
+        }
+    }
+}
+
+impl AddAssign for QuadraticEquationCoefficients {
+    fn add_assign(&mut self, other: Self) {
+        *self = Self {
+            quad_coefficient: self.quad_coefficient + other.quad_coefficient,
+            linear_coefficient: self.linear_coefficient + other.linear_coefficient,
+            constant_coefficient: self.constant_coefficient + other.constant_coefficient,
+        }
+    }
+}
+
+impl Sub for QuadraticEquationCoefficients {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            quad_coefficient: self.quad_coefficient - other.quad_coefficient,
+            linear_coefficient: self.linear_coefficient - other.linear_coefficient,
+            constant_coefficient: self.constant_coefficient - other.constant_coefficient,
+        }
+    }
+}
+
+impl SubAssign for QuadraticEquationCoefficients {
+    fn sub_assign(&mut self, other: Self) {
+        *self = Self {
+            quad_coefficient: self.quad_coefficient - other.quad_coefficient,
+            linear_coefficient: self.linear_coefficient - other.linear_coefficient,
+            constant_coefficient: self.constant_coefficient - other.constant_coefficient,
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use terraswap::asset::{Asset, AssetInfo};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {}
@@ -14,15 +15,59 @@ pub enum ExecuteMsg {
         terraswap_factory: String,
     },
     /// Public Message
+    /// Sweep all given denom balance to GLOW token
     Sweep {
         denom: String,
+        /// Maximum spread of the swap, relative to the belief_price when it is given.
+        /// It can't exceed the max_spread of the config.
+        max_spread: Option<Decimal>,
+        /// Price of GLOW in the denom expected by the sweeper. Only the owner can omit it,
+        /// and it is required to route through an intermediate asset.
+        belief_price: Option<Decimal>,
+        /// Asset to route through when there is no denom/GLOW Terraswap pair
+        intermediate_asset: Option<AssetInfo>,
     },
-    Claim {
-        limit: Option<u32>,
-    },
+    /// Claim the sender's share of every distribution asset
+    Claim { limit: Option<u32> },
+    /// Claim like Claim, but add the claimed GLOW to one of the sender's ve locks.
+    /// Other assets are sent to the sender.
+    ClaimAndLock { lock_id: u64, limit: Option<u32> },
+    /// Claim on behalf of a user who has allowed it through AllowClaimFor.
+    /// The claimed assets are always sent to the user.
+    ClaimFor { address: String, limit: Option<u32> },
+    /// Allow or disallow anyone to claim on behalf of the sender
+    AllowClaimFor { allowed: bool },
+    /// Distribute the contract's unreserved balance of GLOW
+    /// over the configured distribution_weeks
     DistributeGlow {},
+    /// Distribute the contract's unreserved balance of a registered asset
+    /// over the configured distribution_weeks
+    Distribute { asset_info: AssetInfo },
+    /// Spread the contract's unreserved balance of a registered asset evenly
+    /// across the given number of weeks, starting with the current week.
+    /// Only the owner can spread distributions.
+    DistributeOverWeeks { asset_info: AssetInfo, weeks: u64 },
+    /// Register a CW20 or native asset which can be distributed
+    RegisterDistributionAsset { asset_info: AssetInfo },
+    /// Allow a native denom to be swept. Only the owner can allow denoms.
+    AddSweepableDenom { denom: String },
+    /// Stop a native denom from being swept
+    RemoveSweepableDenom { denom: String },
+    /// Withdraw an asset which isn't reserved for distributions, such as a CW20 sent by mistake
+    RescueToken { asset: Asset, recipient: String },
+    /// Recover the distributions of a past week in which nothing was staked.
+    /// They are sent to the treasury if one is set, and otherwise distributed in the current week.
+    RecoverOrphanedWeek { week: u64 },
     UpdateConfig {
         owner: Option<String>,
+        treasury: Option<String>,
+        terraswap_router: Option<String>,
+        /// Pause or unpause distributions and claims
+        paused: Option<bool>,
+        /// Maximum spread accepted by sweeps
+        max_spread: Option<Decimal>,
+        /// Number of weeks over which Distribute and DistributeGlow spread distributions
+        distribution_weeks: Option<u64>,
     },
 }
 
@@ -40,6 +85,24 @@ pub enum QueryMsg {
         fee_limit: Option<u32>,
         fee_start_after: Option<u64>,
     },
+    /// Breakdown of a staker's share of the distributions of every past week
+    StakerClaims {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Distributions of an asset from the current week onwards, which can't be claimed yet
+    ScheduledDistributions {
+        asset_info: AssetInfo,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Past weeks with distributions which can never be claimed because nothing was staked
+    OrphanedWeeks {
+        /// The next_start_after of the previous page
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -48,18 +111,87 @@ pub struct ConfigResponse {
     pub glow_token: String,
     pub ve_token: String,
     pub terraswap_factory: String,
+    pub terraswap_router: Option<String>,
+    pub treasury: Option<String>,
+    pub paused: bool,
+    pub max_spread: Decimal,
+    pub distribution_weeks: u64,
+    pub distribution_assets: Vec<AssetInfo>,
+    pub sweepable_denoms: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub contract_addr: String,
-    pub total_distributed_unclaimed_fees: Uint128,
+    /// Distributed amount of each asset which hasn't been claimed yet
+    pub total_distributed_unclaimed_fees: Vec<Asset>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct StakerResponse {
     pub balance: Uint128,
+    /// Whether anyone can claim on behalf of the staker
+    pub claim_for_allowed: bool,
+    pub claimable_fees: Vec<ClaimableFeesResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ClaimableFeesResponse {
+    pub asset_info: AssetInfo,
     pub initial_last_claimed_fee_timestamp: u64,
     pub last_claimed_fee_timestamp: u64,
     pub claimable_fees_lower_bound: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakerClaimsResponse {
+    pub claims: Vec<WeeklyClaimResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WeeklyClaimResponse {
+    pub week_timestamp: u64,
+    /// Voting balances at the start of the week
+    pub user_voting_balance: Uint128,
+    pub total_voting_balance: Uint128,
+    /// user_voting_balance / total_voting_balance
+    pub share: Decimal,
+    pub distributions: Vec<WeeklyClaimDistributionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WeeklyClaimDistributionResponse {
+    pub asset_info: AssetInfo,
+    pub distributed_amount: Uint128,
+    /// The staker's share of the distributed amount
+    pub claim_amount: Uint128,
+    /// Whether the staker has already claimed the distribution
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WeeklyDistributionResponse {
+    pub week_timestamp: u64,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ScheduledDistributionsResponse {
+    pub distributions: Vec<WeeklyDistributionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct OrphanedWeekResponse {
+    pub week_timestamp: u64,
+    pub distributions: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct OrphanedWeeksResponse {
+    pub weeks: Vec<OrphanedWeekResponse>,
+    /// The week to pass as start_after to read the next page.
+    /// Pages are cut short after a bounded number of weeks has been read, so a page
+    /// may hold fewer than `limit` weeks even when more remain.
+    /// None once every week has been read.
+    pub next_start_after: Option<u64>,
+}
@@ -0,0 +1,101 @@
+use cosmwasm_std::{Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Allocations are expressed in basis points of a staker's voting power
+pub const MAX_WEIGHT_BPS: u16 = 10_000;
+
+/// Minimum time in seconds between two votes of a staker for the same gauge
+pub const WEIGHT_VOTE_DELAY: u64 = 10 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub ve_token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Register a gauge which veGLOW holders can direct emissions to
+    AddGauge { gauge: String },
+    /// Allocate basis points of the sender's voting power to a gauge,
+    /// replacing the sender's previous allocation to that gauge.
+    /// The allocation follows the sender's locks as they were at the time of the vote.
+    /// A staker can vote for each gauge at most once every WEIGHT_VOTE_DELAY seconds.
+    VoteForGaugeWeight { gauge: String, weight_bps: u16 },
+    /// Propose a new owner, who must accept the proposal to become the owner
+    ProposeNewOwner { new_owner: String },
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Accept a pending ownership proposal
+    AcceptOwnership {},
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    Gauges {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Weight of a gauge as of the start of the week containing the timestamp
+    GaugeWeight {
+        gauge: String,
+        timestamp: Option<u64>,
+    },
+    /// Sum of the weights of all gauges as of the start of the week containing the timestamp
+    TotalWeight {
+        timestamp: Option<u64>,
+    },
+    UserVotes {
+        address: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
+    pub ve_token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugesResponse {
+    pub gauges: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GaugeWeightResponse {
+    /// Start of the week the weight was evaluated at
+    pub week_timestamp: u64,
+    pub weight: Uint128,
+    /// Share of the total weight held by the gauge
+    pub relative_weight: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalWeightResponse {
+    /// Start of the week the weight was evaluated at
+    pub week_timestamp: u64,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserVoteResponse {
+    pub gauge: String,
+    pub weight_bps: u16,
+    /// Time of the vote
+    pub timestamp: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserVotesResponse {
+    /// Basis points of voting power allocated across all gauges
+    pub power_used_bps: u16,
+    pub votes: Vec<UserVoteResponse>,
+}
@@ -1,8 +1,9 @@
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use cosmwasm_std::{Binary, Coin, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use terraswap::asset::AssetInfo;
 
 use crate::common::OrderBy;
 
@@ -10,8 +11,11 @@ use crate::common::OrderBy;
 pub struct InstantiateMsg {
     pub quorum: Decimal,
     pub threshold: Decimal,
+    /// Voting period in seconds
     pub voting_period: u64,
+    /// Seconds after the end of voting before a passed poll can be executed
     pub timelock_period: u64,
+    /// Seconds after the end of voting before a passed poll can be expired
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
@@ -30,6 +34,14 @@ pub enum ExecuteMsg {
     /// Sweep all given denom balance to GLOW token
     Sweep {
         denom: String,
+        /// Maximum spread of the swap, relative to the belief_price when it is given.
+        /// It can't exceed the max_spread of the config.
+        max_spread: Option<Decimal>,
+        /// Price of GLOW in the denom expected by the sweeper. Only the owner can omit it,
+        /// and it is required to route through an intermediate asset.
+        belief_price: Option<Decimal>,
+        /// Asset to route through when there is no denom/GLOW Terraswap pair
+        intermediate_asset: Option<AssetInfo>,
     },
     UpdateConfig {
         owner: Option<String>,
@@ -40,6 +52,15 @@ pub enum ExecuteMsg {
         expiration_period: Option<u64>,
         proposal_deposit: Option<Uint128>,
         snapshot_period: Option<u64>,
+        terraswap_router: Option<String>,
+        deposit_destination: Option<DepositDestination>,
+        fee_distributor: Option<String>,
+        /// Share of no votes above which a poll is vetoed, from 1 - threshold to 1
+        veto_threshold: Option<Decimal>,
+        /// Disable vetoes
+        remove_veto_threshold: Option<bool>,
+        /// Maximum spread accepted by sweeps
+        max_spread: Option<Decimal>,
     },
     CastVote {
         poll_id: u64,
@@ -72,14 +93,54 @@ pub enum Cw20HookMsg {
 #[serde(rename_all = "snake_case")]
 pub struct PollExecuteMsg {
     pub order: u64,
-    pub contract: String,
-    pub msg: Binary,
+    pub action: PollAction,
 }
 
-/// We currently take no arguments for migrations
+/// Action of a passed poll, executed by the gov contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollAction {
+    /// Execute a contract, sending it native funds held by the gov contract
+    WasmExecute {
+        contract: String,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+    /// Migrate a contract administered by the gov contract
+    WasmMigrate {
+        contract: String,
+        new_code_id: u64,
+        msg: Binary,
+    },
+    /// Change the admin of a contract administered by the gov contract
+    UpdateAdmin { contract: String, admin: String },
+    /// Send native funds held by the gov contract
+    BankSend {
+        to_address: String,
+        amount: Vec<Coin>,
+    },
+    /// Transfer CW20 tokens held by the gov contract
+    Cw20Transfer {
+        token: String,
+        recipient: String,
+        amount: Uint128,
+    },
+}
+
+/// Migrates the poll lifecycle from block heights to seconds
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
-    pub ve_token: String,
+    /// Voting period in seconds. The current period is converted with block_time when omitted.
+    pub voting_period: Option<u64>,
+    /// Seconds after the end of voting before a passed poll can be executed.
+    /// The current period is converted with block_time when omitted.
+    pub timelock_period: Option<u64>,
+    /// Seconds after the end of voting before a passed poll can be expired.
+    /// The current period is converted with block_time when omitted.
+    pub expiration_period: Option<u64>,
+    /// Average block time in seconds, used to convert the end height of in progress and
+    /// passed polls to an end time
+    pub block_time: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -102,6 +163,9 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    ExecutionResults {
+        poll_id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -109,6 +173,7 @@ pub struct ConfigResponse {
     pub owner: String,
     pub glow_token: String,
     pub terraswap_factory: String,
+    pub terraswap_router: Option<String>,
     pub quorum: Decimal,
     pub threshold: Decimal,
     pub voting_period: u64,
@@ -116,6 +181,10 @@ pub struct ConfigResponse {
     pub expiration_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub deposit_destination: Option<DepositDestination>,
+    pub fee_distributor: Option<String>,
+    pub veto_threshold: Option<Decimal>,
+    pub max_spread: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
@@ -123,6 +192,17 @@ pub struct StateResponse {
     pub poll_count: u64,
     pub total_share: Uint128,
     pub total_deposit: Uint128,
+    /// Total amount of deposits which were not refunded
+    pub total_slashed: Uint128,
+}
+
+/// Where the deposits which are not refunded are sent. They stay in the gov contract
+/// when no destination is set.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DepositDestination {
+    Burn,
+    FeeDistributor,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -131,14 +211,17 @@ pub struct PollResponse {
     pub creator: String,
     pub status: PollStatus,
     pub start_time: u64,
-    pub end_height: u64,
+    /// Only set for polls created before the migration to an end time
+    pub end_height: Option<u64>,
+    pub end_time: u64,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
     pub deposit_amount: Uint128,
     pub execute_data: Option<Vec<PollExecuteMsg>>,
-    pub yes_votes: Uint128, // balance
-    pub no_votes: Uint128,  // balance
+    pub yes_votes: Uint128,     // balance
+    pub no_votes: Uint128,      // balance
+    pub abstain_votes: Uint128, // balance
     pub staked_amount: Option<Uint128>,
     pub total_balance_at_end_poll: Option<Uint128>,
 }
@@ -148,6 +231,20 @@ pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ExecutionResultResponse {
+    pub order: u64,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ExecutionResultsResponse {
+    pub status: PollStatus,
+    /// Results of the messages which have been executed, in execution order
+    pub results: Vec<ExecutionResultResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct PollCountResponse {
     pub poll_count: u64,
@@ -179,6 +276,8 @@ pub enum PollStatus {
     Rejected,
     Executed,
     Expired,
+    /// Executed, but at least one of the messages failed
+    Failed,
 }
 
 impl fmt::Display for PollStatus {
@@ -192,14 +291,15 @@ impl fmt::Display for PollStatus {
 pub enum VoteOption {
     Yes,
     No,
+    Abstain,
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if *self == VoteOption::Yes {
-            write!(f, "yes")
-        } else {
-            write!(f, "no")
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+            VoteOption::Abstain => write!(f, "abstain"),
         }
     }
 }
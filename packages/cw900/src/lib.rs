@@ -1,7 +1,10 @@
 pub mod common;
+pub mod curve;
 pub mod fee_distributor;
+pub mod gauge_controller;
 pub mod gov;
 pub mod querier;
+pub mod sweep;
 pub mod ve_token;
 
 #[cfg(test)]
@@ -1,9 +1,12 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, Uint128,
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use terraswap::asset::{AssetInfo, PairInfo};
 
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -25,6 +28,19 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     tax_querier: TaxQuerier,
+    terraswap_querier: TerraswapQuerier,
+}
+
+#[derive(Clone, Default)]
+pub struct TerraswapQuerier {
+    // pair contract of each pair of assets
+    pairs: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Pair { asset_infos: [AssetInfo; 2] },
 }
 
 #[derive(Clone, Default)]
@@ -95,6 +111,23 @@ impl WasmMockQuerier {
                     panic!("DO NOT ENTER HERE")
                 }
             }
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. }) => match from_binary(msg) {
+                Ok(QueryMsg::Pair { asset_infos }) => {
+                    let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
+                    match self.terraswap_querier.pairs.get(&key) {
+                        Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
+                            contract_addr: v.to_string(),
+                            liquidity_token: "liquidity".to_string(),
+                            asset_infos,
+                        }))),
+                        None => SystemResult::Err(SystemError::InvalidRequest {
+                            error: "No pair info exists".to_string(),
+                            request: msg.as_slice().into(),
+                        }),
+                    }
+                }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
             _ => self.base.handle_query(request),
         }
     }
@@ -105,6 +138,7 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             tax_querier: TaxQuerier::default(),
+            terraswap_querier: TerraswapQuerier::default(),
         }
     }
 
@@ -112,4 +146,13 @@ impl WasmMockQuerier {
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
+
+    // configure the terraswap pairs, keyed by the concatenation of their assets
+    pub fn with_terraswap_pairs(&mut self, pairs: &[(&String, &String)]) {
+        for (assets, pair) in pairs.iter() {
+            self.terraswap_querier
+                .pairs
+                .insert(assets.to_string(), pair.to_string());
+        }
+    }
 }
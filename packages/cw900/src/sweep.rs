@@ -0,0 +1,124 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, Decimal, QuerierWrapper, StdError, StdResult, Uint128,
+    WasmMsg,
+};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
+use terraswap::querier::query_pair_info;
+use terraswap::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
+
+/// Maximum spread of sweeps until the owner configures one
+pub fn default_max_spread() -> Decimal {
+    Decimal::percent(1)
+}
+
+/// validate_max_spread returns an error if the spread is above 100%
+pub fn validate_max_spread(max_spread: Decimal) -> StdResult<()> {
+    if max_spread > Decimal::one() {
+        Err(StdError::generic_err("max_spread must be 0 to 1"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Build the message which swaps a native coin to GLOW.
+///
+/// The swap never accepts more than `max_spread_limit`, the maximum spread configured by the
+/// owner; a `max_spread` given by the sweeper can only tighten it.
+///
+/// The coin is swapped through the denom/GLOW Terraswap pair when it exists,
+/// which enforces the spread and belief_price itself.
+///
+/// Otherwise the coin is swapped through the Terraswap router, from the denom to the
+/// intermediate asset and from the intermediate asset to GLOW. The router enforces a minimum
+/// return of the spread below the return expected from `belief_price`, which is then required.
+#[allow(clippy::too_many_arguments)]
+pub fn sweep_msg(
+    querier: &QuerierWrapper,
+    terraswap_factory: &Addr,
+    terraswap_router: Option<&Addr>,
+    glow_token: &Addr,
+    offer_coin: Coin,
+    max_spread: Option<Decimal>,
+    max_spread_limit: Decimal,
+    belief_price: Option<Decimal>,
+    intermediate_asset: Option<AssetInfo>,
+) -> StdResult<CosmosMsg> {
+    validate_max_spread(max_spread_limit)?;
+    let max_spread = match max_spread {
+        Some(max_spread) => {
+            validate_max_spread(max_spread)?;
+            max_spread.min(max_spread_limit)
+        }
+        None => max_spread_limit,
+    };
+
+    if belief_price == Some(Decimal::zero()) {
+        return Err(StdError::generic_err("belief_price must be positive"));
+    }
+
+    let offer_asset_info = AssetInfo::NativeToken {
+        denom: offer_coin.denom.clone(),
+    };
+    let glow_asset_info = AssetInfo::Token {
+        contract_addr: glow_token.to_string(),
+    };
+
+    let pair_info = query_pair_info(
+        querier,
+        terraswap_factory.clone(),
+        &[offer_asset_info.clone(), glow_asset_info.clone()],
+    );
+
+    // Swap through the direct pair when it exists
+    let intermediate_asset = match (pair_info, intermediate_asset) {
+        (Ok(pair_info), _) => {
+            return Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_info.contract_addr,
+                msg: to_binary(&TerraswapExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: offer_asset_info,
+                        amount: offer_coin.amount,
+                    },
+                    max_spread: Some(max_spread),
+                    belief_price,
+                    to: None,
+                })?,
+                funds: vec![offer_coin],
+            }))
+        }
+        (Err(err), None) => return Err(err),
+        (Err(_), Some(intermediate_asset)) => intermediate_asset,
+    };
+
+    let terraswap_router =
+        terraswap_router.ok_or_else(|| StdError::generic_err("Terraswap router is not set"))?;
+
+    let operations = vec![
+        SwapOperation::TerraSwap {
+            offer_asset_info,
+            ask_asset_info: intermediate_asset.clone(),
+        },
+        SwapOperation::TerraSwap {
+            offer_asset_info: intermediate_asset,
+            ask_asset_info: glow_asset_info,
+        },
+    ];
+
+    let belief_price = belief_price.ok_or_else(|| {
+        StdError::generic_err("belief_price is required to swap through the router")
+    })?;
+    let minimum_receive = Uint256::from(offer_coin.amount) / Decimal256::from(belief_price)
+        * (Decimal256::one() - Decimal256::from(max_spread));
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: terraswap_router.to_string(),
+        msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive: Some(Uint128::from(minimum_receive)),
+            to: None,
+        })?,
+        funds: vec![offer_coin],
+    }))
+}
@@ -1,8 +1,12 @@
 use crate::mock_querier::mock_dependencies;
 use crate::querier::{compute_tax, deduct_tax, query_tax_rate};
+use crate::sweep::sweep_msg;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{Coin, Decimal, Uint128};
+use cosmwasm_std::{to_binary, Addr, Coin, CosmosMsg, Decimal, StdError, Uint128, WasmMsg};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as TerraswapExecuteMsg;
+use terraswap::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
 
 #[test]
 fn tax_rate_querier() {
@@ -64,3 +68,203 @@ fn test_deduct_tax() {
         }
     );
 }
+
+#[test]
+fn test_sweep_msg_direct_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier
+        .with_terraswap_pairs(&[(&"uusdglow_token".to_string(), &"pair".to_string())]);
+
+    let msg = sweep_msg(
+        &deps.as_ref().querier,
+        &Addr::unchecked("terraswap_factory"),
+        None,
+        &Addr::unchecked("glow_token"),
+        Coin::new(1000u128, "uusd"),
+        Some(Decimal::percent(1)),
+        Decimal::percent(5),
+        Some(Decimal::percent(50)),
+        None,
+    )
+    .unwrap();
+
+    let swap_msg = |max_spread: Decimal, belief_price: Option<Decimal>| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair".to_string(),
+            msg: to_binary(&TerraswapExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+                max_spread: Some(max_spread),
+                belief_price,
+                to: None,
+            })
+            .unwrap(),
+            funds: vec![Coin::new(1000u128, "uusd")],
+        })
+    };
+    assert_eq!(
+        msg,
+        swap_msg(Decimal::percent(1), Some(Decimal::percent(50)))
+    );
+
+    // The configured max spread applies when the sweeper gives none or a looser one
+    for max_spread in [None, Some(Decimal::percent(10))] {
+        let msg = sweep_msg(
+            &deps.as_ref().querier,
+            &Addr::unchecked("terraswap_factory"),
+            None,
+            &Addr::unchecked("glow_token"),
+            Coin::new(1000u128, "uusd"),
+            max_spread,
+            Decimal::percent(5),
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(msg, swap_msg(Decimal::percent(5), None));
+    }
+
+    // Invalid spreads and prices are rejected
+    for (max_spread, max_spread_limit, belief_price, error) in [
+        (
+            Some(Decimal::percent(101)),
+            Decimal::percent(5),
+            None,
+            "max_spread must be 0 to 1",
+        ),
+        (
+            None,
+            Decimal::percent(101),
+            None,
+            "max_spread must be 0 to 1",
+        ),
+        (
+            None,
+            Decimal::percent(5),
+            Some(Decimal::zero()),
+            "belief_price must be positive",
+        ),
+    ] {
+        let res = sweep_msg(
+            &deps.as_ref().querier,
+            &Addr::unchecked("terraswap_factory"),
+            None,
+            &Addr::unchecked("glow_token"),
+            Coin::new(1000u128, "uusd"),
+            max_spread,
+            max_spread_limit,
+            belief_price,
+            None,
+        );
+        match res {
+            Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, error),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+#[test]
+fn test_sweep_msg_through_intermediate_asset() {
+    let deps = mock_dependencies(&[]);
+
+    let router = Addr::unchecked("terraswap_router");
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let operations = vec![
+        SwapOperation::TerraSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "ukrw".to_string(),
+            },
+            ask_asset_info: uusd.clone(),
+        },
+        SwapOperation::TerraSwap {
+            offer_asset_info: uusd.clone(),
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "glow_token".to_string(),
+            },
+        },
+    ];
+
+    // Without a direct pair, an intermediate asset is required
+    let res = sweep_msg(
+        &deps.as_ref().querier,
+        &Addr::unchecked("terraswap_factory"),
+        Some(&router),
+        &Addr::unchecked("glow_token"),
+        Coin::new(1000u128, "ukrw"),
+        None,
+        Decimal::percent(5),
+        None,
+        None,
+    );
+    assert!(res.is_err());
+
+    // The router requires at least max_spread below the return expected from belief_price
+    let msg = sweep_msg(
+        &deps.as_ref().querier,
+        &Addr::unchecked("terraswap_factory"),
+        Some(&router),
+        &Addr::unchecked("glow_token"),
+        Coin::new(1000u128, "ukrw"),
+        Some(Decimal::percent(10)),
+        Decimal::percent(20),
+        Some(Decimal::percent(50)),
+        Some(uusd.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: router.to_string(),
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive: Some(Uint128::from(1800u128)),
+                to: None,
+            })
+            .unwrap(),
+            funds: vec![Coin::new(1000u128, "ukrw")],
+        })
+    );
+
+    // Routing requires a belief_price to compute the minimum return from
+    let res = sweep_msg(
+        &deps.as_ref().querier,
+        &Addr::unchecked("terraswap_factory"),
+        Some(&router),
+        &Addr::unchecked("glow_token"),
+        Coin::new(1000u128, "ukrw"),
+        None,
+        Decimal::percent(20),
+        None,
+        Some(uusd.clone()),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "belief_price is required to swap through the router")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Routing requires the router to be set
+    let res = sweep_msg(
+        &deps.as_ref().querier,
+        &Addr::unchecked("terraswap_factory"),
+        None,
+        &Addr::unchecked("glow_token"),
+        Coin::new(1000u128, "ukrw"),
+        None,
+        Decimal::percent(5),
+        Some(Decimal::percent(50)),
+        Some(uusd),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Terraswap router is not set"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -1,18 +1,48 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {}
+/// Curve parameters default to weekly epochs, a 52 week maximum lock,
+/// a voting power constant divisor of 52 weeks, and the linear unlock decay model.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub seconds_per_week: Option<u64>,
+    pub max_weeks: Option<u64>,
+    pub voting_power_constant_divisor: Option<u64>,
+    pub decay_model: Option<DecayModel>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayModel {
+    /// The deposit unlocks linearly until the end lock time,
+    /// so voting power decays quadratically
+    LinearUnlock,
+    /// The deposit unlocks all at once at the end lock time,
+    /// and voting power decays linearly
+    CliffUnlock,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
-    Withdraw {},
-    Checkpoint {},
+    Withdraw {
+        lock_id: u64,
+    },
+    /// Withdraw the entire deposit of a lock before it expires.
+    /// A penalty proportional to the remaining lock time is taken from the locked amount.
+    EarlyWithdraw {
+        lock_id: u64,
+    },
+    /// Apply pending slope changes to the global state, processing at most `max_weeks` weeks.
+    /// Returns the number of weeks which remain to be processed in the `remaining_weeks` attribute.
+    Checkpoint {
+        max_weeks: Option<u64>,
+    },
     IncreaseEndLockTime {
+        lock_id: u64,
         // unlock_week specifies the week at which to unlock
         // in units of weeks since the epoch
         end_lock_time: u64,
@@ -20,6 +50,53 @@ pub enum ExecuteMsg {
     RegisterContracts {
         cw20_address: String,
     },
+    /// Update the config. The locked token can only be updated while nothing is deposited
+    UpdateConfig {
+        cw20_address: Option<String>,
+        penalty_recipient: Option<String>,
+        early_withdraw_penalty_rate: Option<Decimal>,
+        tokenless_production: Option<Decimal>,
+        gauge_controller: Option<String>,
+        fee_distributor: Option<String>,
+    },
+    /// Propose a new owner, who must accept the proposal to become the owner
+    ProposeNewOwner {
+        new_owner: String,
+    },
+    /// Cancel the pending ownership proposal
+    CancelOwnershipProposal {},
+    /// Accept a pending ownership proposal
+    AcceptOwnership {},
+    /// Move a lock to another address
+    TransferLock {
+        lock_id: u64,
+        recipient: String,
+    },
+    /// Combine two of the sender's locks into `lock_id`, voiding `other_lock_id`.
+    /// The merged lock ends at the later of the two end lock times.
+    MergeLock {
+        lock_id: u64,
+        other_lock_id: u64,
+    },
+    /// Delegate the voting power of the sender's lock to another address.
+    /// Delegation is not transitive, delegated voting power can't be delegated again.
+    Delegate {
+        to: String,
+    },
+    /// Reclaim the voting power of the sender's lock
+    Undelegate {},
+    /// Remove one of the sender's delegators, which can't delegate to the sender again
+    RemoveDelegator {
+        delegator: String,
+    },
+    /// Allow a contract to create, modify, and withdraw locks
+    AddToWhitelist {
+        contract_address: String,
+    },
+    /// Stop a previously whitelisted contract from creating, modifying, and withdrawing locks
+    RemoveFromWhitelist {
+        contract_address: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -32,7 +109,12 @@ pub enum Cw20HookMsg {
         // in units of weeks since the epoch
         end_lock_time: u64,
     },
-    IncreaseLockAmount {},
+    IncreaseLockAmount {
+        lock_id: u64,
+        /// Owner of the lock to increase, defaults to the sender.
+        /// Only the configured fee distributor can increase the lock of another staker.
+        address: Option<String>,
+    },
 }
 
 /// We currently take no arguments for migrations
@@ -46,19 +128,103 @@ pub enum QueryMsg {
     State {
         timestamp: Option<u64>,
     },
+    /// Aggregate balances across all of a staker's locks
     Staker {
         address: String,
         timestamp: Option<u64>,
     },
+    /// Balances of each of a staker's locks
+    StakerLocks {
+        address: String,
+        timestamp: Option<u64>,
+    },
+    Whitelist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Number of weeks of slope changes which haven't been applied to the global state
+    PendingCheckpoint {},
+    /// Voting power of the staker's own locks at each of the timestamps
+    StakerBalances {
+        address: String,
+        timestamps: Vec<u64>,
+    },
+    /// Total voting power at each of the timestamps
+    TotalBalances {
+        timestamps: Vec<u64>,
+    },
+    /// Boosted balance of a staker's deposit into an integrating contract, such as a prize pool.
+    /// `working_supply` is the total amount deposited into the integrating contract,
+    /// and `deposit` is the staker's own deposit.
+    Boost {
+        address: String,
+        working_supply: Uint128,
+        deposit: Uint128,
+        timestamp: Option<u64>,
+    },
+    /// Every update made to a staker's locks, oldest first
+    StakerHistory {
+        address: String,
+        /// The (lock_id, timestamp) of the last checkpoint of the previous page
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
+    /// Every lock of every staker, ordered by address and lock id
+    Stakers {
+        /// The next_start_after of the previous page
+        start_after: Option<(String, u64)>,
+        limit: Option<u32>,
+        timestamp: Option<u64>,
+        /// Only return locks in the given state
+        status: Option<LockStatus>,
+    },
+}
+
+/// The change made to a lock by an update
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LockAction {
+    CreateLock,
+    IncreaseLockAmount,
+    IncreaseEndLockTime,
+    Withdraw,
+    EarlyWithdraw,
+    /// The lock was transferred away and voided
+    TransferLock,
+    /// The lock was created by a transfer from another staker
+    ReceiveLock,
+    /// The lock was merged into another lock and voided, or another lock was merged into it
+    MergeLock,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LockStatus {
+    /// The lock's end_lock_time is in the future
+    Active,
+    /// The lock's end_lock_time is in the past or at the current timestamp
+    Expired,
+    /// The lock was fully withdrawn
+    Void,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
-    pub cw20_address: String,
+    pub pending_owner: Option<String>,
+    pub cw20_address: Option<String>,
+    pub penalty_recipient: Option<String>,
+    pub early_withdraw_penalty_rate: Decimal,
+    pub seconds_per_week: u64,
+    pub max_weeks: u64,
+    pub voting_power_constant_divisor: u64,
+    pub decay_model: DecayModel,
+    pub tokenless_production: Decimal,
+    pub gauge_controller: Option<String>,
+    pub fee_distributor: Option<String>,
 }
 
-#[derive(Default, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub total_deposited_amount: Uint128,
     pub total_locked_amount: Uint128,
@@ -67,7 +233,96 @@ pub struct StateResponse {
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct StakerResponse {
+    pub deposited_amount: Uint128,
+    pub locked_amount: Uint128,
+    /// Voting power of the staker's own lock
+    pub balance: Uint128,
+    /// Voting power delegated to the staker by other addresses
+    pub delegated_balance: Uint128,
+    /// The address the staker has delegated its own voting power to
+    pub delegate: Option<String>,
+    /// Voting power the staker can vote with.
+    /// Includes the delegated balance, and the staker's own balance unless it is delegated.
+    pub voting_power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct LockResponse {
+    pub lock_id: u64,
+    pub deposited_amount: Uint128,
+    pub locked_amount: Uint128,
+    pub balance: Uint128,
+    pub start_lock_time: u64,
+    pub end_lock_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakerLocksResponse {
+    pub locks: Vec<LockResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WhitelistResponse {
+    pub contracts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct BoostResponse {
+    /// The staker's deposit weighted by its boost
+    pub working_balance: Uint128,
+    /// Ratio of the working balance to the unboosted working balance.
+    /// Ranges from 1 to 1 / tokenless_production.
+    pub boost: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct BalancesResponse {
+    /// Balances in the order of the requested timestamps
+    pub balances: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PendingCheckpointResponse {
+    /// Time up to which the global state is up to date
+    pub state_timestamp: u64,
+    pub pending_weeks: u64,
+    /// Maximum number of pending weeks that user actions will process on their own.
+    /// User actions fail while more weeks than this are pending.
+    pub max_pending_weeks: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct LockCheckpointResponse {
+    pub lock_id: u64,
+    pub action: LockAction,
+    /// Time at which the update was made
+    pub timestamp: u64,
+    pub deposited_amount: Uint128,
+    pub start_lock_time: u64,
+    pub end_lock_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakerHistoryResponse {
+    pub history: Vec<LockCheckpointResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponseItem {
+    pub address: String,
+    pub lock_id: u64,
     pub deposited_amount: Uint128,
     pub locked_amount: Uint128,
     pub balance: Uint128,
+    pub end_lock_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponse {
+    pub stakers: Vec<StakersResponseItem>,
+    /// The (address, lock_id) to pass as start_after to read the next page.
+    /// Pages are cut short after a bounded number of locks has been read, so a page
+    /// may hold fewer than `limit` locks even when more remain.
+    /// None once every lock has been read.
+    pub next_start_after: Option<(String, u64)>,
 }